@@ -26,6 +26,7 @@ pub fn app() -> Html {
                         (50.0, 50.0),
                         NodeProperties {
                             label: "Foo".to_string(),
+                            ..Default::default()
                         },
                     ));
                 }
@@ -37,11 +38,12 @@ pub fn app() -> Html {
                     let a = nodes.get(a);
                     let b = nodes.get(b);
                     if let Some((a, b)) = a.zip(b) {
-                        graph.add_edge(
+                        let _ = graph.add_edge(
                             *a,
                             *b,
                             EdgeProperties {
-                                weight: (100.0 + random() * 500.0) as _,
+                                length: Some(100.0 + random() * 500.0),
+                                ..Default::default()
                             },
                         );
                     }
@@ -0,0 +1,241 @@
+//! Applying changes pushed by a live backend, e.g. received through a WebSocket or an agent.
+//!
+//! A backend identifies nodes by its own keys. The [`GraphBridge`] maps them to the nodes of
+//! the graph, and applies the queued messages with the next animation frame:
+//!
+//! ```no_run
+//! # use web_graph::bridge::{GraphBridge, GraphMessage};
+//! # use web_graph::graph::{GraphController, NodeProperties, NodeSize};
+//! # fn connect(graph: GraphController) {
+//! let handle = GraphBridge::<String>::new(graph).run();
+//! let sender = handle.sender();
+//! // e.g. for every message received from the backend
+//! sender.send(GraphMessage::Node {
+//!     key: "a".into(),
+//!     properties: NodeProperties::default(),
+//!     size: NodeSize::FitLabel,
+//!     position: None,
+//! });
+//! # }
+//! ```
+
+use crate::graph::{
+    EdgeProperties, Graph, GraphController, Node, NodeProperties, NodeSize, Position, RenderLoop,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem::take;
+use std::rc::{Rc, Weak};
+
+/// A change of the graph, referring to nodes by the keys of the backend.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphMessage<K> {
+    /// Add a node, or update an existing one.
+    Node {
+        key: K,
+        properties: NodeProperties,
+        size: NodeSize,
+        /// The position of the center, otherwise the layout places a new node and an existing
+        /// one stays where it is.
+        position: Option<Position>,
+    },
+    /// Remove a node, along with its edges.
+    RemoveNode(K),
+    /// Add an edge, or replace an existing one. Edges to unknown nodes are ignored.
+    Edge {
+        from: K,
+        to: K,
+        properties: EdgeProperties,
+    },
+    RemoveEdge {
+        from: K,
+        to: K,
+    },
+    /// Remove all nodes added through the bridge.
+    Clear,
+}
+
+type Queue<K> = RefCell<Vec<GraphMessage<K>>>;
+
+/// Queues messages for a [`GraphBridge`].
+///
+/// Senders can be cloned, e.g. to move one into the task receiving from the backend. Messages
+/// sent after the bridge was dropped are discarded.
+pub struct GraphSender<K> {
+    queue: Weak<Queue<K>>,
+}
+
+impl<K> Clone for GraphSender<K> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<K> GraphSender<K> {
+    pub fn send(&self, message: GraphMessage<K>) {
+        if let Some(queue) = self.queue.upgrade() {
+            queue.borrow_mut().push(message);
+        }
+    }
+}
+
+#[cfg(feature = "yew")]
+impl<K: 'static> From<GraphSender<K>> for yew::Callback<GraphMessage<K>> {
+    fn from(value: GraphSender<K>) -> Self {
+        yew::Callback::from(move |message| value.send(message))
+    }
+}
+
+/// Applies the messages of its senders to a graph.
+pub struct GraphBridge<K> {
+    graph: GraphController,
+    queue: Rc<Queue<K>>,
+    nodes: HashMap<K, Node>,
+}
+
+impl<K> GraphBridge<K>
+where
+    K: Clone + Eq + Hash + 'static,
+{
+    pub fn new(graph: GraphController) -> Self {
+        Self {
+            graph,
+            queue: Default::default(),
+            nodes: Default::default(),
+        }
+    }
+
+    pub fn sender(&self) -> GraphSender<K> {
+        GraphSender {
+            queue: Rc::downgrade(&self.queue),
+        }
+    }
+
+    /// The node of a key.
+    pub fn node(&self, key: &K) -> Option<Node> {
+        self.nodes.get(key).copied()
+    }
+
+    /// Apply the queued messages, which is done with every frame while running.
+    ///
+    /// While the graph is busy, the messages stay queued. Once it was shut down, they are
+    /// discarded.
+    pub fn apply(&mut self) {
+        if self.queue.borrow().is_empty() {
+            return;
+        }
+        if self.graph.is_stopped() {
+            self.queue.borrow_mut().clear();
+            self.nodes.clear();
+            return;
+        }
+
+        let queue = self.queue.clone();
+        let graph = self.graph.clone();
+        graph.with_graph(|graph| {
+            // nodes may have been removed by the user, or through the controller
+            self.nodes.retain(|_, node| graph.storage().contains(*node));
+            for message in take(&mut *queue.borrow_mut()) {
+                self.apply_message(graph, message);
+            }
+        });
+    }
+
+    fn apply_message(&mut self, graph: &mut Graph, message: GraphMessage<K>) {
+        match message {
+            GraphMessage::Node {
+                key,
+                properties,
+                size,
+                position,
+            } => match self.nodes.get(&key) {
+                Some(node) => {
+                    graph.set_properties(*node, properties);
+                    graph.set_size(*node, size);
+                    if let Some(position) = position {
+                        graph.set_center(*node, position);
+                    }
+                }
+                None => {
+                    let position = position.unwrap_or(Position { x: 0.0, y: 0.0 });
+                    let node = graph.add_node(position, size, properties);
+                    graph.set_center(node, position);
+                    self.nodes.insert(key, node);
+                }
+            },
+            GraphMessage::RemoveNode(key) => {
+                if let Some(node) = self.nodes.remove(&key) {
+                    graph.remove_node(node);
+                }
+            }
+            GraphMessage::Edge {
+                from,
+                to,
+                properties,
+            } => {
+                if let (Some(a), Some(b)) = (self.node(&from), self.node(&to)) {
+                    if let Err(err) = graph.add_edge(a, b, properties) {
+                        log::warn!("Failed to add an edge: {err}");
+                    }
+                }
+            }
+            GraphMessage::RemoveEdge { from, to } => {
+                if let (Some(a), Some(b)) = (self.node(&from), self.node(&to)) {
+                    graph.remove_edge(a, b);
+                }
+            }
+            GraphMessage::Clear => {
+                for (_, node) in self.nodes.drain() {
+                    graph.remove_node(node);
+                }
+            }
+        }
+    }
+
+    /// Start applying the messages with every frame, until the returned handle is dropped.
+    pub fn run(self) -> GraphBridgeHandle<K> {
+        let sender = self.sender();
+        let bridge = Rc::new(RefCell::new(self));
+
+        let render_loop = {
+            let bridge = bridge.clone();
+            RenderLoop::start(move |_| bridge.borrow_mut().apply())
+        };
+
+        GraphBridgeHandle {
+            render_loop,
+            bridge,
+            sender,
+        }
+    }
+}
+
+/// A running [`GraphBridge`], stopped when dropped.
+pub struct GraphBridgeHandle<K> {
+    render_loop: Rc<RenderLoop>,
+    bridge: Rc<RefCell<GraphBridge<K>>>,
+    sender: GraphSender<K>,
+}
+
+impl<K> GraphBridgeHandle<K>
+where
+    K: Clone + Eq + Hash + 'static,
+{
+    pub fn sender(&self) -> GraphSender<K> {
+        self.sender.clone()
+    }
+
+    /// The node of a key, once the message adding it was applied.
+    pub fn node(&self, key: &K) -> Option<Node> {
+        self.bridge.borrow().node(key)
+    }
+}
+
+impl<K> Drop for GraphBridgeHandle<K> {
+    fn drop(&mut self) {
+        self.render_loop.cancel();
+    }
+}
@@ -0,0 +1,206 @@
+//! Copying parts of a graph, to paste them into the same or another graph.
+
+use crate::graph::{Position, Rect, Size};
+use js_sys::{Array, Object, Reflect, JSON};
+use wasm_bindgen::JsValue;
+
+/// The value of the `type` field, identifying copied graphs.
+const FORMAT: &str = "web-graph/subgraph";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ParseSubgraphError {
+    #[error("invalid JSON")]
+    Json,
+    #[error("not a copied graph")]
+    Format,
+    #[error("invalid field: {0}")]
+    Field(&'static str),
+}
+
+/// Nodes and the edges between them, independent of any graph.
+///
+/// Nodes are referenced by their index, so that pasting them creates new nodes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Subgraph {
+    pub nodes: Vec<SubgraphNode>,
+    pub edges: Vec<SubgraphEdge>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubgraphNode {
+    pub label: String,
+    pub category: Option<String>,
+    /// The area covered by the node, in graph coordinates.
+    pub rect: Rect,
+    /// If the node was sized to fit its label.
+    pub fit_label: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubgraphEdge {
+    /// The index of the source node.
+    pub from: usize,
+    /// The index of the target node.
+    pub to: usize,
+    pub length: Option<f64>,
+    pub strength: f64,
+    pub category: Option<String>,
+    /// Points the edge passes through, ordered from source to target.
+    pub waypoints: Vec<Position>,
+}
+
+impl Subgraph {
+    /// Serialize to JSON, e.g. for the clipboard.
+    pub fn to_json(&self) -> String {
+        let nodes: Array = self
+            .nodes
+            .iter()
+            .map(|node| {
+                object([
+                    ("label", node.label.as_str().into()),
+                    ("category", optional(&node.category)),
+                    ("x", node.rect.position.x.into()),
+                    ("y", node.rect.position.y.into()),
+                    ("width", node.rect.size.width.into()),
+                    ("height", node.rect.size.height.into()),
+                    ("fitLabel", node.fit_label.into()),
+                ])
+            })
+            .collect();
+
+        let edges: Array = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let waypoints: Array = edge
+                    .waypoints
+                    .iter()
+                    .map(|p| JsValue::from(Array::of2(&p.x.into(), &p.y.into())))
+                    .collect();
+                object([
+                    ("from", (edge.from as f64).into()),
+                    ("to", (edge.to as f64).into()),
+                    ("length", edge.length.map_or(JsValue::NULL, JsValue::from)),
+                    ("strength", edge.strength.into()),
+                    ("category", optional(&edge.category)),
+                    ("waypoints", waypoints.into()),
+                ])
+            })
+            .collect();
+
+        let value = object([
+            ("type", FORMAT.into()),
+            ("nodes", nodes.into()),
+            ("edges", edges.into()),
+        ]);
+
+        JSON::stringify(&value)
+            .ok()
+            .and_then(|json| json.as_string())
+            .unwrap_or_default()
+    }
+
+    /// Parse JSON created by [`Self::to_json`].
+    pub fn from_json(json: &str) -> Result<Self, ParseSubgraphError> {
+        let value = JSON::parse(json).map_err(|_| ParseSubgraphError::Json)?;
+        if get(&value, "type").as_string().as_deref() != Some(FORMAT) {
+            return Err(ParseSubgraphError::Format);
+        }
+
+        let nodes = array(&value, "nodes")?
+            .iter()
+            .map(|node| {
+                Ok(SubgraphNode {
+                    label: get(&node, "label")
+                        .as_string()
+                        .ok_or(ParseSubgraphError::Field("label"))?,
+                    category: get(&node, "category").as_string(),
+                    rect: Rect {
+                        position: Position {
+                            x: number(&node, "x")?,
+                            y: number(&node, "y")?,
+                        },
+                        size: Size {
+                            width: number(&node, "width")?,
+                            height: number(&node, "height")?,
+                        },
+                    },
+                    fit_label: get(&node, "fitLabel").is_truthy(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let index = |value: &JsValue, name| {
+            let index = number(value, name)? as usize;
+            match index < nodes.len() {
+                true => Ok(index),
+                false => Err(ParseSubgraphError::Field(name)),
+            }
+        };
+
+        let edges = array(&value, "edges")?
+            .iter()
+            .map(|edge| {
+                let waypoints = array(&edge, "waypoints")?
+                    .iter()
+                    .map(|point| {
+                        let point = Array::from(&point);
+                        match (point.get(0).as_f64(), point.get(1).as_f64()) {
+                            (Some(x), Some(y)) => Ok(Position { x, y }),
+                            _ => Err(ParseSubgraphError::Field("waypoints")),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(SubgraphEdge {
+                    from: index(&edge, "from")?,
+                    to: index(&edge, "to")?,
+                    length: get(&edge, "length").as_f64(),
+                    strength: number(&edge, "strength")?,
+                    category: get(&edge, "category").as_string(),
+                    waypoints,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { nodes, edges })
+    }
+
+    /// The area covered by all nodes, if there are any.
+    pub fn bounds(&self) -> Option<Rect> {
+        self.nodes
+            .iter()
+            .map(|node| node.rect)
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+fn object<const N: usize>(fields: [(&str, JsValue); N]) -> JsValue {
+    let object = Object::new();
+    for (key, value) in fields {
+        let _ = Reflect::set(&object, &key.into(), &value);
+    }
+    object.into()
+}
+
+fn optional(value: &Option<String>) -> JsValue {
+    value.as_deref().map_or(JsValue::NULL, JsValue::from)
+}
+
+fn get(value: &JsValue, name: &str) -> JsValue {
+    Reflect::get(value, &name.into()).unwrap_or(JsValue::UNDEFINED)
+}
+
+fn number(value: &JsValue, name: &'static str) -> Result<f64, ParseSubgraphError> {
+    get(value, name)
+        .as_f64()
+        .ok_or(ParseSubgraphError::Field(name))
+}
+
+fn array(value: &JsValue, name: &'static str) -> Result<Array, ParseSubgraphError> {
+    let value = get(value, name);
+    match Array::is_array(&value) {
+        true => Ok(Array::from(&value)),
+        false => Err(ParseSubgraphError::Field(name)),
+    }
+}
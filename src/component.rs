@@ -1,7 +1,40 @@
+mod children;
+mod descriptor;
+mod legend;
+mod linked;
+mod minimap;
+mod overlay;
+mod provider;
+mod search;
+mod toolbar;
+
+pub use children::*;
+pub use descriptor::*;
+pub use legend::*;
+pub use linked::*;
+pub use minimap::*;
+pub use provider::*;
+pub use search::*;
+pub use toolbar::*;
+
 use crate::graph::*;
+use crate::layout::PhysicsConfig;
+use crate::persist::persist_layout;
+use crate::render::Backend;
+use overlay::position_overlays;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::rc::Rc;
+use web_sys::Element;
+use yew::html::ChildrenRenderer;
+use yew::platform::spawn_local;
 use yew::prelude::*;
 
+/// Space between a node and its tooltip, in pixels.
+const TOOLTIP_OFFSET: f64 = 8.0;
+
 #[derive(PartialEq, Properties)]
 pub struct GraphCanvasProperties {
     #[prop_or_default]
@@ -12,7 +45,159 @@ pub struct GraphCanvasProperties {
     #[prop_or_default]
     pub class: Classes,
 
+    /// Sets up the graph, whenever it gets created.
+    #[prop_or_default]
     pub initializer: GraphInitializer,
+
+    /// Loads data, e.g. by fetching it, and then sets up the graph, after the initializer.
+    ///
+    /// The graph is already shown while loading. When it gets re-created, the loaded data is
+    /// applied again, without loading it again.
+    #[prop_or_default]
+    pub async_initializer: Option<AsyncGraphInitializer>,
+
+    /// Shown next to the graph while the async initializer is loading.
+    #[prop_or_default]
+    pub loading: Html,
+
+    /// Nodes of the graph, in addition to the ones created by the initializer.
+    ///
+    /// Changes are applied to the running graph, by the keys of the nodes.
+    #[prop_or_default]
+    pub nodes: Vec<NodeDescriptor>,
+
+    /// Edges between the declared nodes.
+    #[prop_or_default]
+    pub edges: Vec<EdgeDescriptor>,
+
+    /// Nodes and edges, declared as [`GraphNode`] and [`GraphEdge`] children.
+    ///
+    /// They get added after the ones of the `nodes` and `edges` properties.
+    #[prop_or_default]
+    pub children: ChildrenRenderer<GraphChild>,
+
+    /// Keep the positions of the declared nodes in the local storage, under this key.
+    ///
+    /// They are restored when the graph gets created, and stored whenever the layout came to
+    /// rest. The key is only read when creating the graph.
+    #[prop_or_default]
+    pub persist_layout: Option<AttrValue>,
+
+    /// The viewport the graph opens with, applied after the initializer whenever it gets created.
+    #[prop_or_default]
+    pub initial_viewport: InitialViewport,
+
+    /// The rendering backend, which can't be changed without re-creating the graph.
+    #[prop_or_default]
+    pub backend: Backend,
+
+    /// The parameters of the layout simulation, applied before the initializer runs.
+    #[prop_or_default]
+    pub physics: PhysicsConfig,
+
+    /// Which gestures are available to the user.
+    #[prop_or_default]
+    pub mode: InteractionMode,
+
+    /// Disable dragging and editing, while still allowing to select, pan, and zoom.
+    #[prop_or_default]
+    pub readonly: bool,
+
+    /// Renders the tooltip of a hovered node, shown next to it after the tooltip delay.
+    #[prop_or_default]
+    pub tooltip: Option<Callback<Node, Html>>,
+
+    /// Called when the user requests a context menu, with the element below the pointer and the
+    /// position in client coordinates.
+    #[prop_or_default]
+    pub oncontextmenu: Option<Callback<(Target, Position)>>,
+
+    /// Provides the items of a built-in context menu, for the element below the pointer.
+    ///
+    /// No menu is shown when there are no items.
+    #[prop_or_default]
+    pub contextmenu: Option<Callback<Target, Vec<MenuItem>>>,
+
+    /// Called once the graph is running, with a controller for changing it.
+    ///
+    /// This is called again when the graph gets re-created, e.g. for a new initializer.
+    #[prop_or_default]
+    pub onready: Option<Callback<GraphController>>,
+
+    /// Called when a graph got created, after `onready`.
+    ///
+    /// Each call is followed by a call to `onunmount`, with a controller for the same graph.
+    #[prop_or_default]
+    pub onmount: Option<Callback<GraphController>>,
+
+    /// Called before a graph gets dropped, when the component gets removed or re-creates it.
+    ///
+    /// The controller still works during the call, and stops working afterwards.
+    #[prop_or_default]
+    pub onunmount: Option<Callback<GraphController>>,
+
+    /// Called when creating the graph, or drawing a frame, failed.
+    ///
+    /// Otherwise, errors are only logged.
+    #[prop_or_default]
+    pub onerror: Option<Callback<Error>>,
+
+    /// Called once the layout came to rest.
+    ///
+    /// Named after [`Graph::on_stabilized`], this is the `onlayoutstable` event, which isn't a
+    /// property of its own.
+    #[prop_or_default]
+    pub onstabilized: Option<Callback<()>>,
+
+    /// Called when a node is clicked or tapped.
+    #[prop_or_default]
+    pub onnodeclick: Option<Callback<NodeEvent>>,
+
+    /// Called when the pointer starts or stops hovering a node.
+    #[prop_or_default]
+    pub onnodehover: Option<Callback<Option<NodeEvent>>>,
+
+    /// Called when an edge is clicked or tapped, with the nodes it connects.
+    #[prop_or_default]
+    pub onedgeclick: Option<Callback<(NodeEvent, NodeEvent)>>,
+
+    /// Called with the selected nodes, whenever the selection changed.
+    #[prop_or_default]
+    pub onselectionchange: Option<Callback<Vec<NodeEvent>>>,
+
+    /// The keys of the selected nodes, controlling the selection.
+    ///
+    /// When set, selection changes by the user are only reported to `onselectionchange`, and
+    /// get reverted until this property is updated. Only declared nodes can be selected.
+    #[prop_or_default]
+    pub selected: Option<Vec<NodeKey>>,
+}
+
+/// The properties which re-create the graph when they change.
+///
+/// Effects setting callbacks on the graph depend on them as well, so that they are set again on
+/// each new graph.
+type Mounted = (GraphInitializer, Backend);
+
+type ContextMenuProps = (
+    Option<Callback<(Target, Position)>>,
+    Option<Callback<Target, Vec<MenuItem>>>,
+);
+
+/// An entry of the built-in context menu.
+#[derive(Clone, PartialEq)]
+pub struct MenuItem {
+    pub label: AttrValue,
+    pub onclick: Callback<()>,
+}
+
+impl MenuItem {
+    pub fn new(label: impl Into<AttrValue>, onclick: Callback<()>) -> Self {
+        Self {
+            label: label.into(),
+            onclick,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -27,42 +212,598 @@ impl GraphInitializer {
     }
 }
 
+impl Default for GraphInitializer {
+    fn default() -> Self {
+        Self::new(|_| {})
+    }
+}
+
 impl PartialEq for GraphInitializer {
     fn eq(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.0, &other.0)
     }
 }
 
+/// The viewport a graph opens with.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InitialViewport {
+    /// The origin of the graph coordinates in the top left corner, unzoomed.
+    #[default]
+    Origin,
+    /// A position in graph coordinates in the center, at a zoom level.
+    Center { position: Position, scale: f64 },
+    /// Show all nodes, keeping a margin in canvas pixels, see [`Graph::fit`].
+    ///
+    /// This uses the positions the nodes have when the graph gets created, which the layout
+    /// changes afterwards for nodes without a declared position.
+    Fit { margin: f64 },
+}
+
+impl InitialViewport {
+    fn apply(self, graph: &mut Graph) {
+        match self {
+            Self::Origin => {}
+            Self::Center { position, scale } => {
+                let mut viewport = graph.viewport();
+                viewport.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+                graph.set_viewport(viewport);
+                graph.center_on(position);
+            }
+            Self::Fit { margin } => graph.fit(margin),
+        }
+    }
+}
+
+type InitializerFuture = Pin<Box<dyn Future<Output = GraphInitializer>>>;
+
+/// Loads data before setting up the graph, resolving to the [`GraphInitializer`] applying it.
+#[derive(Clone)]
+pub struct AsyncGraphInitializer(pub Rc<dyn Fn() -> InitializerFuture>);
+
+impl AsyncGraphInitializer {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: Fn() -> Fut + 'static,
+        Fut: Future<Output = GraphInitializer> + 'static,
+    {
+        Self(Rc::new(move || Box::pin(f())))
+    }
+}
+
+impl PartialEq for AsyncGraphInitializer {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Displays a graph.
+///
+/// When rendered server side, this is only an empty element. The graph gets created once the
+/// component is mounted or hydrated in the browser.
 #[function_component(GraphCanvas)]
 pub fn graph_canvas(props: &GraphCanvasProperties) -> Html {
     let canvas = use_node_ref();
+    let handle = use_mut_ref(|| None::<Handle>);
+    let declared = use_mut_ref(Declared::default);
+    let context = use_context::<GraphContext>();
+    let selected = use_mut_ref(|| None::<Vec<NodeKey>>);
+    let tooltip = use_state_eq(|| None::<(Node, Rect)>);
+    let menu = use_state_eq(|| None::<(Vec<MenuItem>, Position)>);
+    let overlays = use_mut_ref(HashMap::<NodeKey, NodeRef>::new);
+    let loaded = use_mut_ref(|| None::<GraphInitializer>);
+    let loading = use_state_eq(|| props.async_initializer.is_some());
+    let (nodes, edges) = declarations(&props.nodes, &props.edges, &props.children);
+    let mounted: Mounted = (props.initializer.clone(), props.backend);
 
+    // keep the references of existing overlays, so that they stay attached to their elements
     {
-        let canvas = canvas.clone();
+        let mut overlays = overlays.borrow_mut();
+        overlays.retain(|key, _| {
+            nodes
+                .iter()
+                .any(|node| node.id == *key && node.overlay.is_some())
+        });
+        for node in nodes.iter().filter(|node| node.overlay.is_some()) {
+            overlays.entry(node.id.clone()).or_default();
+        }
+    }
+
+    {
+        let handle = handle.clone();
+        let declared = declared.clone();
+        let has_overlays = !overlays.borrow().is_empty();
+        let overlays = overlays.clone();
         use_effect_with_deps(
-            move |initializer| {
-                let mut graph = Graph::new(canvas.cast().unwrap());
+            move |has_overlays| {
+                // follow the nodes every frame, as they move without re-rendering the component
+                let render_loop = has_overlays.then(|| {
+                    RenderLoop::start(move |_| {
+                        if let Some(handle) = handle.borrow().as_ref() {
+                            handle.with_graph(|graph| {
+                                position_overlays(graph, &declared.borrow(), &overlays.borrow())
+                            });
+                        }
+                    })
+                });
+                move || {
+                    if let Some(render_loop) = render_loop {
+                        render_loop.cancel();
+                    }
+                }
+            },
+            has_overlays,
+        );
+    }
 
-                initializer.0(&mut graph);
+    {
+        let canvas = canvas.clone();
+        let handle = handle.clone();
+        let physics = props.physics;
+        let initial_viewport = props.initial_viewport;
+        let mode = props.mode;
+        let read_only = props.readonly;
+        let tooltip = tooltip.clone();
+        let onready = props.onready.clone();
+        let onmount = props.onmount.clone();
+        let persist = props.persist_layout.clone();
+        let onunmount = props.onunmount.clone();
+        let context = context.clone();
+        let onerror = props.onerror.clone();
+        let declared = declared.clone();
+        let selected = selected.clone();
+        let loaded = loaded.clone();
+        let (nodes, edges) = (nodes.clone(), edges.clone());
+        use_effect_with_deps(
+            move |(initializer, backend)| {
+                let keys = declared.clone();
+                let mut persistence = None;
+                let created = canvas
+                    .cast::<Element>()
+                    .ok_or(Error::MissingElement)
+                    .and_then(|element| {
+                        let renderer = backend.create_renderer(&element)?;
+                        let mut graph = Graph::with_renderer(element, renderer);
+                        graph.set_physics(physics);
+                        graph.set_interaction_mode(mode);
+                        graph.set_read_only(read_only);
+                        graph.on_tooltip(move |_, target| tooltip.set(target));
+                        initializer.0(&mut graph);
+                        if let Some(loaded) = &*loaded.borrow() {
+                            loaded.0(&mut graph);
+                        }
+                        // a new graph, so everything needs to be added again
+                        let mut declared = declared.borrow_mut();
+                        *declared = Declared::default();
+                        declared.sync(&mut graph, &nodes, &edges);
+                        apply_selection(&mut graph, &declared, &selected.borrow());
+                        initial_viewport.apply(&mut graph);
+                        Ok(graph.run())
+                    });
 
-                let handle = graph.run();
+                *handle.borrow_mut() = match created {
+                    Ok(handle) => {
+                        if let Some(onready) = &onready {
+                            onready.emit(handle.controller());
+                        }
+                        if let Some(onmount) = &onmount {
+                            onmount.emit(handle.controller());
+                        }
+                        persistence = persist.as_ref().map(|storage_key| {
+                            persist_layout(
+                                handle.controller(),
+                                storage_key.to_string(),
+                                move |node| Some(keys.borrow().event(node).key?.to_string()),
+                            )
+                        });
+                        if let Some(context) = &context {
+                            context.set(Some(handle.controller()));
+                        }
+                        Some(handle)
+                    }
+                    Err(err) => {
+                        log::error!("Failed to create graph: {err}");
+                        if let Some(onerror) = &onerror {
+                            onerror.emit(err);
+                        }
+                        None
+                    }
+                };
 
-                || {
+                move || {
                     log::debug!("Dropping graph");
+                    drop(persistence);
+                    if let Some(context) = &context {
+                        context.set(None);
+                    }
+                    let handle = handle.borrow_mut().take();
+                    if let (Some(onunmount), Some(handle)) = (&onunmount, &handle) {
+                        onunmount.emit(handle.controller());
+                    }
                     drop(handle);
                 }
             },
-            props.initializer.clone(),
+            mounted.clone(),
+        );
+    }
+
+    {
+        let handle = handle.clone();
+        let loading = loading.clone();
+        use_effect_with_deps(
+            move |initializer: &Option<AsyncGraphInitializer>| {
+                *loaded.borrow_mut() = None;
+                loading.set(initializer.is_some());
+
+                // a replaced initializer must not apply its data once it resolves
+                let cancelled = Rc::new(Cell::new(false));
+                if let Some(initializer) = initializer {
+                    let future = initializer.0();
+                    let cancelled = cancelled.clone();
+                    spawn_local(async move {
+                        let initializer = future.await;
+                        if cancelled.get() {
+                            return;
+                        }
+                        if let Some(handle) = handle.borrow().as_ref() {
+                            handle.with_graph(|graph| initializer.0(graph));
+                        }
+                        *loaded.borrow_mut() = Some(initializer);
+                        loading.set(false);
+                    });
+                }
+
+                move || cancelled.set(true)
+            },
+            props.async_initializer.clone(),
+        );
+    }
+
+    {
+        let declared = declared.clone();
+        let selected = selected.clone();
+        use_effect_with_deps(
+            update(&handle, move |graph, (nodes, edges): &(Vec<_>, Vec<_>)| {
+                let mut declared = declared.borrow_mut();
+                declared.sync(graph, nodes, edges);
+                // keys may refer to different nodes now
+                apply_selection(graph, &declared, &selected.borrow());
+            }),
+            (nodes.clone(), edges),
+        );
+    }
+
+    {
+        let declared = declared.clone();
+        let selected = selected.clone();
+        let handle = handle.clone();
+        use_effect_with_deps(
+            move |keys: &Option<Vec<NodeKey>>| {
+                *selected.borrow_mut() = keys.clone();
+                if let Some(handle) = handle.borrow().as_ref() {
+                    handle.with_graph(|graph| {
+                        apply_selection(graph, &declared.borrow(), &selected.borrow())
+                    });
+                }
+            },
+            props.selected.clone(),
+        );
+    }
+
+    use_effect_with_deps(
+        update(
+            &handle,
+            |graph, (onerror, _): &(Option<Callback<Error>>, Mounted)| {
+                let onerror = onerror.clone();
+                graph.on_error(move |_, err| match &onerror {
+                    Some(onerror) => onerror.emit(err),
+                    None => log::warn!("Failed to draw the graph: {err}"),
+                });
+            },
+        ),
+        (props.onerror.clone(), mounted.clone()),
+    );
+
+    use_effect_with_deps(
+        update(&handle, |graph, physics| {
+            if graph.physics() != physics {
+                graph.set_physics(*physics);
+            }
+        }),
+        props.physics,
+    );
+
+    use_effect_with_deps(
+        update(&handle, |graph, mode| {
+            if graph.interaction_mode() != *mode {
+                graph.set_interaction_mode(*mode);
+            }
+        }),
+        props.mode,
+    );
+
+    use_effect_with_deps(
+        update(&handle, |graph, readonly| {
+            if graph.is_read_only() != *readonly {
+                graph.set_read_only(*readonly);
+            }
+        }),
+        props.readonly,
+    );
+
+    use_effect_with_deps(
+        update(
+            &handle,
+            |graph, (onstabilized, _): &(Option<Callback<()>>, Mounted)| {
+                let onstabilized = onstabilized.clone();
+                graph.on_stabilized(move |_| {
+                    if let Some(onstabilized) = &onstabilized {
+                        onstabilized.emit(());
+                    }
+                });
+            },
+        ),
+        (props.onstabilized.clone(), mounted.clone()),
+    );
+
+    {
+        let declared = declared.clone();
+        use_effect_with_deps(
+            update(
+                &handle,
+                move |graph, (onnodeclick, _): &(Option<Callback<NodeEvent>>, Mounted)| {
+                    let onnodeclick = onnodeclick.clone();
+                    graph.on_node_click(move |_, node| {
+                        if let Some(onnodeclick) = &onnodeclick {
+                            onnodeclick.emit(declared.borrow().event(node));
+                        }
+                    });
+                },
+            ),
+            (props.onnodeclick.clone(), mounted.clone()),
+        );
+    }
+
+    {
+        let declared = declared.clone();
+        use_effect_with_deps(
+            update(
+                &handle,
+                move |graph, (onnodehover, _): &(Option<Callback<Option<NodeEvent>>>, Mounted)| {
+                    let onnodehover = onnodehover.clone();
+                    graph.on_node_hover(move |_, node| {
+                        if let Some(onnodehover) = &onnodehover {
+                            onnodehover.emit(node.map(|node| declared.borrow().event(node)));
+                        }
+                    });
+                },
+            ),
+            (props.onnodehover.clone(), mounted.clone()),
+        );
+    }
+
+    {
+        let declared = declared.clone();
+        use_effect_with_deps(
+            update(
+                &handle,
+                move |graph, (onedgeclick, _): &(Option<Callback<(NodeEvent, NodeEvent)>>, Mounted)| {
+                    let onedgeclick = onedgeclick.clone();
+                    graph.on_edge_click(move |_, a, b| {
+                        if let Some(onedgeclick) = &onedgeclick {
+                            let declared = declared.borrow();
+                            onedgeclick.emit((declared.event(a), declared.event(b)));
+                        }
+                    });
+                },
+            ),
+            (props.onedgeclick.clone(), mounted.clone()),
         );
     }
 
+    {
+        let declared = declared.clone();
+        let selected = selected.clone();
+        use_effect_with_deps(
+            update(
+                &handle,
+                move |graph, (onselectionchange, _): &(Option<Callback<Vec<NodeEvent>>>, Mounted)| {
+                    let onselectionchange = onselectionchange.clone();
+                    graph.on_selection_change(move |graph| {
+                        let declared = declared.borrow();
+                        let selected = selected.borrow();
+                        if let Some(keys) = selected.as_ref() {
+                            if *graph.selection() == declared.resolve(keys) {
+                                // applied from the property, or reverted
+                                return;
+                            }
+                        }
+                        if let Some(onselectionchange) = &onselectionchange {
+                            let nodes = graph
+                                .selection()
+                                .iter()
+                                .map(|node| declared.event(*node))
+                                .collect();
+                            onselectionchange.emit(nodes);
+                        }
+                        apply_selection(graph, &declared, &selected);
+                    });
+                },
+            ),
+            (props.onselectionchange.clone(), mounted.clone()),
+        );
+    }
+
+    {
+        let menu = menu.clone();
+        use_effect_with_deps(
+            update(
+                &handle,
+                move |graph, ((oncontextmenu, contextmenu), _): &(ContextMenuProps, Mounted)| {
+                    if oncontextmenu.is_none() && contextmenu.is_none() {
+                        return;
+                    }
+                    let oncontextmenu = oncontextmenu.clone();
+                    let contextmenu = contextmenu.clone();
+                    graph.on_context_menu(move |_, target, position| {
+                        if let Some(oncontextmenu) = &oncontextmenu {
+                            oncontextmenu.emit((target, position));
+                        }
+                        if let Some(contextmenu) = &contextmenu {
+                            let items = contextmenu.emit(target);
+                            menu.set((!items.is_empty()).then_some((items, position)));
+                        }
+                    });
+                },
+            ),
+            (
+                (props.oncontextmenu.clone(), props.contextmenu.clone()),
+                mounted.clone(),
+            ),
+        );
+    }
+
+    // a portal, so that the tooltip isn't clipped by the container of the graph
+    let tooltip = match (&props.tooltip, *tooltip, portal_host()) {
+        (Some(render), Some((node, rect)), Some(host)) => create_portal(
+            html!(
+                <div style={format!(
+                    "position: fixed; left: {}px; top: {}px; z-index: 1000; pointer-events: none;",
+                    rect.right() + TOOLTIP_OFFSET,
+                    rect.position.y,
+                )}>
+                    { render.emit(node) }
+                </div>
+            ),
+            host,
+        ),
+        _ => html!(),
+    };
+
+    // a full page backdrop, closing the menu on any click outside of it
+    let menu = match (&*menu, portal_host()) {
+        (Some((items, position)), Some(host)) => {
+            let close = {
+                let menu = menu.clone();
+                Callback::from(move |evt: MouseEvent| {
+                    evt.prevent_default();
+                    menu.set(None);
+                })
+            };
+            create_portal(
+                html!(
+                    <div
+                        style="position: fixed; inset: 0; z-index: 1000;"
+                        onclick={close.clone()}
+                        oncontextmenu={close}
+                    >
+                        <ul
+                            class="web-graph-menu"
+                            style={format!(
+                                "position: fixed; left: {}px; top: {}px; margin: 0; padding: 4px 0; list-style: none; background: white; border: 1px solid #ccc; box-shadow: 0 2px 6px rgba(0, 0, 0, 0.2);",
+                                position.x, position.y,
+                            )}
+                        >
+                            { for items.iter().map(|item| {
+                                let onclick = item.onclick.reform(|_: MouseEvent| ());
+                                html!(
+                                    <li style="padding: 4px 12px; cursor: pointer;" {onclick}>
+                                        { &item.label }
+                                    </li>
+                                )
+                            }) }
+                        </ul>
+                    </div>
+                ),
+                host,
+            )
+        }
+        _ => html!(),
+    };
+
+    // positioned by the render loop, hidden until then
+    let overlays = match portal_host() {
+        Some(host) if !overlays.borrow().is_empty() => create_portal(
+            html!(
+                <div class="web-graph-overlays" style="pointer-events: none;">
+                    { for nodes.iter().filter_map(|node| {
+                        let overlay = node.overlay.clone()?;
+                        let noderef = overlays.borrow().get(&node.id)?.clone();
+                        Some(html!(
+                            <div
+                                key={node.id.to_string()}
+                                ref={noderef}
+                                class="web-graph-overlay"
+                                style="position: fixed; left: 0; top: 0; z-index: 999; transform-origin: 0 0; box-sizing: border-box; overflow: hidden; visibility: hidden;"
+                            >
+                                { overlay }
+                            </div>
+                        ))
+                    }) }
+                </div>
+            ),
+            host,
+        ),
+        _ => html!(),
+    };
+
+    let graph = match props.backend {
+        Backend::Svg => html!(
+            <svg
+                id={&props.id}
+                ref={canvas}
+                class={props.class.clone()}
+                style={&props.style}
+            >
+            </svg>
+        ),
+        _ => html!(
+            <canvas
+                id={&props.id}
+                ref={canvas}
+                class={props.class.clone()}
+                style={&props.style}
+            >
+            </canvas>
+        ),
+    };
+
     html!(
-        <canvas
-            id={&props.id}
-            ref={canvas}
-            class={props.class.clone()}
-            style={&props.style}
-        >
-        </canvas>
+        <>
+            { graph }
+            if *loading {
+                <div class="web-graph-loading">{ props.loading.clone() }</div>
+            }
+            { overlays }
+            { tooltip }
+            { menu }
+        </>
     )
 }
+
+/// The element to render portals into, unless rendering server side.
+fn portal_host() -> Option<Element> {
+    document().ok()?.body().map(Into::into)
+}
+
+/// Select the nodes of the keys, if the selection is controlled.
+fn apply_selection(graph: &mut Graph, declared: &Declared, selected: &Option<Vec<NodeKey>>) {
+    if let Some(keys) = selected {
+        let nodes = declared.resolve(keys);
+        if *graph.selection() != nodes {
+            graph.set_selection(nodes);
+        }
+    }
+}
+
+/// Create an effect, applying a property to the running graph.
+fn update<T, F>(handle: &Rc<RefCell<Option<Handle>>>, f: F) -> impl FnOnce(&T)
+where
+    F: FnOnce(&mut Graph, &T) + 'static,
+{
+    let handle = handle.clone();
+    move |value| {
+        if let Some(handle) = handle.borrow().as_ref() {
+            handle.with_graph(|graph| f(graph, value));
+        }
+    }
+}
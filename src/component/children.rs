@@ -0,0 +1,120 @@
+use super::{EdgeDescriptor, NodeDescriptor, NodeKey};
+use crate::graph::{EdgeProperties, NodeSize, Position};
+use yew::html::ChildrenRenderer;
+use yew::prelude::*;
+use yew::virtual_dom::VChild;
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct GraphNodeProperties {
+    pub id: NodeKey,
+    #[prop_or_default]
+    pub label: String,
+    #[prop_or_default]
+    pub category: Option<String>,
+    #[prop_or(NodeSize::FitLabel)]
+    pub size: NodeSize,
+    /// The position of the center, otherwise the layout places the node.
+    #[prop_or_default]
+    pub position: Option<Position>,
+    /// Shown over the node, see [`NodeDescriptor::overlay`].
+    #[prop_or_default]
+    pub children: Children,
+}
+
+impl From<&GraphNodeProperties> for NodeDescriptor {
+    fn from(value: &GraphNodeProperties) -> Self {
+        Self {
+            id: value.id.clone(),
+            label: value.label.clone(),
+            category: value.category.clone(),
+            size: value.size,
+            position: value.position,
+            overlay: (!value.children.is_empty()).then(|| html!({ for value.children.iter() })),
+        }
+    }
+}
+
+/// A node, declared as a child of a [`super::GraphCanvas`].
+///
+/// This renders nothing by itself, the canvas adds it to the graph like a [`NodeDescriptor`].
+#[function_component(GraphNode)]
+pub fn graph_node(_props: &GraphNodeProperties) -> Html {
+    html!()
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct GraphEdgeProperties {
+    pub from: NodeKey,
+    pub to: NodeKey,
+    #[prop_or_default]
+    pub length: Option<f64>,
+    #[prop_or(EdgeProperties::default().strength)]
+    pub strength: f64,
+    #[prop_or_default]
+    pub category: Option<String>,
+}
+
+impl From<&GraphEdgeProperties> for EdgeDescriptor {
+    fn from(value: &GraphEdgeProperties) -> Self {
+        Self {
+            from: value.from.clone(),
+            to: value.to.clone(),
+            length: value.length,
+            strength: value.strength,
+            category: value.category.clone(),
+        }
+    }
+}
+
+/// An edge, declared as a child of a [`super::GraphCanvas`].
+///
+/// This renders nothing by itself, the canvas adds it to the graph like an [`EdgeDescriptor`].
+#[function_component(GraphEdge)]
+pub fn graph_edge(_props: &GraphEdgeProperties) -> Html {
+    html!()
+}
+
+/// A child of a [`super::GraphCanvas`].
+#[derive(Clone, PartialEq)]
+pub enum GraphChild {
+    Node(VChild<GraphNode>),
+    Edge(VChild<GraphEdge>),
+}
+
+impl From<VChild<GraphNode>> for GraphChild {
+    fn from(value: VChild<GraphNode>) -> Self {
+        Self::Node(value)
+    }
+}
+
+impl From<VChild<GraphEdge>> for GraphChild {
+    fn from(value: VChild<GraphEdge>) -> Self {
+        Self::Edge(value)
+    }
+}
+
+impl From<GraphChild> for Html {
+    fn from(value: GraphChild) -> Self {
+        match value {
+            GraphChild::Node(child) => child.into(),
+            GraphChild::Edge(child) => child.into(),
+        }
+    }
+}
+
+/// The nodes and edges declared by the properties, followed by the ones declared as children.
+pub(crate) fn declarations(
+    nodes: &[NodeDescriptor],
+    edges: &[EdgeDescriptor],
+    children: &ChildrenRenderer<GraphChild>,
+) -> (Vec<NodeDescriptor>, Vec<EdgeDescriptor>) {
+    let mut nodes = nodes.to_vec();
+    let mut edges = edges.to_vec();
+    for child in children.iter() {
+        match child {
+            GraphChild::Node(child) => nodes.push((&*child.props).into()),
+            GraphChild::Edge(child) => edges.push((&*child.props).into()),
+        }
+    }
+    (nodes, edges)
+}
@@ -0,0 +1,213 @@
+use crate::graph::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use yew::{AttrValue, Html};
+
+/// The key identifying a node declared through the properties of a [`super::GraphCanvas`].
+pub type NodeKey = AttrValue;
+
+/// A node, declared through the properties of a [`super::GraphCanvas`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDescriptor {
+    pub id: NodeKey,
+    pub label: String,
+    /// See [`NodeProperties::category`].
+    pub category: Option<String>,
+    pub size: NodeSize,
+    /// The position of the center, otherwise the layout places the node.
+    ///
+    /// Changing it moves the node.
+    pub position: Option<Position>,
+    /// HTML shown over the node, sized like the node and scaled with the graph.
+    ///
+    /// It doesn't receive pointer events, unless it enables them through its style.
+    pub overlay: Option<Html>,
+}
+
+impl NodeDescriptor {
+    /// A node fitting its label, placed by the layout.
+    pub fn new(id: impl Into<NodeKey>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            category: None,
+            size: NodeSize::FitLabel,
+            position: None,
+            overlay: None,
+        }
+    }
+
+    fn properties(&self) -> NodeProperties {
+        NodeProperties {
+            label: self.label.clone(),
+            category: self.category.clone(),
+        }
+    }
+}
+
+/// A node, as reported by the events of a [`super::GraphCanvas`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeEvent {
+    pub node: Node,
+    /// The key, if the node was declared through the properties.
+    pub key: Option<NodeKey>,
+}
+
+/// An edge, declared through the properties of a [`super::GraphCanvas`].
+///
+/// Edges between nodes which aren't declared are ignored. Like the edges of the graph, they are
+/// undirected: of two edges between the same nodes, only the later one is added.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeDescriptor {
+    pub from: NodeKey,
+    pub to: NodeKey,
+    /// See [`EdgeProperties::length`].
+    pub length: Option<f64>,
+    /// See [`EdgeProperties::strength`].
+    pub strength: f64,
+    /// See [`EdgeProperties::category`].
+    pub category: Option<String>,
+}
+
+impl EdgeDescriptor {
+    pub fn new(from: impl Into<NodeKey>, to: impl Into<NodeKey>) -> Self {
+        let EdgeProperties {
+            length,
+            strength,
+            category,
+        } = Default::default();
+        Self {
+            from: from.into(),
+            to: to.into(),
+            length,
+            strength,
+            category,
+        }
+    }
+
+    fn properties(&self) -> EdgeProperties {
+        EdgeProperties {
+            length: self.length,
+            strength: self.strength,
+            category: self.category.clone(),
+        }
+    }
+}
+
+/// The declared nodes and edges, as applied to a graph.
+#[derive(Default)]
+pub(crate) struct Declared {
+    nodes: HashMap<NodeKey, (Node, NodeDescriptor)>,
+    keys: HashMap<Node, NodeKey>,
+    /// By the keys of their nodes, with the smaller key first
+    edges: HashMap<(NodeKey, NodeKey), EdgeDescriptor>,
+}
+
+impl Declared {
+    /// The node of a key.
+    pub(crate) fn node(&self, key: &NodeKey) -> Option<Node> {
+        self.nodes.get(key).map(|(node, _)| *node)
+    }
+
+    /// The nodes of the keys, skipping the ones which aren't declared.
+    pub(crate) fn resolve(&self, keys: &[NodeKey]) -> BTreeSet<Node> {
+        keys.iter().filter_map(|key| self.node(key)).collect()
+    }
+
+    /// A node, with its key if it was declared.
+    pub(crate) fn event(&self, node: Node) -> NodeEvent {
+        NodeEvent {
+            node,
+            key: self.keys.get(&node).cloned(),
+        }
+    }
+
+    /// Apply the differences to the previous descriptors to the graph.
+    pub(crate) fn sync(
+        &mut self,
+        graph: &mut Graph,
+        nodes: &[NodeDescriptor],
+        edges: &[EdgeDescriptor],
+    ) {
+        // nodes
+
+        let keys: HashSet<&NodeKey> = nodes.iter().map(|node| &node.id).collect();
+        self.nodes.retain(|key, (node, _)| {
+            if !graph.storage().contains(*node) {
+                // removed by the user, added again below if it's still declared
+                return false;
+            }
+            let keep = keys.contains(key);
+            if !keep {
+                // this removes its edges too
+                graph.remove_node(*node);
+            }
+            keep
+        });
+        let nodes_by_key = &self.nodes;
+        self.keys.retain(|_, key| nodes_by_key.contains_key(key));
+
+        for descriptor in nodes {
+            let Some((node, previous)) = self.nodes.get_mut(&descriptor.id) else {
+                let position = descriptor.position.unwrap_or(Position { x: 0.0, y: 0.0 });
+                let node = graph.add_node(position, descriptor.size, descriptor.properties());
+                graph.set_center(node, position);
+                self.keys.insert(node, descriptor.id.clone());
+                self.nodes
+                    .insert(descriptor.id.clone(), (node, descriptor.clone()));
+                continue;
+            };
+
+            if previous.label != descriptor.label || previous.category != descriptor.category {
+                graph.set_properties(*node, descriptor.properties());
+            }
+            if previous.size != descriptor.size {
+                graph.set_size(*node, descriptor.size);
+            }
+            if previous.position != descriptor.position {
+                if let Some(position) = descriptor.position {
+                    graph.set_center(*node, position);
+                }
+            }
+            *previous = descriptor.clone();
+        }
+
+        // edges, which are replaced when they change
+
+        let declared: HashMap<(NodeKey, NodeKey), &EdgeDescriptor> =
+            edges.iter().map(|edge| (edge_key(edge), edge)).collect();
+
+        let nodes = &self.nodes;
+        self.edges.retain(|(from, to), previous| {
+            let (Some((a, _)), Some((b, _))) = (nodes.get(from), nodes.get(to)) else {
+                // already removed along with the node
+                return false;
+            };
+            let keep = declared
+                .get(&(from.clone(), to.clone()))
+                .is_some_and(|edge| *edge == previous);
+            if !keep {
+                graph.remove_edge(*a, *b);
+            }
+            keep
+        });
+
+        for (key, edge) in declared {
+            if self.edges.contains_key(&key) {
+                continue;
+            }
+            if let (Some(a), Some(b)) = (self.node(&edge.from), self.node(&edge.to)) {
+                if graph.add_edge(a, b, edge.properties()).is_ok() {
+                    self.edges.insert(key, edge.clone());
+                }
+            }
+        }
+    }
+}
+
+/// The key of an edge, with the smaller key of its nodes first, as edges are undirected.
+fn edge_key(edge: &EdgeDescriptor) -> (NodeKey, NodeKey) {
+    match edge.from <= edge.to {
+        true => (edge.from.clone(), edge.to.clone()),
+        false => (edge.to.clone(), edge.from.clone()),
+    }
+}
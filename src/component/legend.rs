@@ -0,0 +1,79 @@
+use super::use_graph_or;
+use crate::graph::{Category, GraphController};
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct GraphLegendProperties {
+    /// The graph to describe, as provided by [`super::GraphCanvasProperties::onready`].
+    ///
+    /// Defaults to the graph of the enclosing [`super::GraphProvider`].
+    #[prop_or_default]
+    pub graph: Option<GraphController>,
+
+    #[prop_or_default]
+    pub id: AttrValue,
+
+    #[prop_or_default]
+    pub style: AttrValue,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// Lists the styled categories of a graph shown by a [`super::GraphCanvas`], with their colors.
+///
+/// Each category has a checkbox, showing or hiding its nodes and edges. See
+/// [`crate::graph::Graph::set_category`].
+#[function_component(GraphLegend)]
+pub fn graph_legend(props: &GraphLegendProperties) -> Html {
+    let graph = use_graph_or(&props.graph);
+    // visibility is changed on the graph, so the legend needs to render again
+    let update = use_force_update();
+
+    let categories: Vec<(String, Category)> = graph
+        .as_ref()
+        .and_then(|graph| {
+            graph.with_graph(|graph| {
+                graph
+                    .categories()
+                    .iter()
+                    .map(|(name, category)| (name.clone(), category.clone()))
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+
+    html!(
+        <ul
+            id={&props.id}
+            class={classes!("web-graph-legend", props.class.clone())}
+            style={format!("margin: 0; padding: 0; list-style: none; {}", props.style)}
+        >
+            { for categories.into_iter().map(|(name, category)| {
+                let onchange = {
+                    let graph = graph.clone();
+                    let update = update.clone();
+                    let name = name.clone();
+                    let visible = !category.visible;
+                    Callback::from(move |_: Event| {
+                        if let Some(graph) = &graph {
+                            graph.with_graph(|graph| graph.set_category_visible(&name, visible));
+                            update.force_update();
+                        }
+                    })
+                };
+                html!(
+                    <li>
+                        <label style="display: flex; align-items: center; gap: 4px;">
+                            <input type="checkbox" checked={category.visible} {onchange} />
+                            <span style={format!(
+                                "display: inline-block; width: 12px; height: 12px; background: {};",
+                                category.color,
+                            )}></span>
+                            { name }
+                        </label>
+                    </li>
+                )
+            }) }
+        </ul>
+    )
+}
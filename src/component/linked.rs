@@ -0,0 +1,83 @@
+use super::use_graph_or;
+use crate::graph::GraphController;
+use crate::linked::{LinkedView, LinkedViewHandle};
+use crate::render::Backend;
+use web_sys::Element;
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct GraphLinkedViewProperties {
+    /// The graph to show, as provided by [`super::GraphCanvasProperties::onready`].
+    ///
+    /// Defaults to the graph of the enclosing [`super::GraphProvider`].
+    #[prop_or_default]
+    pub graph: Option<GraphController>,
+
+    /// The rendering backend of this view, independent of the one of the graph.
+    #[prop_or_default]
+    pub backend: Backend,
+
+    #[prop_or_default]
+    pub id: AttrValue,
+
+    #[prop_or_default("width: 100%; height: 100%;")]
+    pub style: AttrValue,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// A read-only overview of a graph shown by a [`super::GraphCanvas`], with its own viewport,
+/// see [`LinkedView`].
+///
+/// Apart from panning, zooming, and selecting nodes by clicking them, the graph is only
+/// interacted with through its [`super::GraphCanvas`].
+#[function_component(GraphLinkedView)]
+pub fn graph_linked_view(props: &GraphLinkedViewProperties) -> Html {
+    let graph = use_graph_or(&props.graph);
+    let element = use_node_ref();
+    let handle = use_mut_ref(|| None::<LinkedViewHandle>);
+
+    {
+        let element = element.clone();
+        use_effect_with_deps(
+            move |(graph, backend): &(Option<GraphController>, Backend)| {
+                *handle.borrow_mut() = match (element.cast::<Element>(), graph) {
+                    (Some(element), Some(graph)) => {
+                        match LinkedView::new(element, *backend, graph.clone()) {
+                            Ok(view) => Some(view.run()),
+                            Err(err) => {
+                                log::error!("Failed to create linked view: {err}");
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+
+                move || drop(handle.borrow_mut().take())
+            },
+            (graph.clone(), props.backend),
+        );
+    }
+
+    match props.backend {
+        Backend::Svg => html!(
+            <svg
+                id={&props.id}
+                ref={element}
+                class={props.class.clone()}
+                style={&props.style}
+            >
+            </svg>
+        ),
+        _ => html!(
+            <canvas
+                id={&props.id}
+                ref={element}
+                class={props.class.clone()}
+                style={&props.style}
+            >
+            </canvas>
+        ),
+    }
+}
@@ -0,0 +1,57 @@
+use super::use_graph_or;
+use crate::graph::GraphController;
+use crate::minimap::{Minimap, MinimapHandle};
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct GraphMinimapProperties {
+    /// The graph to show, as provided by [`super::GraphCanvasProperties::onready`].
+    ///
+    /// Defaults to the graph of the enclosing [`super::GraphProvider`].
+    ///
+    /// Without a graph, the minimap stays empty.
+    #[prop_or_default]
+    pub graph: Option<GraphController>,
+
+    #[prop_or_default]
+    pub id: AttrValue,
+
+    #[prop_or_default("width: 200px; height: 150px;")]
+    pub style: AttrValue,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// An overview of a graph shown by a [`super::GraphCanvas`], see [`Minimap`].
+#[function_component(GraphMinimap)]
+pub fn graph_minimap(props: &GraphMinimapProperties) -> Html {
+    let graph = use_graph_or(&props.graph);
+    let canvas = use_node_ref();
+    let handle = use_mut_ref(|| None::<MinimapHandle>);
+
+    {
+        let canvas = canvas.clone();
+        use_effect_with_deps(
+            move |graph: &Option<GraphController>| {
+                *handle.borrow_mut() = match (canvas.cast::<HtmlCanvasElement>(), graph) {
+                    (Some(canvas), Some(graph)) => Some(Minimap::new(canvas, graph.clone()).run()),
+                    _ => None,
+                };
+
+                move || drop(handle.borrow_mut().take())
+            },
+            graph.clone(),
+        );
+    }
+
+    html!(
+        <canvas
+            id={&props.id}
+            ref={canvas}
+            class={props.class.clone()}
+            style={&props.style}
+        >
+        </canvas>
+    )
+}
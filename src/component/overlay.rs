@@ -0,0 +1,50 @@
+use super::{Declared, NodeKey};
+use crate::graph::{Graph, Rect};
+use std::collections::HashMap;
+use web_sys::HtmlElement;
+use yew::NodeRef;
+
+/// Move the overlays of the declared nodes over their nodes, scaled like the graph.
+///
+/// Overlays of nodes which aren't shown, or outside of the element, are hidden.
+pub(crate) fn position_overlays(
+    graph: &Graph,
+    declared: &Declared,
+    overlays: &HashMap<NodeKey, NodeRef>,
+) {
+    let bounds = graph.element().get_bounding_client_rect();
+    let bounds = Rect::spanning(
+        (bounds.left(), bounds.top()).into(),
+        (bounds.right(), bounds.bottom()).into(),
+    );
+    let scale = graph.viewport().scale;
+
+    for (key, overlay) in overlays {
+        let Some(overlay) = overlay.cast::<HtmlElement>() else {
+            continue;
+        };
+        let style = overlay.style();
+
+        let rect = declared
+            .node(key)
+            .filter(|node| !graph.is_hidden(*node))
+            .and_then(|node| graph.storage().rect(node));
+        let Some(rect) = rect else {
+            let _ = style.set_property("visibility", "hidden");
+            continue;
+        };
+
+        let client = graph.client_rect(rect);
+        let visible = client.intersects(&bounds);
+        let _ = style.set_property("visibility", if visible { "visible" } else { "hidden" });
+        let _ = style.set_property("width", &format!("{}px", rect.size.width));
+        let _ = style.set_property("height", &format!("{}px", rect.size.height));
+        let _ = style.set_property(
+            "transform",
+            &format!(
+                "translate({}px, {}px) scale({scale})",
+                client.position.x, client.position.y
+            ),
+        );
+    }
+}
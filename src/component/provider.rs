@@ -0,0 +1,56 @@
+use crate::graph::GraphController;
+use yew::prelude::*;
+
+/// The graph of the [`super::GraphCanvas`] within a [`GraphProvider`].
+#[derive(Clone, PartialEq)]
+pub struct GraphContext {
+    /// The running graph, once it was created.
+    pub graph: Option<GraphController>,
+    set: Callback<Option<GraphController>>,
+}
+
+impl GraphContext {
+    /// Make a graph available to the components within the provider.
+    pub(crate) fn set(&self, graph: Option<GraphController>) {
+        self.set.emit(graph);
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct GraphProviderProperties {
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Makes the graph of a [`super::GraphCanvas`] within it available to all other components
+/// within it, see [`use_graph`].
+///
+/// The widgets, like [`super::GraphToolbar`], use it when no graph is passed to them.
+#[function_component(GraphProvider)]
+pub fn graph_provider(props: &GraphProviderProperties) -> Html {
+    let graph = use_state_eq(|| None::<GraphController>);
+
+    let context = GraphContext {
+        graph: (*graph).clone(),
+        set: Callback::from(move |value| graph.set(value)),
+    };
+
+    html!(
+        <ContextProvider<GraphContext> {context}>
+            { for props.children.iter() }
+        </ContextProvider<GraphContext>>
+    )
+}
+
+/// The graph of the enclosing [`GraphProvider`], once it is running.
+#[hook]
+pub fn use_graph() -> Option<GraphController> {
+    use_context::<GraphContext>().and_then(|context| context.graph)
+}
+
+/// The graph passed to a widget, or otherwise the one of the enclosing [`GraphProvider`].
+#[hook]
+pub(crate) fn use_graph_or(graph: &Option<GraphController>) -> Option<GraphController> {
+    let provided = use_graph();
+    graph.clone().or(provided)
+}
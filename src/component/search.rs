@@ -0,0 +1,177 @@
+use super::use_graph_or;
+use crate::graph::{GraphController, Node};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The number of results listed, the graph highlights all of them.
+const MAX_RESULTS: usize = 10;
+
+#[derive(PartialEq, Properties)]
+pub struct GraphSearchProperties {
+    /// The graph to search, as provided by [`super::GraphCanvasProperties::onready`].
+    ///
+    /// Defaults to the graph of the enclosing [`super::GraphProvider`].
+    #[prop_or_default]
+    pub graph: Option<GraphController>,
+
+    #[prop_or_default("Search")]
+    pub placeholder: AttrValue,
+
+    #[prop_or_default]
+    pub id: AttrValue,
+
+    #[prop_or_default]
+    pub style: AttrValue,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// A search box for the labels of the nodes of a graph shown by a [`super::GraphCanvas`].
+///
+/// While searching, matching nodes are highlighted, see [`crate::graph::Graph::highlight`].
+/// Choosing a result, by clicking it or with the arrow keys and Enter, selects the node and
+/// moves the view to it. Escape clears the search.
+#[function_component(GraphSearch)]
+pub fn graph_search(props: &GraphSearchProperties) -> Html {
+    let graph = use_graph_or(&props.graph);
+    let query = use_state_eq(String::new);
+    let results = use_state_eq(Vec::<(Node, String)>::new);
+    let current = use_state_eq(|| None::<usize>);
+
+    // stop highlighting when switching to another graph, or going away
+    use_effect_with_deps(
+        |graph: &Option<GraphController>| {
+            let graph = graph.clone();
+            move || {
+                if let Some(graph) = graph {
+                    graph.with_graph(|graph| graph.clear_highlight());
+                }
+            }
+        },
+        graph.clone(),
+    );
+
+    let search = {
+        let graph = graph.clone();
+        let query = query.clone();
+        let results = results.clone();
+        let current = current.clone();
+        move |value: String| {
+            let found = graph.as_ref().and_then(|graph| {
+                graph.with_graph(|graph| {
+                    let nodes = graph.search(&value);
+                    match value.trim().is_empty() {
+                        true => graph.clear_highlight(),
+                        false => graph.highlight(nodes.iter().copied()),
+                    }
+                    nodes
+                        .into_iter()
+                        .take(MAX_RESULTS)
+                        .filter_map(|node| {
+                            let label = graph.storage().properties(node)?.label.clone();
+                            Some((node, label))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            });
+            query.set(value);
+            results.set(found.unwrap_or_default());
+            current.set(None);
+        }
+    };
+
+    let show = {
+        let graph = graph.clone();
+        move |node: Node| {
+            if let Some(graph) = &graph {
+                graph.with_graph(|graph| {
+                    graph.set_selection([node]);
+                    // close enough to read the label
+                    let scale = graph.viewport().scale;
+                    if scale < 1.0 {
+                        graph.zoom(1.0 / scale);
+                    }
+                    if let Some(center) = graph.storage().center(node) {
+                        graph.center_on(center);
+                    }
+                });
+            }
+        }
+    };
+
+    let oninput = {
+        let search = search.clone();
+        Callback::from(move |evt: InputEvent| {
+            search(evt.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let onkeydown = {
+        let results = results.clone();
+        let current = current.clone();
+        let show = show.clone();
+        Callback::from(move |evt: KeyboardEvent| {
+            let len = results.len();
+            match evt.key().as_str() {
+                "ArrowDown" if len > 0 => {
+                    evt.prevent_default();
+                    current.set(Some(current.map_or(0, |i| (i + 1) % len)));
+                }
+                "ArrowUp" if len > 0 => {
+                    evt.prevent_default();
+                    current.set(Some(current.map_or(len - 1, |i| (i + len - 1) % len)));
+                }
+                "Enter" => {
+                    if let Some((node, _)) = results.get(current.unwrap_or(0)) {
+                        show(*node);
+                    }
+                }
+                "Escape" => search(String::new()),
+                _ => {}
+            }
+        })
+    };
+
+    html!(
+        <div
+            id={&props.id}
+            class={classes!("web-graph-search", props.class.clone())}
+            style={&props.style}
+        >
+            <input
+                type="search"
+                placeholder={&props.placeholder}
+                value={(*query).clone()}
+                disabled={graph.is_none()}
+                {oninput}
+                {onkeydown}
+            />
+            if !results.is_empty() {
+                <ul role="listbox" style="margin: 0; padding: 0; list-style: none;">
+                    { for results.iter().enumerate().map(|(i, (node, label))| {
+                        let onclick = {
+                            let show = show.clone();
+                            let node = *node;
+                            Callback::from(move |_: MouseEvent| show(node))
+                        };
+                        let selected = *current == Some(i);
+                        let style = match selected {
+                            true => "cursor: pointer; background: #eee;",
+                            false => "cursor: pointer;",
+                        };
+                        html!(
+                            <li
+                                role="option"
+                                aria-selected={selected.to_string()}
+                                {style}
+                                {onclick}
+                            >
+                                { label }
+                            </li>
+                        )
+                    }) }
+                </ul>
+            }
+        </div>
+    )
+}
@@ -0,0 +1,158 @@
+use super::use_graph_or;
+use crate::graph::{GraphController, InteractionMode};
+use yew::prelude::*;
+
+/// The factor each click on the zoom buttons zooms by.
+const ZOOM_STEP: f64 = 1.25;
+/// The margin kept around the nodes when zooming to fit, in canvas pixels.
+const FIT_MARGIN: f64 = 20.0;
+
+const MODES: [(InteractionMode, &str); 4] = [
+    (InteractionMode::View, "View"),
+    (InteractionMode::Pan, "Pan"),
+    (InteractionMode::Select, "Select"),
+    (InteractionMode::Edit, "Edit"),
+];
+
+#[derive(PartialEq, Properties)]
+pub struct GraphToolbarProperties {
+    /// The graph to control, as provided by [`super::GraphCanvasProperties::onready`].
+    ///
+    /// Defaults to the graph of the enclosing [`super::GraphProvider`].
+    ///
+    /// Without a graph, all buttons are disabled.
+    #[prop_or_default]
+    pub graph: Option<GraphController>,
+
+    /// If buttons for switching the [`InteractionMode`] are shown.
+    #[prop_or(true)]
+    pub modes: bool,
+
+    /// If a button for showing the graph in fullscreen is shown.
+    #[prop_or(true)]
+    pub fullscreen: bool,
+
+    #[prop_or_default]
+    pub id: AttrValue,
+
+    #[prop_or_default("display: flex; gap: 4px;")]
+    pub style: AttrValue,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// Buttons for zooming, fitting the graph into the view, re-running the layout, fullscreen, and
+/// switching the interaction mode of a graph shown by a [`super::GraphCanvas`].
+#[function_component(GraphToolbar)]
+pub fn graph_toolbar(props: &GraphToolbarProperties) -> Html {
+    let graph = use_graph_or(&props.graph);
+    // the mode is changed on the graph, so the toolbar needs to render again
+    let update = use_force_update();
+
+    let action = |f: fn(&GraphController)| {
+        let graph = graph.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(graph) = &graph {
+                f(graph);
+            }
+        })
+    };
+
+    let disabled = graph.is_none();
+
+    let fullscreen = props.fullscreen.then(|| {
+        html!(
+            <button
+                type="button"
+                title="Toggle fullscreen"
+                {disabled}
+                onclick={action(|graph| {
+                    if let Some(Err(err)) = graph.toggle_fullscreen() {
+                        log::warn!("Failed to enter fullscreen: {err}");
+                    }
+                })}
+            >
+                { "Fullscreen" }
+            </button>
+        )
+    });
+    let current = graph
+        .as_ref()
+        .and_then(|graph| graph.with_graph(|graph| graph.interaction_mode()));
+
+    let modes = props.modes.then(|| {
+        html!(
+            { for MODES.iter().map(|(mode, label)| {
+                let onclick = {
+                    let graph = graph.clone();
+                    let update = update.clone();
+                    let mode = *mode;
+                    Callback::from(move |_: MouseEvent| {
+                        if let Some(graph) = &graph {
+                            graph.with_graph(|graph| graph.set_interaction_mode(mode));
+                            update.force_update();
+                        }
+                    })
+                };
+                let pressed = (current == Some(*mode)).to_string();
+                html!(
+                    <button type="button" aria-pressed={pressed} {disabled} {onclick}>
+                        { label }
+                    </button>
+                )
+            }) }
+        )
+    });
+
+    html!(
+        <div
+            id={&props.id}
+            class={classes!("web-graph-toolbar", props.class.clone())}
+            style={&props.style}
+            role="toolbar"
+        >
+            <button
+                type="button"
+                title="Zoom in"
+                {disabled}
+                onclick={action(|graph| {
+                    graph.zoom(ZOOM_STEP);
+                })}
+            >
+                { "+" }
+            </button>
+            <button
+                type="button"
+                title="Zoom out"
+                {disabled}
+                onclick={action(|graph| {
+                    graph.zoom(1.0 / ZOOM_STEP);
+                })}
+            >
+                { "−" }
+            </button>
+            <button
+                type="button"
+                title="Zoom to fit"
+                {disabled}
+                onclick={action(|graph| {
+                    graph.fit(FIT_MARGIN);
+                })}
+            >
+                { "Fit" }
+            </button>
+            <button
+                type="button"
+                title="Re-run the layout"
+                {disabled}
+                onclick={action(|graph| {
+                    graph.with_graph(|graph| graph.reheat());
+                })}
+            >
+                { "Layout" }
+            </button>
+            { fullscreen }
+            { modes }
+        </div>
+    )
+}
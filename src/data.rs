@@ -0,0 +1,84 @@
+//! A description of a whole graph, for saving and restoring it.
+//!
+//! With the `serde` feature, it can be serialized in any format supported by serde, e.g. JSON.
+
+use crate::graph::{Category, Position, Size};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum GraphDataError {
+    #[error("duplicate node key: {0}")]
+    DuplicateKey(String),
+    #[error("edge to unknown node: {0}")]
+    UnknownNode(String),
+}
+
+/// The nodes, edges, and categories of a graph, see [`crate::graph::Graph::to_data`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphData {
+    pub nodes: Vec<NodeData>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub edges: Vec<EdgeData>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub categories: BTreeMap<String, Category>,
+}
+
+impl GraphData {
+    /// Check that the keys of the nodes are unique, and that the edges only refer to them.
+    pub fn validate(&self) -> Result<(), GraphDataError> {
+        let mut keys = HashSet::new();
+        for node in &self.nodes {
+            if !keys.insert(node.key.as_str()) {
+                return Err(GraphDataError::DuplicateKey(node.key.clone()));
+            }
+        }
+        for edge in &self.edges {
+            for key in [&edge.from, &edge.to] {
+                if !keys.contains(key.as_str()) {
+                    return Err(GraphDataError::UnknownNode(key.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeData {
+    /// Identifies the node for the edges, unique within the data.
+    pub key: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub label: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub category: Option<String>,
+    /// The position of the center, in graph coordinates.
+    pub position: Position,
+    /// A fixed size, otherwise the node fits its label.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub size: Option<Size>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeData {
+    /// The key of the source node.
+    pub from: String,
+    /// The key of the target node.
+    pub to: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub length: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default = "default_strength"))]
+    pub strength: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub category: Option<String>,
+    /// Points the edge passes through, ordered from source to target.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub waypoints: Vec<Position>,
+}
+
+#[cfg(feature = "serde")]
+fn default_strength() -> f64 {
+    crate::graph::EdgeProperties::default().strength
+}
@@ -0,0 +1,69 @@
+//! Exporting the graph as an image.
+
+use crate::graph::Error;
+use js_sys::{Array, Object, Promise, Reflect};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, ClipboardItem, HtmlCanvasElement};
+
+/// What to export, see [`crate::graph::Graph::render_image`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportOptions {
+    pub area: ExportArea,
+    /// A CSS color filling the background, which is transparent otherwise.
+    pub background: Option<String>,
+    /// Image pixels per CSS pixel, e.g. `4.0` for printing, independent of the size on screen.
+    ///
+    /// By default, this is the device pixel ratio. Browsers limit the size of canvases, larger
+    /// images fail to encode.
+    pub scale: Option<f64>,
+}
+
+/// The part of the graph to export.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExportArea {
+    /// What is currently visible, at the current zoom level.
+    #[default]
+    Viewport,
+    /// All nodes, unzoomed, with a margin around them in CSS pixels.
+    Graph { margin: f64 },
+}
+
+/// Start encoding the content of a canvas as PNG.
+///
+/// The promise resolves to the `Blob`, or to `null` if the image couldn't be created, e.g.
+/// because it's empty.
+fn png_promise(canvas: &HtmlCanvasElement) -> Result<Promise, Error> {
+    let mut result = Ok(());
+    let promise = Promise::new(&mut |resolve, _| {
+        result = canvas.to_blob(&resolve);
+    });
+    result?;
+    Ok(promise)
+}
+
+/// Encode the content of a canvas as PNG.
+pub async fn to_png(canvas: &HtmlCanvasElement) -> Result<Blob, Error> {
+    JsFuture::from(png_promise(canvas)?)
+        .await?
+        .dyn_into::<Blob>()
+        .map_err(|_| Error::Encoding)
+}
+
+/// Start writing the content of a canvas to the clipboard, as PNG.
+///
+/// This happens right away, instead of when awaiting the returned promise, as browsers only
+/// allow writing to the clipboard while handling a user gesture, like a click.
+pub fn write_png_to_clipboard(canvas: &HtmlCanvasElement) -> Result<Promise, Error> {
+    let clipboard = web_sys::window()
+        .ok_or(Error::MissingWindow)?
+        .navigator()
+        .clipboard();
+
+    // the clipboard item takes the promise, so that encoding doesn't delay the write
+    let items = Object::new();
+    Reflect::set(&items, &"image/png".into(), &png_promise(canvas)?.into())?;
+    let item = ClipboardItem::new_with_record_from_str_to_blob_promise(&items)?;
+
+    Ok(clipboard.write(&Array::of1(&item)))
+}
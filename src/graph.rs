@@ -1,19 +1,69 @@
-use gloo_events::EventListener;
+use gloo_events::{EventListener, EventListenerOptions};
 use js_sys::{
-    Math::{abs, atan2, cos, max, min, pow, sin, sqrt},
+    Math::{pow, sqrt},
     Object,
 };
-use std::cell::RefCell;
+use std::cell::{Cell, OnceCell, RefCell};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::hash::Hash;
 use std::mem::swap;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{EventTarget, HtmlCanvasElement, MouseEvent};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Blob, CanvasRenderingContext2d, ClipboardEvent, DataTransfer, Document, DragEvent, Element,
+    EventTarget, HtmlCanvasElement, HtmlElement, KeyboardEvent, MouseEvent, PointerEvent,
+    ResizeObserver, SvgElement, WheelEvent,
+};
+
+use crate::clipboard::{Subgraph, SubgraphEdge, SubgraphNode};
+use crate::data::{EdgeData, GraphData, GraphDataError, NodeData};
+use crate::export::{to_png, write_png_to_clipboard, ExportArea, ExportOptions};
+use crate::label::{Label, LabelStyle};
+use crate::layout::{
+    Align, Alignment, Arrangement, Axis, Constraint, Distribute, ForceLayout, Layout,
+    LayoutMetrics, OverlapRemoval, PhysicsConfig, StaticLayout,
+};
+use crate::render::{Canvas2dRenderer, Detail, EdgeView, Frame, LevelOfDetail, NodeView, Renderer};
+use crate::storage::{EdgeState, GraphStorage, NodeState};
+use crate::transition::{Animation, Transition};
+use crate::view::{Bounds, ViewState, Viewport};
 
-const MAX_MOVE: f64 = 5.0;
+/// Elements this close to the visible area (in screen pixels) still get drawn.
+const CULL_MARGIN: f64 = 50.0;
+/// The fixed time step of the layout, in seconds.
+const LAYOUT_STEP: f64 = 1.0 / 60.0;
+/// Upper bound of the time caught up in a single frame, e.g. after the tab was in the background.
+const MAX_FRAME_TIME: f64 = 0.25;
+/// The range of zoom levels reachable using the mouse wheel.
+pub(crate) const MIN_SCALE: f64 = 0.05;
+pub(crate) const MAX_SCALE: f64 = 20.0;
+/// Zoom factor per pixel scrolled using the mouse wheel, on a logarithmic scale.
+pub(crate) const WHEEL_ZOOM_SPEED: f64 = 0.002;
+/// Pixels per line, for wheel events scrolling by line.
+pub(crate) const WHEEL_LINE_HEIGHT: f64 = 16.0;
+/// A pointer moving less than this (in screen pixels) while pressed is a tap.
+const TAP_DISTANCE: f64 = 10.0;
+/// The maximum time between two taps of a double click, in milliseconds.
+const DOUBLE_CLICK_TIME: f64 = 400.0;
+/// Clicks this close to an edge (in screen pixels) hit it.
+const EDGE_HIT_DISTANCE: f64 = 5.0;
+/// The default distance around nodes still hitting them, in screen pixels.
+const HIT_TOLERANCE: f64 = 3.0;
+/// How far pasted nodes are moved from where they were copied, in screen pixels.
+const PASTE_OFFSET: f64 = 20.0;
+/// The size of the handles for resizing nodes, in screen pixels.
+const HANDLE_SIZE: f64 = 8.0;
+/// The opacity of nodes and edges which aren't highlighted, see [`Graph::highlight`].
+const HIGHLIGHT_FADE: f64 = 0.2;
+/// The most layout steps computed in a single frame, with reduced motion.
+const REDUCED_MOTION_STEPS: usize = 300;
+/// The media query of the user preferring reduced motion.
+const REDUCED_MOTION_QUERY: &str = "(prefers-reduced-motion: reduce)";
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -21,6 +71,16 @@ pub enum Error {
     Web(String),
     #[error("missing canvas context")]
     MissingContext,
+    #[error("not running in a browser")]
+    MissingWindow,
+    #[error("missing element to display the graph in")]
+    MissingElement,
+    #[error("an edge refers to the missing node {0:?}")]
+    MissingNode(Node),
+    #[error("the graph is no longer running")]
+    Stopped,
+    #[error("failed to encode the image")]
+    Encoding,
 }
 
 impl From<JsValue> for Error {
@@ -39,28 +99,231 @@ impl From<Object> for Error {
     }
 }
 
-pub struct Graph {
-    canvas: HtmlCanvasElement,
-    counter: usize,
-    nodes: HashMap<Node, Rc<RefCell<NodeState>>>,
+/// A callback, notifying about a change of the graph.
+type GraphCallback = Box<dyn FnMut(&mut Graph)>;
+/// A callback, notifying about an interaction with a node.
+type NodeCallback = Box<dyn FnMut(&mut Graph, Node)>;
+/// A callback, notifying about the node now being hovered, if any.
+type HoverCallback = Box<dyn FnMut(&mut Graph, Option<Node>)>;
+/// A callback, notifying about an error while running the graph.
+type ErrorCallback = Box<dyn FnMut(&mut Graph, Error)>;
+/// A callback, notifying about an interaction with an edge, by its two nodes.
+type EdgeCallback = Box<dyn FnMut(&mut Graph, Node, Node)>;
+/// A callback, notifying about an interaction at a position, in graph coordinates.
+type PositionCallback = Box<dyn FnMut(&mut Graph, Position)>;
+/// A callback, deciding if and how an edge between two nodes gets created.
+type EdgeCreateCallback = Box<dyn FnMut(&mut Graph, Node, Node) -> Option<EdgeProperties>>;
+/// A callback, showing (or hiding with `None`) the tooltip of a node, at a rectangle in client
+/// coordinates.
+type TooltipCallback = Box<dyn FnMut(&mut Graph, Option<(Node, Rect)>)>;
+/// A callback, notifying about a request for a context menu, at a position in client
+/// coordinates.
+type ContextMenuCallback = Box<dyn FnMut(&mut Graph, Target, Position)>;
+/// A callback, notifying about data dropped at a position in graph coordinates.
+type DropCallback = Box<dyn FnMut(&mut Graph, Position, DataTransfer)>;
+/// A callback, deciding if the selected nodes and edges may be deleted.
+type DeleteCallback = Box<dyn FnMut(&mut Graph, &[Node], &[(Node, Node)]) -> bool>;
+/// A test, if a position in graph coordinates hits a node covering a rectangle.
+type HitTest = Box<dyn Fn(Node, Rect, Position) -> bool>;
+/// A callback, notifying about the new size of a node.
+type NodeResizeCallback = Box<dyn FnMut(&mut Graph, Node, Size)>;
+/// A callback, deciding if and how a node gets created at a position.
+type NodeCreateCallback =
+    Box<dyn FnMut(&mut Graph, Position) -> Option<(NodeProperties, NodeSize)>>;
 
-    edges: HashMap<Node, HashMap<Node, Rc<EdgeState>>>,
-    edges_rev: HashMap<Node, HashMap<Node, Rc<EdgeState>>>,
+pub struct Graph {
+    /// The element the graph is displayed in, receiving input events
+    element: Element,
+    storage: GraphStorage,
+    layout: Box<dyn Layout>,
+    physics: PhysicsConfig,
+    /// If the layout is not stepped with each frame
+    layout_paused: bool,
+    /// The timestamp of the last frame, in milliseconds
+    last_frame: Option<f64>,
+    /// Time not yet simulated by the layout, in seconds
+    pending_time: f64,
+    /// If the layout was stable with the last frame
+    stable: bool,
+    on_stabilized: Option<GraphCallback>,
+    on_node_hover: Option<HoverCallback>,
+    on_selection_change: Option<GraphCallback>,
+    on_error: Option<ErrorCallback>,
+    /// The hovered node, as last reported
+    reported_hover: Option<Node>,
+    /// The selected nodes and edges, as last reported
+    reported_selection: (BTreeSet<Node>, BTreeSet<(Node, Node)>),
+    on_node_click: Option<NodeCallback>,
+    on_node_dblclick: Option<NodeCallback>,
+    on_edge_click: Option<EdgeCallback>,
+    on_background_click: Option<PositionCallback>,
+    on_edge_create: Option<EdgeCreateCallback>,
+    on_node_create: Option<NodeCreateCallback>,
+    on_node_resize: Option<NodeResizeCallback>,
+    on_tooltip: Option<TooltipCallback>,
+    on_context_menu: Option<ContextMenuCallback>,
+    on_delete: Option<DeleteCallback>,
+    on_drop: Option<DropCallback>,
+    /// Time to hover a node before its tooltip is shown, in milliseconds
+    tooltip_delay: f64,
+    /// The focused node, and the depth of its neighborhood
+    focus: Option<(Node, usize)>,
+    focus_style: FocusStyle,
+    categories: BTreeMap<String, Category>,
+    /// The highlighted nodes, everything else gets faded
+    highlighted: Option<BTreeSet<Node>>,
+    /// The opacity of nodes and edges not connected to the hovered node, if highlighting
+    neighbor_highlight: Option<f64>,
+    /// The hovered node, and when hovering started
+    hover_start: Option<(Node, f64)>,
+    /// The node and rectangle the tooltip was last shown for
+    tooltip: Option<(Node, Rect)>,
+    /// Nodes added since the layout was last notified
+    added: BTreeSet<Node>,
+    /// Existing nodes which got new edges since the layout was last notified
+    touched: BTreeSet<Node>,
+    transition: Transition,
+    /// Nodes moving towards the positions of the last arrangement
+    animation: Option<Animation>,
+    /// Show the results of the layout without animating them
+    reduced_motion: bool,
 
     hovering: Option<Node>,
     dragging: bool,
+    /// Pressed pointers by ID, in element coordinates
+    pointers: HashMap<i32, Position>,
+    /// Where a single pointer was pressed, as long as it may still be a tap
+    tap: Option<Position>,
+    /// Timestamp and position of the last click, which may become a double click
+    last_click: Option<(f64, Position)>,
+    background_drag: BackgroundDrag,
+    /// The selection rectangle, while it is drawn
+    marquee: Option<Marquee>,
+    /// The edge being created, while it is drawn
+    connecting: Option<Connection>,
+    /// Which gestures are active
+    mode: InteractionMode,
+    /// If dragging and editing is disabled, independent of the mode
+    read_only: bool,
+    resize_limits: ResizeLimits,
+    /// The node being resized, by one of its handles
+    resizing: Option<(Node, ResizeHandle)>,
+    /// The edge, and the handle of its waypoint, the user is moving, along with the position the
+    /// pointer was pressed at
+    ///
+    /// A new waypoint is only inserted once the pointer moved further than a tap.
+    bending: Option<(Node, Node, WaypointHandle, Position)>,
+    hit_test: Option<HitTest>,
+    /// Distance around nodes still hitting them, in screen pixels
+    hit_tolerance: f64,
+    /// The resize handle below the pointer, while not pressed
+    hover_handle: Option<ResizeHandle>,
+    cursors: Cursors,
+    /// The cursor last set on the element
+    cursor: String,
+
+    viewport: Viewport,
+    bounds: Bounds,
+    selection: BTreeSet<Node>,
+    /// Selected edges, with the smaller node first
+    selected_edges: BTreeSet<(Node, Node)>,
+
+    label_style: LabelStyle,
+
+    /// If the graph needs to be redrawn
+    dirty: Cell<bool>,
+    /// The number of requested redraws, for the views drawing the graph elsewhere
+    redraws: Cell<u64>,
+
+    renderer: RefCell<Box<dyn Renderer>>,
+    level_of_detail: LevelOfDetail,
+    /// The size of the element, in CSS pixels
+    display_size: Cell<Size>,
+    measure_context: OnceCell<CanvasRenderingContext2d>,
 }
 
 impl Graph {
+    /// Create a new graph, drawing on a canvas using the [`Canvas2dRenderer`].
     pub fn new(canvas: HtmlCanvasElement) -> Self {
+        Self::with_renderer(canvas.clone().into(), Canvas2dRenderer::new(canvas))
+    }
+
+    /// Create a new graph, displayed in the provided element using a custom renderer.
+    ///
+    /// The element is used for sizing and receiving input events, the renderer must draw into it
+    /// (or something which covers it).
+    pub fn with_renderer(element: Element, renderer: impl Renderer + 'static) -> Self {
         let result = Self {
-            canvas,
-            counter: 0,
-            nodes: Default::default(),
-            edges: Default::default(),
-            edges_rev: Default::default(),
+            element,
+            storage: Default::default(),
+            layout: Box::new(ForceLayout::default()),
+            physics: Default::default(),
+            layout_paused: false,
+            last_frame: None,
+            pending_time: 0.0,
+            stable: false,
+            on_stabilized: None,
+            on_node_hover: None,
+            on_selection_change: None,
+            on_error: None,
+            reported_hover: None,
+            reported_selection: Default::default(),
+            on_node_click: None,
+            on_node_dblclick: None,
+            on_edge_click: None,
+            on_background_click: None,
+            on_edge_create: None,
+            on_node_create: None,
+            on_node_resize: None,
+            on_tooltip: None,
+            on_context_menu: None,
+            on_delete: None,
+            on_drop: None,
+            tooltip_delay: 500.0,
+            focus: None,
+            focus_style: Default::default(),
+            categories: Default::default(),
+            highlighted: None,
+            neighbor_highlight: None,
+            hover_start: None,
+            tooltip: None,
+            added: Default::default(),
+            touched: Default::default(),
+            transition: Default::default(),
+            animation: None,
+            reduced_motion: prefers_reduced_motion(),
             hovering: None,
             dragging: false,
+            pointers: Default::default(),
+            tap: None,
+            last_click: None,
+            background_drag: Default::default(),
+            marquee: None,
+            connecting: None,
+            mode: Default::default(),
+            read_only: false,
+            resize_limits: Default::default(),
+            resizing: None,
+            bending: None,
+            hit_test: None,
+            hit_tolerance: HIT_TOLERANCE,
+            hover_handle: None,
+            cursors: Default::default(),
+            cursor: String::new(),
+            viewport: Default::default(),
+            bounds: Default::default(),
+            selection: Default::default(),
+            selected_edges: Default::default(),
+            label_style: Default::default(),
+            dirty: Cell::new(true),
+            redraws: Cell::new(0),
+            renderer: RefCell::new(Box::new(renderer)),
+            level_of_detail: Default::default(),
+            display_size: Cell::new(Size {
+                width: 0.0,
+                height: 0.0,
+            }),
+            measure_context: Default::default(),
         };
 
         result.adjust_resolution();
@@ -68,11 +331,14 @@ impl Graph {
         result
     }
 
+    /// Size the renderer to the displayed size of the element, in device pixels.
+    ///
+    /// While the graph is running, this happens automatically when the element gets resized.
     pub fn adjust_resolution(&self) {
         fn get_style_dimensions(graph: &Graph) -> Option<(f64, f64)> {
-            let window = gloo_utils::window();
+            let window = web_sys::window()?;
 
-            if let Ok(Some(style)) = window.get_computed_style(&graph.canvas) {
+            if let Ok(Some(style)) = window.get_computed_style(&graph.element) {
                 if let (Ok(width), Ok(height)) = (
                     style.get_property_value("width"),
                     style.get_property_value("height"),
@@ -91,441 +357,3873 @@ impl Graph {
         }
 
         if let Some((width, height)) = get_style_dimensions(self) {
-            let window = gloo_utils::window();
-            let dpi = window.device_pixel_ratio();
+            self.display_size.set(Size { width, height });
+
+            let dpi = device_pixel_ratio();
 
             let width = (width * dpi) as u32;
             let height = (height * dpi) as u32;
 
-            self.canvas.set_width(width);
-            self.canvas.set_height(height);
+            self.renderer.borrow_mut().resize(width, height);
+
+            // changing the size clears the canvas
+            self.request_redraw();
         }
     }
 
-    pub fn add_node(
-        &mut self,
-        position: impl Into<Position>,
-        size: impl Into<Size>,
-        node: NodeProperties,
-    ) -> Node {
-        let handle = Node { id: self.counter };
-        self.counter += 1;
-
-        let state = NodeState {
-            properties: node,
-            handle,
-            position: position.into(),
-            size: size.into(),
-        };
-
-        self.nodes.insert(handle, Rc::new(RefCell::new(state)));
-
-        handle
+    /// If the element is shown in fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        document()
+            .ok()
+            .and_then(|document| document.fullscreen_element())
+            .is_some_and(|element| element == self.element)
     }
 
-    pub fn remove_node(&mut self, node: Node) {}
-
-    pub fn add_edge(&mut self, mut a: Node, mut b: Node, edge: EdgeProperties) {
-        let state = Rc::new(EdgeState { properties: edge });
+    /// Show the element in fullscreen, using the Fullscreen API of the browser.
+    ///
+    /// Browsers only allow this in response to a user interaction, like a click.
+    pub fn request_fullscreen(&self) -> Result<(), Error> {
+        Ok(self.element.request_fullscreen()?)
+    }
 
-        match a.cmp(&b) {
-            Ordering::Equal => return,
-            Ordering::Less => {}
-            Ordering::Greater => {
-                // ensure that the smaller one is "a", so that we don't create duplicate entries
-                swap(&mut a, &mut b);
+    /// Leave fullscreen, after [`Self::request_fullscreen`].
+    pub fn exit_fullscreen(&self) {
+        if self.is_fullscreen() {
+            if let Ok(document) = document() {
+                document.exit_fullscreen();
             }
         }
+    }
 
-        // we add them twice, in both directions
-        self.edges.entry(a).or_default().insert(b, state.clone());
-        self.edges_rev.entry(b).or_default().insert(a, state);
+    /// Adapt to a changed size of the element, keeping the center of the view in place.
+    fn resized(&mut self) {
+        let before = self.display_size.get();
+        self.adjust_resolution();
+        let after = self.display_size.get();
+
+        if before.width > 0.0 && before.height > 0.0 && before != after {
+            self.pan_by(
+                (after.width - before.width) / 2.0,
+                (after.height - before.height) / 2.0,
+            );
+        }
     }
 
-    pub fn remove_edge(&mut self, edge: Edge) {}
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
 
-    pub fn draw(&self) -> Result<(), Error> {
-        let ctx = self
-            .canvas
-            .get_context("2d")?
-            .ok_or_else(|| Error::MissingContext)?
-            .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+        self.request_redraw();
+    }
 
-        // self.adjust_resolution();
+    /// Move the viewport, so that a position in graph coordinates is in the center of the
+    /// element.
+    pub fn center_on(&mut self, position: Position) {
+        let size = self.display_size.get();
+        let mut viewport = self.viewport;
+        viewport.offset = Position {
+            x: size.width / 2.0 - position.x * viewport.scale,
+            y: size.height / 2.0 - position.y * viewport.scale,
+        };
+        self.set_viewport(viewport);
+    }
 
-        ctx.clear_rect(
-            0.0,
-            0.0,
-            self.canvas.width() as _,
-            self.canvas.height() as _,
+    /// Zoom by a factor, keeping the center of the element in place.
+    pub fn zoom(&mut self, factor: f64) {
+        let size = self.display_size.get();
+        self.zoom_at(
+            Position {
+                x: size.width / 2.0,
+                y: size.height / 2.0,
+            },
+            factor,
         );
+    }
 
-        let dpi = gloo_utils::window().device_pixel_ratio();
+    /// Zoom and move the viewport, so that all nodes are visible.
+    ///
+    /// The margin is kept free around the nodes, in canvas pixels.
+    pub fn fit(&mut self, margin: f64) {
+        let size = self.display_size.get();
+        let Some(bounds) = self.storage.bounds() else {
+            return;
+        };
+        if size.width <= 0.0 || size.height <= 0.0 {
+            return;
+        }
 
-        ctx.save();
-        let _ = ctx.scale(dpi, dpi);
+        let scale = ((size.width - margin * 2.0) / bounds.size.width)
+            .min((size.height - margin * 2.0) / bounds.size.height)
+            .clamp(MIN_SCALE, MAX_SCALE);
+        self.viewport.scale = scale;
+        self.center_on(bounds.center());
+    }
 
-        // draw edges first
+    /// Move the viewport by an amount, in canvas pixels.
+    pub fn pan_by(&mut self, dx: f64, dy: f64) {
+        let mut viewport = self.viewport;
+        viewport.offset.x += dx;
+        viewport.offset.y += dy;
+        self.set_viewport(viewport);
+    }
 
-        for (from, edges) in &self.edges {
-            // we can do better here, instead of doing another lookup and unwrapping, we should
-            // find a way to keep a reference to the nodes (from and to).
-            let from = self.nodes.get(from).unwrap();
-            for (to, _edge) in edges {
-                let to = self.nodes.get(to).unwrap();
-                ctx.begin_path();
+    /// Multiply the zoom level by `factor`, keeping the point at `position` (in canvas pixels)
+    /// in place.
+    pub fn zoom_at(&mut self, position: Position, factor: f64) {
+        let scale = (self.viewport.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.set_viewport(self.viewport.zoom_at(position, scale));
+    }
 
-                let Position { x, y } = from.borrow().center();
-                ctx.move_to(x, y);
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
 
-                let Position { x, y } = to.borrow().center();
-                ctx.line_to(x, y);
+    /// Keep nodes inside an area, when moved by the layout or dragged.
+    pub fn set_bounds(&mut self, bounds: Bounds) {
+        self.bounds = bounds;
+        self.confine();
+        self.request_redraw();
+    }
 
-                ctx.stroke();
-            }
+    /// Move all nodes back into the bounds, if there are any.
+    fn confine(&mut self) {
+        let Some(bounds) = self.bounds.rect(self.visible_rect()) else {
+            return;
+        };
+        for node in self.storage.nodes.values() {
+            let mut node = node.borrow_mut();
+            node.position = bounds.confine(node.rect());
         }
+    }
 
-        // next draw nodes
+    /// The selected nodes.
+    ///
+    /// Clicking a node selects it, clicking it with Ctrl (or Cmd) pressed toggles it, and
+    /// clicking the background clears the selection. Dragging a selected node moves all selected
+    /// nodes along.
+    pub fn selection(&self) -> &BTreeSet<Node> {
+        &self.selection
+    }
 
-        ctx.set_fill_style(&JsValue::from_str("red"));
-        for (id, node) in &self.nodes {
-            let node = node.borrow();
+    /// The selected edges, with the smaller node first.
+    ///
+    /// Edges are selected by clicking them, just like nodes.
+    pub fn selected_edges(&self) -> &BTreeSet<(Node, Node)> {
+        &self.selected_edges
+    }
 
-            ctx.begin_path();
-            ctx.fill_rect(
-                node.position.x,
-                node.position.y,
-                node.size.width,
-                node.size.height,
-            );
-            if self.hovering == Some(*id) {
-                ctx.set_line_width(5.0);
-            } else {
-                ctx.set_line_width(1.0);
-            }
-            ctx.rect(
-                node.position.x,
-                node.position.y,
-                node.size.width,
-                node.size.height,
-            );
-            ctx.stroke();
-        }
+    /// The selected nodes, and the edges between them.
+    ///
+    /// With the focus on the graph, Ctrl+C (or Cmd+C) puts this on the clipboard, as
+    /// [`Subgraph::to_json`].
+    pub fn copy_selection(&self) -> Subgraph {
+        let nodes: Vec<Node> = self.selection.iter().copied().collect();
+        let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
 
-        ctx.restore();
+        let edges = self
+            .storage
+            .directed_edges()
+            .filter_map(|(a, b, properties)| {
+                Some(SubgraphEdge {
+                    from: *index.get(&a)?,
+                    to: *index.get(&b)?,
+                    length: properties.length,
+                    strength: properties.strength,
+                    category: properties.category.clone(),
+                    waypoints: self.waypoints(a, b).unwrap_or_default(),
+                })
+            })
+            .collect();
 
-        Ok(())
-    }
+        let nodes = nodes
+            .iter()
+            .filter_map(|node| {
+                let state = self.storage.nodes.get(node)?.borrow();
+                Some(SubgraphNode {
+                    label: state.properties.label.clone(),
+                    category: state.properties.category.clone(),
+                    rect: state.rect(),
+                    fit_label: state.fit_label,
+                })
+            })
+            .collect();
 
-    fn tick(&mut self) {
-        self.walk_edges();
-        // TODO: causes jitter
-        // due to the fact that nodes might get pulled in many different directions, and we apply
-        // movement directly, this causes some jitter. I guess this should be changed more in some
-        // "drag", which is the combined requested movements, also only capping the final movement
-        // not the individual ones.
-        // self.walk_all_nodes();
+        Subgraph { nodes, edges }
     }
 
-    fn walk_all_nodes(&mut self) {
-        for (from, from_state) in &self.nodes {
-            for (to, to_state) in &self.nodes {
-                if from == to {
-                    continue;
-                }
+    /// Describe all nodes, edges, and categories, e.g. for saving the graph.
+    ///
+    /// Nodes are keyed by their handle.
+    pub fn to_data(&self) -> GraphData {
+        let key = |node: Node| node.id.to_string();
 
-                let distance = abs(from_state
-                    .borrow()
-                    .position
-                    .delta(to_state.borrow().position));
-
-                if distance < 100.0 {
-                    // let's move away from it
-                    let delta = -100.0 - distance;
-                    if !self.dragging || self.hovering != Some(*from) {
-                        from_state
-                            .borrow_mut()
-                            .move_to(delta / 2.0, to_state.borrow().center());
-                    }
-                    if !self.dragging || self.hovering != Some(*to) {
-                        to_state
-                            .borrow_mut()
-                            .move_to(delta / 2.0, from_state.borrow().center());
-                    }
-                }
-            }
+        let nodes = self
+            .storage
+            .nodes()
+            .filter_map(|node| {
+                let state = self.storage.nodes.get(&node)?.borrow();
+                let rect = state.rect();
+                Some(NodeData {
+                    key: key(node),
+                    label: state.properties.label.clone(),
+                    category: state.properties.category.clone(),
+                    position: rect.center(),
+                    size: (!state.fit_label).then_some(rect.size),
+                })
+            })
+            .collect();
+
+        let edges = self
+            .storage
+            .directed_edges()
+            .map(|(a, b, properties)| EdgeData {
+                from: key(a),
+                to: key(b),
+                length: properties.length,
+                strength: properties.strength,
+                category: properties.category.clone(),
+                waypoints: self.waypoints(a, b).unwrap_or_default(),
+            })
+            .collect();
+
+        GraphData {
+            nodes,
+            edges,
+            categories: self.categories.clone(),
         }
     }
 
-    fn walk_edges(&mut self) {
-        for (from, edges) in &self.edges {
-            // again, I think we can do better here
-            let from_state = self.nodes.get(from).unwrap();
-            for (to, edge) in edges {
-                let to_state = self.nodes.get(to).unwrap();
+    /// The centers of the nodes, by keys of the application, e.g. for storing the layout.
+    ///
+    /// Nodes without a key are skipped.
+    pub fn export_positions<K, F>(&self, key: F) -> HashMap<K, Position>
+    where
+        K: Eq + Hash,
+        F: Fn(Node) -> Option<K>,
+    {
+        self.storage
+            .nodes()
+            .filter_map(|node| Some((key(node)?, self.storage.center(node)?)))
+            .collect()
+    }
 
-                let distance = abs(from_state
-                    .borrow()
-                    .position
-                    .delta(to_state.borrow().position));
-
-                // the delta we want to move
-                let delta = distance - edge.properties.weight as f64;
-                if abs(delta) > 0.1 {
-                    // move only if we don't drag them
-                    if !self.dragging || self.hovering != Some(*from) {
-                        from_state
-                            .borrow_mut()
-                            .move_to(delta / 2.0, to_state.borrow().center());
-                    }
-                    if !self.dragging || self.hovering != Some(*to) {
-                        to_state
-                            .borrow_mut()
-                            .move_to(delta / 2.0, from_state.borrow().center());
-                    }
-                }
+    /// Move nodes to stored centers, by keys of the application, e.g. for restoring a layout
+    /// for freshly loaded nodes.
+    ///
+    /// Keys without a node are skipped. The layout isn't reheated, but continues from the new
+    /// positions if it is still running, see [`Self::pause_layout`].
+    pub fn apply_positions<K, F>(&mut self, positions: &HashMap<K, Position>, node: F)
+    where
+        F: Fn(&K) -> Option<Node>,
+    {
+        for (key, position) in positions {
+            let Some(node) = node(key) else {
+                continue;
+            };
+            if let Some(animation) = &mut self.animation {
+                animation.remove(node);
             }
+            self.storage.set_center(node, *position);
         }
+        self.confine();
+        self.request_redraw();
     }
 
-    pub fn run(self) -> Handle {
-        fn request_animation_frame(f: &Closure<dyn FnMut()>) {
-            gloo_utils::window()
-                .request_animation_frame(f.as_ref().unchecked_ref())
-                .expect("should register `requestAnimationFrame` OK");
-        }
+    /// Create a graph rendering to a canvas, restoring the nodes, edges, and categories.
+    pub fn from_data(canvas: HtmlCanvasElement, data: &GraphData) -> Result<Self, GraphDataError> {
+        let mut graph = Self::new(canvas);
+        graph.add_data(data)?;
+        Ok(graph)
+    }
 
-        let canvas = self.canvas.clone();
-        let graph = Rc::new(RefCell::new(self));
+    /// Add the nodes, edges, and categories, in addition to the existing ones.
+    ///
+    /// Returns the new nodes by their keys. Nothing is added if the data is invalid.
+    pub fn add_data(&mut self, data: &GraphData) -> Result<HashMap<String, Node>, GraphDataError> {
+        data.validate()?;
 
-        fn mouse_event<F>(
-            target: &EventTarget,
-            event_type: &'static str,
-            graph: &Rc<RefCell<Graph>>,
-            f: F,
-        ) -> EventListener
-        where
-            F: Fn(&mut Graph, &MouseEvent) + 'static,
-        {
-            let graph = graph.clone();
-            EventListener::new(target, event_type, move |evt| {
-                if let Ok(mut graph) = graph.try_borrow_mut() {
-                    if let Some(evt) = evt.dyn_ref::<MouseEvent>() {
-                        f(&mut graph, evt);
-                    }
-                }
-            })
+        for (name, category) in &data.categories {
+            self.set_category(name.clone(), category.clone());
         }
 
-        let mut listeners = vec![];
-
-        listeners.push(mouse_event(&canvas, "mousedown", &graph, |graph, evt| {
-            graph.mouse_down(evt);
-        }));
+        let mut nodes = HashMap::new();
+        for node in &data.nodes {
+            nodes.insert(node.key.clone(), self.add_node_data(node));
+        }
+        for edge in &data.edges {
+            self.add_edge_data(&nodes, edge);
+        }
 
-        listeners.push(mouse_event(&canvas, "mouseup", &graph, |graph, evt| {
-            graph.mouse_up(evt);
-        }));
+        Ok(nodes)
+    }
 
-        listeners.push(mouse_event(&canvas, "mouseout", &graph, |graph, evt| {
-            graph.mouse_out(evt);
-        }));
+    fn add_node_data(&mut self, node: &NodeData) -> Node {
+        let properties = NodeProperties {
+            label: node.label.clone(),
+            category: node.category.clone(),
+        };
+        let handle = self.add_node(node.position, node_size(node), properties);
+        self.set_center(handle, node.position);
+        handle
+    }
 
-        {
-            let graph = graph.clone();
-            listeners.push(EventListener::new(&canvas, "mousemove", move |evt| {
-                if let Ok(mut graph) = graph.try_borrow_mut() {
-                    if let Some(evt) = evt.dyn_ref::<MouseEvent>() {
-                        graph.mouse_move(evt);
-                    }
-                }
-            }))
+    fn add_edge_data(&mut self, nodes: &HashMap<String, Node>, edge: &EdgeData) {
+        let (Some(from), Some(to)) = (nodes.get(&edge.from), nodes.get(&edge.to)) else {
+            return;
+        };
+        let properties = EdgeProperties {
+            length: edge.length,
+            strength: edge.strength,
+            category: edge.category.clone(),
+        };
+        if self.add_edge(*from, *to, properties).is_ok() {
+            self.set_waypoints(*from, *to, edge.waypoints.clone());
         }
+    }
 
-        let f = Rc::new(RefCell::new(None));
-        let g = f.clone();
+    /// Change the graph from one state to another, only touching what changed between them.
+    ///
+    /// The nodes of the keys in `old` are looked up in `nodes`, as returned by
+    /// [`Self::add_data`], which gets updated with the added and removed nodes. Nodes which
+    /// didn't change keep their current positions, changed ones are only moved if their position
+    /// changed. Keys without a node, e.g. because it was removed in the meantime, get a new one,
+    /// along with all of their edges. Categories missing in `new` are kept. Nothing is changed if
+    /// either state is invalid.
+    pub fn apply_diff(
+        &mut self,
+        old: &GraphData,
+        new: &GraphData,
+        nodes: &mut HashMap<String, Node>,
+    ) -> Result<(), GraphDataError> {
+        old.validate()?;
+        new.validate()?;
 
-        *g.borrow_mut() = Some(Closure::new(move || {
-            if let Ok(mut graph) = graph.try_borrow_mut() {
-                graph.tick();
-                let _ = graph.draw();
+        for (name, category) in &new.categories {
+            if old.categories.get(name) != Some(category) {
+                self.set_category(name.clone(), category.clone());
             }
+        }
 
-            request_animation_frame(f.borrow().as_ref().unwrap());
-        }));
+        let edge_key = |edge: &EdgeData| (edge.from.clone(), edge.to.clone());
+        let old_edges: HashMap<_, _> = old.edges.iter().map(|e| (edge_key(e), e)).collect();
+        let new_edges: HashMap<_, _> = new.edges.iter().map(|e| (edge_key(e), e)).collect();
+        let old_nodes: HashMap<_, _> = old.nodes.iter().map(|n| (n.key.as_str(), n)).collect();
+        let new_nodes: HashMap<_, _> = new.nodes.iter().map(|n| (n.key.as_str(), n)).collect();
+
+        for (key, edge) in &old_edges {
+            if new_edges.contains_key(key) {
+                continue;
+            }
+            if let (Some(a), Some(b)) = (nodes.get(&edge.from), nodes.get(&edge.to)) {
+                self.remove_edge(*a, *b);
+            }
+        }
+        for key in old_nodes.keys() {
+            if new_nodes.contains_key(key) {
+                continue;
+            }
+            if let Some(node) = nodes.remove(*key) {
+                self.remove_node(node);
+            }
+        }
 
-        request_animation_frame(g.clone().borrow().as_ref().unwrap());
+        // nodes which got added, so that all of their edges need to be added as well
+        let mut added = HashSet::new();
+        for node in &new.nodes {
+            let existing = nodes
+                .get(&node.key)
+                .copied()
+                .filter(|handle| self.storage.contains(*handle));
+            let (Some(old), Some(handle)) = (old_nodes.get(node.key.as_str()), existing) else {
+                let handle = self.add_node_data(node);
+                nodes.insert(node.key.clone(), handle);
+                added.insert(node.key.as_str());
+                continue;
+            };
+            if *old == node {
+                continue;
+            }
+            if (&old.label, &old.category) != (&node.label, &node.category) {
+                let properties = NodeProperties {
+                    label: node.label.clone(),
+                    category: node.category.clone(),
+                };
+                self.set_properties(handle, properties);
+            }
+            if old.size != node.size {
+                self.set_size(handle, node_size(node));
+            }
+            if old.position != node.position {
+                self.set_center(handle, node.position);
+            }
+        }
 
-        Handle {
-            _render_loop: g,
-            listeners,
+        for edge in &new.edges {
+            // adding an edge replaces an existing one
+            let changed = old_edges.get(&edge_key(edge)) != Some(&edge);
+            if changed || added.contains(edge.from.as_str()) || added.contains(edge.to.as_str()) {
+                self.add_edge_data(nodes, edge);
+            }
         }
+
+        Ok(())
     }
 
-    fn mouse_move(&mut self, evt: &MouseEvent) {
-        //log::info!("Move: {}", Position::from(evt));
-        let position = self.adjust_mouse_position(evt.into());
+    /// Add a copy of a subgraph, moved by an offset in graph coordinates, and select it.
+    ///
+    /// Returns the new nodes, in the order of the subgraph. In [`InteractionMode::Edit`], with
+    /// the focus on the graph, Ctrl+V (or Cmd+V) pastes a subgraph from the clipboard, next to
+    /// where it was copied. Edges referring to nodes outside of the subgraph are skipped.
+    pub fn paste(&mut self, subgraph: &Subgraph, offset: Position) -> Vec<Node> {
+        let moved = |p: Position| Position {
+            x: p.x + offset.x,
+            y: p.y + offset.y,
+        };
 
-        if let Some(selected) = self.hovering.and_then(|n| self.nodes.get_mut(&n)) {
-            if self.dragging {
-                // if we are dragging, we don't lose the selection
-                selected.borrow_mut().set_centered(position);
-            } else if !selected.borrow().contains(position) {
-                // lost selection
-                self.hovering = None;
+        let nodes: Vec<Node> = subgraph
+            .nodes
+            .iter()
+            .map(|node| {
+                let size = match node.fit_label {
+                    true => NodeSize::FitLabel,
+                    false => NodeSize::Fixed(node.rect.size),
+                };
+                let properties = NodeProperties {
+                    label: node.label.clone(),
+                    category: node.category.clone(),
+                };
+                self.add_node(moved(node.rect.position), size, properties)
+            })
+            .collect();
+
+        for edge in &subgraph.edges {
+            let (Some(from), Some(to)) = (nodes.get(edge.from), nodes.get(edge.to)) else {
+                continue;
+            };
+            let (from, to) = (*from, *to);
+            let properties = EdgeProperties {
+                length: edge.length,
+                strength: edge.strength,
+                category: edge.category.clone(),
+            };
+            if self.add_edge(from, to, properties).is_err() {
+                continue;
             }
+            self.set_waypoints(
+                from,
+                to,
+                edge.waypoints.iter().copied().map(moved).collect(),
+            );
         }
 
-        // try selecting a new none
-        if self.hovering.is_none() {
-            self.hovering = self.first_node(position).map(|(id, _)| id.clone());
+        self.selection = nodes.iter().copied().collect();
+        self.selected_edges.clear();
+        self.request_redraw();
+        nodes
+    }
+
+    /// Remove all selected nodes, with their edges, and all selected edges.
+    ///
+    /// In [`InteractionMode::Edit`], the user can do this by pressing Delete or Backspace, see
+    /// [`Self::on_delete`].
+    pub fn delete_selection(&mut self) {
+        for (a, b) in std::mem::take(&mut self.selected_edges) {
+            self.remove_edge(a, b);
+        }
+        for node in std::mem::take(&mut self.selection) {
+            self.remove_node(node);
         }
     }
 
-    fn mouse_down(&mut self, _evt: &MouseEvent) {
-        self.dragging = self.hovering.is_some();
+    pub fn background_drag(&self) -> BackgroundDrag {
+        self.background_drag
     }
 
-    fn mouse_up(&mut self, _evt: &MouseEvent) {
-        self.dragging = false;
+    /// Configure what dragging on the background does.
+    pub fn set_background_drag(&mut self, background_drag: BackgroundDrag) {
+        self.background_drag = background_drag;
     }
 
-    fn mouse_out(&mut self, _evt: &MouseEvent) {
+    pub fn interaction_mode(&self) -> InteractionMode {
+        self.mode
+    }
+
+    /// Switch which gestures are available to the user.
+    ///
+    /// Gestures which are no longer available get cancelled.
+    pub fn set_interaction_mode(&mut self, mode: InteractionMode) {
+        self.mode = mode;
+        self.pointers.clear();
+        self.tap = None;
         self.dragging = false;
-        self.hovering = None;
+        self.marquee = None;
+        self.connecting = None;
+        self.resizing = None;
+        self.bending = None;
+        self.storage.held.clear();
+        self.request_redraw();
     }
 
-    fn first_node(
-        &self,
-        position: impl Into<Position>,
-    ) -> Option<(&Node, &Rc<RefCell<NodeState>>)> {
-        let position = position.into();
-        self.nodes
-            .iter()
-            .find(|(_, n)| n.borrow().contains(position))
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
-    fn adjust_mouse_position(&self, position: Position) -> Position {
-        let rect = self.canvas.get_bounding_client_rect();
-        Position {
-            x: position.x - rect.left(),
-            y: position.y - rect.top(),
+    /// Prevent the user from changing the graph, by dragging or editing.
+    ///
+    /// Hovering, selecting, panning, and zooming stay available, as far as the interaction mode
+    /// allows it.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        if read_only {
+            self.dragging = false;
+            self.connecting = None;
+            self.resizing = None;
+            self.bending = None;
+            self.storage.held.clear();
         }
+        self.request_redraw();
     }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Position {
-    pub x: f64,
-    pub y: f64,
-}
 
-impl Position {
-    pub fn delta(&self, other: Position) -> f64 {
-        sqrt(pow(other.x - self.x, 2.0) + pow(other.y - self.y, 2.0))
+    /// Replace the test, if a position hits a node, e.g. for nodes which aren't rectangular.
+    ///
+    /// The test receives the node, its rectangle grown by the hit tolerance, and the position,
+    /// all in graph coordinates. By default, any position within the rectangle hits the node.
+    ///
+    /// ```ignore
+    /// graph.set_hit_test(|_, rect, position| rect.ellipse_contains(position));
+    /// ```
+    pub fn set_hit_test<F>(&mut self, f: F)
+    where
+        F: Fn(Node, Rect, Position) -> bool + 'static,
+    {
+        self.hit_test = Some(Box::new(f));
     }
-}
 
-impl Display for Position {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}/{}", self.x, self.y)
+    pub fn hit_tolerance(&self) -> f64 {
+        self.hit_tolerance
     }
-}
 
-impl From<(f64, f64)> for Position {
-    fn from((x, y): (f64, f64)) -> Self {
-        Self { x, y }
+    /// Set how far around a node the pointer still hits it, in screen pixels.
+    ///
+    /// This makes small nodes easier to hover and grab.
+    pub fn set_hit_tolerance(&mut self, tolerance: f64) {
+        self.hit_tolerance = tolerance.max(0.0);
     }
-}
 
-impl From<&MouseEvent> for Position {
-    fn from(value: &MouseEvent) -> Self {
-        Self {
-            x: value.client_x() as _,
-            y: value.client_y() as _,
+    /// If a position in graph coordinates hits a node.
+    fn hits(&self, node: Node, state: &NodeState, position: Position) -> bool {
+        let rect = state.rect().grow(self.hit_tolerance / self.viewport.scale);
+        match &self.hit_test {
+            Some(hit_test) => hit_test(node, rect, position),
+            None => rect.contains(position),
         }
     }
-}
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Size {
-    pub width: f64,
-    pub height: f64,
-}
+    pub fn cursors(&self) -> &Cursors {
+        &self.cursors
+    }
 
-impl From<(f64, f64)> for Size {
-    fn from((width, height): (f64, f64)) -> Self {
-        Self { width, height }
+    /// Change the cursors shown for the different interactions.
+    pub fn set_cursors(&mut self, cursors: Cursors) {
+        self.cursors = cursors;
+        self.update_cursor();
     }
-}
 
-pub struct Handle {
-    _render_loop: Rc<RefCell<Option<Closure<dyn FnMut()>>>>,
-    listeners: Vec<EventListener>,
-}
+    /// Show the cursor for the current interaction, on the element.
+    fn update_cursor(&mut self) {
+        let handle = self
+            .resizing
+            .map(|(_, handle)| handle)
+            .or(self.hover_handle.filter(|_| self.pointers.is_empty()));
 
-struct EdgeState {
-    properties: EdgeProperties,
-}
+        let cursor = if self.connecting.is_some() {
+            &self.cursors.connecting
+        } else if let Some(handle) = handle {
+            handle.cursor()
+        } else if self.dragging {
+            &self.cursors.dragging
+        } else if self.hovering.is_some() && self.pointers.is_empty() && self.can_modify() {
+            &self.cursors.node
+        } else {
+            &self.cursors.default
+        };
 
-struct NodeState {
-    properties: NodeProperties,
-    handle: Node,
-    position: Position,
-    size: Size,
-}
+        if *cursor != self.cursor {
+            self.cursor = cursor.to_string();
+            set_style(&self.element, "cursor", cursor);
+        }
+    }
 
-impl NodeState {
-    fn contains(&self, position: impl Into<Position>) -> bool {
-        let position = position.into();
-        position.x >= self.position.x
-            && position.y >= self.position.y
-            && position.x <= (self.position.x + self.size.width)
-            && position.y <= (self.position.y + self.size.height)
+    /// If the user may move nodes.
+    fn can_modify(&self) -> bool {
+        self.mode.can_select() && !self.read_only
     }
 
-    fn set_centered(&mut self, position: impl Into<Position>) {
-        let position = position.into();
-        self.position = Position {
-            x: position.x - self.size.width / 2.0,
-            y: position.y - self.size.height / 2.0,
+    /// If the user may create or resize nodes and edges.
+    fn can_edit(&self) -> bool {
+        self.mode.can_edit() && !self.read_only
+    }
+
+    pub fn resize_limits(&self) -> ResizeLimits {
+        self.resize_limits
+    }
+
+    /// Limit the size of nodes resized by the user.
+    pub fn set_resize_limits(&mut self, limits: ResizeLimits) {
+        self.resize_limits = limits;
+    }
+
+    /// Replace the selection, nodes which don't exist are ignored.
+    pub fn set_selection(&mut self, selection: impl IntoIterator<Item = Node>) {
+        self.selection = selection
+            .into_iter()
+            .filter(|node| self.storage.nodes.contains_key(node))
+            .collect();
+        self.request_redraw();
+    }
+
+    /// Capture the current view (viewport, selection, and hidden categories).
+    ///
+    /// Selected nodes are identified by the keys the function returns for them, selected nodes
+    /// without a key are skipped.
+    pub fn view_state<F>(&self, key: F) -> ViewState
+    where
+        F: Fn(Node) -> Option<String>,
+    {
+        ViewState {
+            viewport: self.viewport,
+            selection: self
+                .selection
+                .iter()
+                .filter_map(|node| key(*node))
+                .collect(),
+            filters: self
+                .categories
+                .iter()
+                .filter(|(_, category)| !category.visible)
+                .map(|(name, _)| name.clone())
+                .collect(),
         }
     }
 
-    fn center(&self) -> Position {
-        Position {
-            x: self.position.x + self.size.width / 2.0,
-            y: self.position.y + self.size.height / 2.0,
+    /// Restore a previously captured view, looking up the selected nodes by the keys the
+    /// function returns for them.
+    ///
+    /// Selected keys without a node in this graph are ignored, the zoom level is limited to the
+    /// supported range. Styled categories are shown unless they are filtered, other filters are
+    /// ignored, see [`Self::set_category_visible`].
+    pub fn apply_view_state<F>(&mut self, state: ViewState, key: F)
+    where
+        F: Fn(Node) -> Option<String>,
+    {
+        let mut viewport = state.viewport;
+        if !viewport.offset.x.is_finite() || !viewport.offset.y.is_finite() {
+            viewport.offset = self.viewport.offset;
+        }
+        viewport.scale = match viewport.scale.is_finite() {
+            true => viewport.scale.clamp(MIN_SCALE, MAX_SCALE),
+            false => self.viewport.scale,
+        };
+        self.set_viewport(viewport);
+
+        let selected: HashSet<String> = state.selection.into_iter().collect();
+        let selection: Vec<Node> = self
+            .storage
+            .nodes()
+            .filter(|node| key(*node).is_some_and(|key| selected.contains(&key)))
+            .collect();
+        self.set_selection(selection);
+
+        let filters: HashSet<String> = state.filters.into_iter().collect();
+        for (name, category) in &mut self.categories {
+            category.visible = !filters.contains(name);
         }
+        self.request_redraw();
     }
 
-    fn move_to(&mut self, amount: f64, position: Position) {
-        let amount = amount.clamp(-MAX_MOVE, MAX_MOVE);
+    pub fn level_of_detail(&self) -> LevelOfDetail {
+        self.level_of_detail
+    }
 
-        let center = self.center();
-        let angle = atan2(position.y - center.y, position.x - center.x);
+    /// Configure when to draw with reduced detail.
+    pub fn set_level_of_detail(&mut self, level_of_detail: LevelOfDetail) {
+        self.level_of_detail = level_of_detail;
+        self.request_redraw();
+    }
 
-        let x = cos(angle) * amount;
-        let y = sin(angle) * amount;
+    /// The nodes and edges of the graph.
+    pub fn storage(&self) -> &GraphStorage {
+        &self.storage
+    }
 
-        //log::info!("Move = x: {x}, y: {y}");
+    /// Arrange all nodes once, and stop the running layout.
+    ///
+    /// This replaces the current layout with a [`StaticLayout`], so that the arrangement
+    /// stays in place. Nodes move to their new positions as configured by
+    /// [`Self::set_transition`].
+    pub fn layout(&mut self, arrangement: impl Arrangement) {
+        self.apply(arrangement);
+        self.layout = Box::new(StaticLayout);
+    }
 
-        self.position.x += x;
-        self.position.y += y;
+    /// Push overlapping nodes apart, without changing the layout.
+    ///
+    /// A running simulation might move them back into an overlap, so this is most useful
+    /// once the layout is stable, or with one-shot arrangements.
+    pub fn remove_overlaps(&mut self, removal: OverlapRemoval) {
+        self.apply(removal);
     }
-}
 
-pub struct EdgeProperties {
-    pub weight: usize,
-}
+    /// Line up the selected nodes, see [`Align`].
+    pub fn align_selection(&mut self, alignment: Alignment) {
+        self.apply(Align {
+            nodes: self.selection.iter().copied().collect(),
+            alignment,
+        });
+    }
 
-pub struct NodeProperties {
-    pub label: String,
-}
+    /// Spread the selected nodes evenly along an axis, see [`Distribute`].
+    pub fn distribute_selection(&mut self, axis: Axis) {
+        self.apply(Distribute {
+            nodes: self.selection.iter().copied().collect(),
+            axis,
+        });
+    }
 
-pub struct Edge {}
+    /// Measure the quality of the current layout.
+    pub fn metrics(&self) -> LayoutMetrics {
+        LayoutMetrics::measure(&self.storage)
+    }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Node {
-    id: usize,
+    fn apply(&mut self, arrangement: impl Arrangement) {
+        let positions = arrangement.arrange(&self.storage);
+        if self.transition.duration > 0.0 && !self.reduced_motion {
+            self.animation = Some(Animation::new(&self.storage, positions, self.transition));
+        } else {
+            self.animation = None;
+            for (node, position) in positions {
+                self.storage.set_center(node, position);
+            }
+            self.confine();
+        }
+        self.request_redraw();
+    }
+
+    pub fn transition(&self) -> Transition {
+        self.transition
+    }
+
+    /// Configure how nodes move to the positions of a new arrangement, see [`Self::layout`].
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    /// Only show the final state of the layout and of arrangements, instead of animating them.
+    ///
+    /// This follows the `prefers-reduced-motion` setting of the user by default, and while
+    /// running, whenever the setting changes.
+    pub fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        self.reduced_motion = reduced_motion;
+        if reduced_motion {
+            if let Some(animation) = self.animation.take() {
+                animation.finish(&mut self.storage);
+                self.confine();
+            }
+        }
+        self.request_redraw();
+    }
+
+    /// Replace the layout, which defaults to [`ForceLayout`].
+    ///
+    /// The layout gets the current [`PhysicsConfig`] applied.
+    pub fn set_layout(&mut self, layout: impl Layout + 'static) {
+        self.layout = Box::new(layout);
+        self.layout.set_physics(&self.physics);
+        self.request_redraw();
+    }
+
+    pub fn physics(&self) -> &PhysicsConfig {
+        &self.physics
+    }
+
+    /// Configure the force-directed simulation of the layout.
+    pub fn set_physics(&mut self, physics: PhysicsConfig) {
+        self.physics = physics;
+        self.layout.set_physics(&self.physics);
+        self.request_redraw();
+    }
+
+    /// Stop stepping the layout with each frame, until resumed.
+    ///
+    /// Nodes can still be dragged, and the layout can be advanced using [`Self::step_layout`].
+    pub fn pause_layout(&mut self) {
+        self.layout_paused = true;
+    }
+
+    pub fn resume_layout(&mut self) {
+        self.layout_paused = false;
+        self.request_redraw();
+    }
+
+    pub fn is_layout_paused(&self) -> bool {
+        self.layout_paused
+    }
+
+    /// If the layout came to rest, as of the last frame.
+    pub fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    /// If the user is currently dragging nodes.
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Advance the layout by `n` steps, even if it is paused.
+    pub fn step_layout(&mut self, n: usize) {
+        self.notify_layout();
+        self.storage.visible = self.visible_rect();
+        for _ in 0..n {
+            self.layout.step(&mut self.storage, LAYOUT_STEP);
+        }
+        self.confine();
+        self.request_redraw();
+    }
+
+    /// Set a callback, which is called once the layout came to rest.
+    ///
+    /// After the layout was reheated, e.g. because the graph changed, the callback will be
+    /// called again once it settled.
+    pub fn on_stabilized<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph) + 'static,
+    {
+        self.on_stabilized = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when the pointer starts or stops hovering a node.
+    pub fn on_node_hover<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Option<Node>) + 'static,
+    {
+        self.on_node_hover = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when the selected nodes or edges changed, by the user or
+    /// the application.
+    ///
+    /// Changes are reported with the next frame, see [`Self::selection`] and
+    /// [`Self::selected_edges`].
+    pub fn on_selection_change<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph) + 'static,
+    {
+        self.on_selection_change = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when drawing a frame failed.
+    ///
+    /// Without a callback, errors are logged. The graph keeps running either way, and tries
+    /// again with the next change.
+    pub fn on_error<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Error) + 'static,
+    {
+        self.on_error = Some(Box::new(f));
+    }
+
+    fn report_error(&mut self, err: Error) {
+        if self.on_error.is_none() {
+            log::warn!("Failed to draw the graph: {err}");
+        }
+        self.emit(|graph| &mut graph.on_error, |f, graph| f(graph, err));
+    }
+
+    /// Report changes of the hovered node and the selection, since the last frame.
+    fn report_changes(&mut self) {
+        if self.hovering != self.reported_hover {
+            self.reported_hover = self.hovering;
+            let hovering = self.hovering;
+            self.emit(
+                |graph| &mut graph.on_node_hover,
+                |f, graph| f(graph, hovering),
+            );
+        }
+
+        if self.selection != self.reported_selection.0
+            || self.selected_edges != self.reported_selection.1
+        {
+            self.reported_selection = (self.selection.clone(), self.selected_edges.clone());
+            self.emit(|graph| &mut graph.on_selection_change, |f, graph| f(graph));
+        }
+    }
+
+    /// Set a callback, which is called when a node is clicked or tapped, without dragging it.
+    pub fn on_node_click<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Node) + 'static,
+    {
+        self.on_node_click = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when a node is clicked or tapped twice.
+    ///
+    /// Both clicks are reported to [`Self::on_node_click`] as well.
+    pub fn on_node_dblclick<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Node) + 'static,
+    {
+        self.on_node_dblclick = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when an edge is clicked, providing the nodes it connects.
+    pub fn on_edge_click<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Node, Node) + 'static,
+    {
+        self.on_edge_click = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when neither a node nor an edge is clicked, providing the
+    /// position in graph coordinates.
+    pub fn on_background_click<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Position) + 'static,
+    {
+        self.on_background_click = Some(Box::new(f));
+    }
+
+    /// Set a callback, enabling the creation of edges by the user.
+    ///
+    /// In [`InteractionMode::Edit`], dragging from a node with Shift pressed draws an edge, and releasing it over another
+    /// node calls the callback with the two nodes. The edge is created with the returned
+    /// properties, returning `None` rejects it.
+    pub fn on_edge_create<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Node, Node) -> Option<EdgeProperties> + 'static,
+    {
+        self.on_edge_create = Some(Box::new(f));
+    }
+
+    /// Set a callback, enabling the creation of nodes by the user.
+    ///
+    /// In [`InteractionMode::Edit`], double-clicking the background calls the callback with the
+    /// position, in graph
+    /// coordinates. A node is created, centered at that position, with the returned properties
+    /// and size. Returning `None` doesn't create a node.
+    pub fn on_node_create<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Position) -> Option<(NodeProperties, NodeSize)> + 'static,
+    {
+        self.on_node_create = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when the user finished resizing a node.
+    ///
+    /// In [`InteractionMode::Edit`], selected nodes show handles for resizing them.
+    pub fn on_node_resize<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Node, Size) + 'static,
+    {
+        self.on_node_resize = Some(Box::new(f));
+    }
+
+    /// Set a callback, which shows or hides the tooltip of a node.
+    ///
+    /// Once a node was hovered for the tooltip delay, the callback is called with the node and
+    /// its rectangle in client coordinates, e.g. for positioning an element using
+    /// `position: fixed`. It is called again whenever the node moves, and with `None` once the
+    /// tooltip should be hidden.
+    pub fn on_tooltip<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Option<(Node, Rect)>) + 'static,
+    {
+        self.on_tooltip = Some(Box::new(f));
+    }
+
+    /// Set a callback, which is called when the user requests a context menu, e.g. by a right
+    /// click.
+    ///
+    /// The callback receives the element below the pointer, and the position of the pointer in
+    /// client coordinates. While the callback is set, the browser's menu is suppressed.
+    pub fn on_context_menu<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Target, Position) + 'static,
+    {
+        self.on_context_menu = Some(Box::new(f));
+    }
+
+    /// Set a callback, deciding if the selection may be deleted by the user.
+    ///
+    /// The callback receives the selected nodes and edges, and returns `false` to keep them.
+    /// Without a callback, deleting is always allowed.
+    pub fn on_delete<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, &[Node], &[(Node, Node)]) -> bool + 'static,
+    {
+        self.on_delete = Some(Box::new(f));
+    }
+
+    /// Set a callback, accepting data dragged onto the graph, e.g. from a palette of the
+    /// application.
+    ///
+    /// The callback receives the position of the drop in graph coordinates, and the data of the
+    /// drag. Dropping is only accepted in [`InteractionMode::Edit`].
+    pub fn on_drop<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Graph, Position, DataTransfer) + 'static,
+    {
+        self.on_drop = Some(Box::new(f));
+    }
+
+    pub fn tooltip_delay(&self) -> f64 {
+        self.tooltip_delay
+    }
+
+    /// Set the time to hover a node before its tooltip is shown, in milliseconds.
+    pub fn set_tooltip_delay(&mut self, delay: f64) {
+        self.tooltip_delay = delay;
+    }
+
+    pub fn neighbor_highlight(&self) -> Option<f64> {
+        self.neighbor_highlight
+    }
+
+    /// Highlight the hovered node, its neighbors, and its edges, by fading everything else to
+    /// an opacity between `0` and `1`.
+    ///
+    /// `None` disables highlighting, which is the default.
+    pub fn set_neighbor_highlight(&mut self, opacity: Option<f64>) {
+        self.neighbor_highlight = opacity.map(|opacity| opacity.clamp(0.0, 1.0));
+        self.request_redraw();
+    }
+
+    /// The focused node, and the depth of the neighborhood in focus.
+    pub fn focused(&self) -> Option<(Node, usize)> {
+        self.focus
+    }
+
+    /// Focus on the neighborhood of a node, fading or hiding everything further away than
+    /// `depth` edges.
+    ///
+    /// How that looks, and if the view gets centered on the node, is configured by
+    /// [`Self::set_focus_style`].
+    pub fn focus(&mut self, node: Node, depth: usize) {
+        if !self.storage.contains(node) {
+            return;
+        }
+
+        self.focus = Some((node, depth));
+        if self.focus_style.recenter {
+            if let Some(center) = self.storage.center(node) {
+                self.center_on(center);
+            }
+        }
+        self.request_redraw();
+    }
+
+    /// Show the full graph again, after [`Self::focus`].
+    pub fn unfocus(&mut self) {
+        self.focus = None;
+        self.request_redraw();
+    }
+
+    pub fn focus_style(&self) -> FocusStyle {
+        self.focus_style
+    }
+
+    pub fn set_focus_style(&mut self, style: FocusStyle) {
+        self.focus_style = style;
+        self.request_redraw();
+    }
+
+    /// The styled categories, by their names.
+    pub fn categories(&self) -> &BTreeMap<String, Category> {
+        &self.categories
+    }
+
+    /// Set how nodes and edges of a category are shown, see [`NodeProperties::category`] and
+    /// [`EdgeProperties::category`].
+    ///
+    /// Nodes and edges without a styled category use the default colors.
+    pub fn set_category(&mut self, name: impl Into<String>, category: Category) {
+        self.categories.insert(name.into(), category);
+        self.request_redraw();
+    }
+
+    /// Show or hide the nodes and edges of a styled category.
+    pub fn set_category_visible(&mut self, name: &str, visible: bool) {
+        if let Some(category) = self.categories.get_mut(name) {
+            category.visible = visible;
+            self.request_redraw();
+        }
+    }
+
+    /// The style of a category, if it is styled.
+    fn category(&self, name: &Option<String>) -> Option<&Category> {
+        self.categories.get(name.as_deref()?)
+    }
+
+    /// If a category is hidden by its style.
+    fn category_hidden(&self, name: &Option<String>) -> bool {
+        self.category(name)
+            .is_some_and(|category| !category.visible)
+    }
+
+    /// If a node is not shown, because of its category or the focus on other nodes.
+    pub fn is_hidden(&self, node: Node) -> bool {
+        self.node_hidden(node)
+            || self
+                .hidden_by_focus()
+                .is_some_and(|focus| !focus.contains(&node))
+    }
+
+    /// If a node is hidden by its category.
+    fn node_hidden(&self, node: Node) -> bool {
+        self.storage
+            .nodes
+            .get(&node)
+            .is_some_and(|state| self.category_hidden(&state.borrow().properties.category))
+    }
+
+    /// The nodes with a label containing the query, ignoring case, in the order they were
+    /// added.
+    ///
+    /// An empty query matches nothing.
+    pub fn search(&self, query: &str) -> Vec<Node> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let mut matches: Vec<Node> = self
+            .storage
+            .nodes
+            .iter()
+            .filter(|(_, state)| {
+                state
+                    .borrow()
+                    .properties
+                    .label
+                    .to_lowercase()
+                    .contains(&query)
+            })
+            .map(|(node, _)| *node)
+            .collect();
+        matches.sort();
+        matches
+    }
+
+    /// The highlighted nodes, if any are.
+    pub fn highlighted(&self) -> Option<&BTreeSet<Node>> {
+        self.highlighted.as_ref()
+    }
+
+    /// Highlight nodes, e.g. the results of [`Self::search`], by fading all other nodes, and
+    /// all edges not between two highlighted nodes.
+    pub fn highlight(&mut self, nodes: impl IntoIterator<Item = Node>) {
+        self.highlighted = Some(
+            nodes
+                .into_iter()
+                .filter(|node| self.storage.contains(*node))
+                .collect(),
+        );
+        self.request_redraw();
+    }
+
+    /// Stop highlighting nodes, after [`Self::highlight`].
+    pub fn clear_highlight(&mut self) {
+        self.highlighted = None;
+        self.request_redraw();
+    }
+
+    /// Show, move, or hide the tooltip, as of the frame's timestamp.
+    fn update_tooltip(&mut self, timestamp: f64) {
+        if self.on_tooltip.is_none() {
+            return;
+        }
+
+        // not while the user is busy with something else
+        let hovering = self.hovering.filter(|_| self.pointers.is_empty());
+
+        let hover_start = match (hovering, self.hover_start) {
+            (Some(node), Some((start_node, start))) if node == start_node => Some((node, start)),
+            (Some(node), _) => Some((node, timestamp)),
+            (None, _) => None,
+        };
+        self.hover_start = hover_start;
+
+        let tooltip = hover_start
+            .filter(|(_, start)| timestamp - start >= self.tooltip_delay)
+            .and_then(|(node, _)| Some((node, self.client_rect(self.storage.rect(node)?))));
+
+        if tooltip != self.tooltip {
+            self.tooltip = tooltip;
+            self.emit(|graph| &mut graph.on_tooltip, |f, graph| f(graph, tooltip));
+        }
+    }
+
+    /// The element the graph is displayed in.
+    pub fn element(&self) -> &Element {
+        &self.element
+    }
+
+    /// Convert a rectangle from graph to client coordinates.
+    pub fn client_rect(&self, rect: Rect) -> Rect {
+        let element = self.element.get_bounding_client_rect();
+        let position = self.viewport.to_screen(rect.position);
+        Rect {
+            position: Position {
+                x: position.x + element.left(),
+                y: position.y + element.top(),
+            },
+            size: Size {
+                width: rect.size.width * self.viewport.scale,
+                height: rect.size.height * self.viewport.scale,
+            },
+        }
+    }
+
+    /// Call a callback of the graph, returning its result if it is set.
+    fn emit<C, R>(
+        &mut self,
+        callback: fn(&mut Graph) -> &mut Option<C>,
+        call: impl FnOnce(&mut C, &mut Graph) -> R,
+    ) -> Option<R> {
+        let mut f = callback(self).take()?;
+        let result = call(&mut f, self);
+        // unless the callback replaced itself
+        callback(self).get_or_insert(f);
+        Some(result)
+    }
+
+    /// Restart the layout simulation, in case it came to rest.
+    ///
+    /// This happens automatically when nodes or edges are added, or a node is dragged.
+    pub fn reheat(&mut self) {
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    /// Replace the renderer, which defaults to [`Canvas2dRenderer`].
+    pub fn set_renderer(&mut self, renderer: impl Renderer + 'static) {
+        self.renderer = RefCell::new(Box::new(renderer));
+        self.adjust_resolution();
+        self.request_redraw();
+    }
+
+    pub fn label_style(&self) -> &LabelStyle {
+        &self.label_style
+    }
+
+    /// Change the label style, re-wrapping all labels and resizing nodes fitting their label.
+    pub fn set_label_style(&mut self, style: LabelStyle) {
+        self.label_style = style;
+
+        if let Ok(ctx) = self.context() {
+            for state in self.storage.nodes.values() {
+                self.layout_label(&ctx, &mut state.borrow_mut());
+            }
+        }
+        self.request_redraw();
+    }
+
+    /// Replace the properties of a node, updating its label.
+    pub fn set_properties(&mut self, node: Node, properties: NodeProperties) {
+        let Some(state) = self.storage.nodes.get(&node).cloned() else {
+            return;
+        };
+
+        let mut state = state.borrow_mut();
+        state.properties = properties;
+        if let Ok(ctx) = self.context() {
+            self.layout_label(&ctx, &mut state);
+        }
+        self.request_redraw();
+    }
+
+    /// Move a node, so that its center is at a position in graph coordinates.
+    pub fn set_center(&mut self, node: Node, position: Position) {
+        if let Some(animation) = &mut self.animation {
+            animation.remove(node);
+        }
+        self.storage.set_center(node, position);
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    /// Change the size of a node, keeping its center in place.
+    pub fn set_size(&mut self, node: Node, size: NodeSize) {
+        let Some(state) = self.storage.nodes.get(&node).cloned() else {
+            return;
+        };
+
+        let mut state = state.borrow_mut();
+        let center = state.center();
+        state.fit_label = size == NodeSize::FitLabel;
+        if let NodeSize::Fixed(size) = size {
+            state.size = size;
+        }
+        if let Ok(ctx) = self.context() {
+            self.layout_label(&ctx, &mut state);
+        }
+        state.set_centered(center);
+
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    /// Add a new node.
+    ///
+    /// The size can either be fixed, e.g. `(50.0, 50.0)`, or [`NodeSize::FitLabel`] to make
+    /// the node as big as its wrapped label.
+    pub fn add_node(
+        &mut self,
+        position: impl Into<Position>,
+        size: impl Into<NodeSize>,
+        node: NodeProperties,
+    ) -> Node {
+        let handle = self.storage.next_handle();
+
+        let size = size.into();
+        let mut state = NodeState {
+            properties: node,
+            handle,
+            position: position.into(),
+            size: match size {
+                NodeSize::Fixed(size) => size,
+                NodeSize::FitLabel => Size {
+                    width: 0.0,
+                    height: 0.0,
+                },
+            },
+            fit_label: size == NodeSize::FitLabel,
+            label: Default::default(),
+            z_index: 0,
+        };
+
+        if let Ok(ctx) = self.context() {
+            self.layout_label(&ctx, &mut state);
+        }
+
+        self.storage
+            .nodes
+            .insert(handle, Rc::new(RefCell::new(state)));
+        self.added.insert(handle);
+        self.request_redraw();
+
+        handle
+    }
+
+    /// Restrict where the layout may move the node.
+    pub fn add_constraint(&mut self, node: Node, constraint: Constraint) {
+        if self.storage.contains(node) {
+            self.storage
+                .constraints
+                .entry(node)
+                .or_default()
+                .push(constraint);
+            self.reheat();
+        }
+    }
+
+    /// Remove all constraints of the node.
+    pub fn clear_constraints(&mut self, node: Node) {
+        if self.storage.constraints.remove(&node).is_some() {
+            self.reheat();
+        }
+    }
+
+    pub fn z_index(&self, node: Node) -> Option<i32> {
+        self.storage
+            .nodes
+            .get(&node)
+            .map(|state| state.borrow().z_index)
+    }
+
+    /// Set the z-index of a node. Nodes with a higher z-index are drawn on top of nodes with a
+    /// lower one. Nodes with the same z-index are drawn in the order they were added.
+    pub fn set_z_index(&mut self, node: Node, z_index: i32) {
+        if let Some(state) = self.storage.nodes.get(&node) {
+            state.borrow_mut().z_index = z_index;
+            self.request_redraw();
+        }
+    }
+
+    /// Move the node one step up.
+    pub fn raise(&mut self, node: Node) {
+        if let Some(z_index) = self.z_index(node) {
+            self.set_z_index(node, z_index.saturating_add(1));
+        }
+    }
+
+    /// Move the node one step down.
+    pub fn lower(&mut self, node: Node) {
+        if let Some(z_index) = self.z_index(node) {
+            self.set_z_index(node, z_index.saturating_sub(1));
+        }
+    }
+
+    /// Put the node on top of all others.
+    pub fn bring_to_front(&mut self, node: Node) {
+        let max = self
+            .storage
+            .nodes
+            .iter()
+            .filter(|(id, _)| **id != node)
+            .map(|(_, state)| state.borrow().z_index)
+            .max();
+        if let Some(max) = max {
+            self.set_z_index(node, max.saturating_add(1));
+        }
+    }
+
+    /// Put the node below all others.
+    pub fn send_to_back(&mut self, node: Node) {
+        let min = self
+            .storage
+            .nodes
+            .iter()
+            .filter(|(id, _)| **id != node)
+            .map(|(_, state)| state.borrow().z_index)
+            .min();
+        if let Some(min) = min {
+            self.set_z_index(node, min.saturating_sub(1));
+        }
+    }
+
+    /// All nodes, in the order they need to be drawn, bottom to top.
+    ///
+    /// Nodes which are currently being dragged are always on top.
+    fn nodes_by_z(&self) -> Vec<(&Node, &Rc<RefCell<NodeState>>)> {
+        let dragged = |id: &Node| self.dragging && self.storage.held.contains(id);
+
+        let mut nodes: Vec<_> = self.storage.nodes.iter().collect();
+        nodes.sort_by_key(|(id, state)| (dragged(id), state.borrow().z_index, **id));
+        nodes
+    }
+
+    fn layout_label(&self, ctx: &CanvasRenderingContext2d, state: &mut NodeState) {
+        let style = &self.label_style;
+
+        let max_width = match state.fit_label {
+            true => style.max_width,
+            false => (state.size.width - style.padding * 2.0).min(style.max_width),
+        };
+
+        state.label = Label::layout(ctx, style, &state.properties.label, max_width);
+
+        if state.fit_label {
+            // keep the center where it was
+            let center = state.center();
+            state.size = state.label.size(style);
+            state.set_centered(center);
+        }
+    }
+
+    /// A context for measuring text.
+    ///
+    /// This uses a detached canvas, as the actual canvas might be used with a different kind of
+    /// context by the renderer.
+    fn context(&self) -> Result<CanvasRenderingContext2d, Error> {
+        if let Some(ctx) = self.measure_context.get() {
+            return Ok(ctx.clone());
+        }
+
+        let ctx = document()?
+            .create_element("canvas")?
+            .unchecked_into::<HtmlCanvasElement>()
+            .get_context("2d")?
+            .ok_or(Error::MissingContext)?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        Ok(self.measure_context.get_or_init(|| ctx).clone())
+    }
+
+    /// Remove a node, along with its edges.
+    pub fn remove_node(&mut self, node: Node) {
+        if !self.storage.remove_node(node) {
+            return;
+        }
+
+        self.selection.remove(&node);
+        if let Some(highlighted) = &mut self.highlighted {
+            highlighted.remove(&node);
+        }
+        if self.focus.is_some_and(|(focus, _)| focus == node) {
+            self.focus = None;
+        }
+        self.selected_edges
+            .retain(|(a, b)| *a != node && *b != node);
+        self.added.remove(&node);
+        self.touched.remove(&node);
+        if let Some(animation) = &mut self.animation {
+            animation.remove(node);
+        }
+
+        // stop interacting with it, the tooltip gets hidden with the next frame
+        if self.hovering == Some(node) {
+            self.hovering = None;
+            self.dragging = false;
+        }
+        if self.connecting.as_ref().is_some_and(|c| c.from == node) {
+            self.connecting = None;
+        }
+        if self.resizing.is_some_and(|(n, _)| n == node) {
+            self.resizing = None;
+        }
+        if self
+            .bending
+            .is_some_and(|(a, b, ..)| a == node || b == node)
+        {
+            self.bending = None;
+        }
+
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    /// Add an edge between two nodes.
+    ///
+    /// Edges are undirected for the purpose of the layout. However, the direction is recorded
+    /// with `a` being the source, for layouts which need it.
+    ///
+    /// Fails if either node doesn't exist, e.g. because it was removed in the meantime.
+    pub fn add_edge(
+        &mut self,
+        mut a: Node,
+        mut b: Node,
+        edge: EdgeProperties,
+    ) -> Result<(), Error> {
+        if let Some(node) = [a, b].into_iter().find(|n| !self.storage.contains(*n)) {
+            return Err(Error::MissingNode(node));
+        }
+
+        let state = Rc::new(EdgeState {
+            properties: edge,
+            source: a,
+            waypoints: Default::default(),
+        });
+
+        match a.cmp(&b) {
+            Ordering::Equal => return Ok(()),
+            Ordering::Less => {}
+            Ordering::Greater => {
+                // ensure that the smaller one is "a", so that we don't create duplicate entries
+                swap(&mut a, &mut b);
+            }
+        }
+
+        // we add them twice, in both directions
+        self.storage
+            .edges
+            .entry(a)
+            .or_default()
+            .insert(b, state.clone());
+        self.storage
+            .edges_rev
+            .entry(b)
+            .or_default()
+            .insert(a, state);
+        self.touched.extend([a, b]);
+        self.request_redraw();
+        Ok(())
+    }
+
+    /// Remove the edge between two nodes.
+    pub fn remove_edge(&mut self, a: Node, b: Node) {
+        if !self.storage.remove_edge(a, b) {
+            return;
+        }
+
+        self.selected_edges.remove(&ordered(a, b));
+        if self
+            .bending
+            .is_some_and(|edge| (edge.0, edge.1) == ordered(a, b))
+        {
+            self.bending = None;
+        }
+
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    /// The points an edge passes through between its nodes, ordered from `a` to `b`.
+    ///
+    /// Returns `None` if there is no edge between the nodes.
+    pub fn waypoints(&self, a: Node, b: Node) -> Option<Vec<Position>> {
+        let (state, reversed) = self.storage.edge(a, b)?;
+        let mut waypoints = state.waypoints.borrow().clone();
+        if reversed {
+            waypoints.reverse();
+        }
+        Some(waypoints)
+    }
+
+    /// Route an edge through points, ordered from `a` to `b`.
+    ///
+    /// Without waypoints, the edge is a straight line. In [`InteractionMode::Edit`], the user can
+    /// move the waypoints of edges connected to selected nodes, or add new ones by dragging the
+    /// middle of a segment.
+    pub fn set_waypoints(&mut self, a: Node, b: Node, mut waypoints: Vec<Position>) {
+        let Some((state, reversed)) = self.storage.edge(a, b) else {
+            return;
+        };
+        if reversed {
+            waypoints.reverse();
+        }
+        *state.waypoints.borrow_mut() = waypoints;
+        self.request_redraw();
+    }
+
+    /// The area currently visible, in graph coordinates.
+    ///
+    /// Returns `None` if the size of the element isn't known yet.
+    pub fn visible_rect(&self) -> Option<Rect> {
+        let size = self.display_size.get();
+        if size.width <= 0.0 || size.height <= 0.0 {
+            return None;
+        }
+        Some(self.viewport.visible_rect(size))
+    }
+
+    /// Mark the graph as changed, so that it gets drawn with the next frame.
+    ///
+    /// Frames are only drawn when something changed, which is tracked automatically for all
+    /// operations on the graph.
+    pub fn request_redraw(&self) {
+        self.dirty.set(true);
+        self.redraws.set(self.redraws.get().wrapping_add(1));
+    }
+
+    /// The number of requested redraws so far, changing whenever the graph needs to be drawn
+    /// again.
+    pub(crate) fn redraws(&self) -> u64 {
+        self.redraws.get()
+    }
+
+    pub fn draw(&self) -> Result<(), Error> {
+        let mut renderer = self.renderer.borrow_mut();
+        self.render(
+            &mut **renderer,
+            self.viewport,
+            self.visible_rect(),
+            true,
+            device_pixel_ratio(),
+        )
+    }
+
+    /// Draw the nodes and edges using another renderer and viewport, e.g. for a
+    /// [`crate::linked::LinkedView`].
+    ///
+    /// The size is the displayed size of the renderer's element, in CSS pixels. Resize handles,
+    /// the selection rectangle, and pending edges only belong to the graph's own element, and
+    /// are not drawn.
+    pub fn draw_to(
+        &self,
+        renderer: &mut dyn Renderer,
+        viewport: Viewport,
+        size: Size,
+    ) -> Result<(), Error> {
+        let visible = (size.width > 0.0 && size.height > 0.0).then(|| viewport.visible_rect(size));
+        self.render(renderer, viewport, visible, false, device_pixel_ratio())
+    }
+
+    /// Draw the nodes and edges into a new canvas, e.g. for exporting them as an image.
+    ///
+    /// Like [`Self::draw_to`], this doesn't draw resize handles, the selection rectangle, or
+    /// pending edges.
+    pub fn render_image(&self, options: &ExportOptions) -> Result<HtmlCanvasElement, Error> {
+        let (viewport, size) = match options.area {
+            ExportArea::Viewport => (self.viewport, self.display_size.get()),
+            ExportArea::Graph { margin } => {
+                let bounds = self.storage.bounds().unwrap_or(Rect {
+                    position: Position { x: 0.0, y: 0.0 },
+                    size: Size {
+                        width: 0.0,
+                        height: 0.0,
+                    },
+                });
+                let viewport = Viewport {
+                    offset: Position {
+                        x: margin - bounds.position.x,
+                        y: margin - bounds.position.y,
+                    },
+                    scale: 1.0,
+                };
+                let size = Size {
+                    width: bounds.size.width + margin * 2.0,
+                    height: bounds.size.height + margin * 2.0,
+                };
+                (viewport, size)
+            }
+        };
+
+        let dpi = options.scale.unwrap_or_else(device_pixel_ratio);
+        let canvas: HtmlCanvasElement = document()?.create_element("canvas")?.unchecked_into();
+        canvas.set_width((size.width * dpi).ceil() as u32);
+        canvas.set_height((size.height * dpi).ceil() as u32);
+
+        let mut renderer = Canvas2dRenderer::new(canvas.clone());
+        self.render(
+            &mut renderer,
+            viewport,
+            Some(viewport.visible_rect(size)),
+            false,
+            dpi,
+        )?;
+
+        if let Some(background) = &options.background {
+            let ctx: CanvasRenderingContext2d = canvas
+                .get_context("2d")?
+                .ok_or(Error::MissingContext)?
+                .unchecked_into();
+            // behind what was already drawn
+            ctx.set_global_composite_operation("destination-over")?;
+            ctx.set_fill_style_str(background);
+            ctx.fill_rect(0.0, 0.0, canvas.width() as _, canvas.height() as _);
+        }
+
+        Ok(canvas)
+    }
+
+    fn render(
+        &self,
+        renderer: &mut dyn Renderer,
+        viewport: Viewport,
+        visible: Option<Rect>,
+        overlays: bool,
+        dpi: f64,
+    ) -> Result<(), Error> {
+        let detail = self
+            .level_of_detail
+            .detail(viewport.scale, self.storage.nodes.len());
+
+        renderer.begin_frame(&Frame {
+            dpi,
+            viewport,
+            detail,
+        })?;
+        // the frame needs to be ended even if drawing failed, e.g. to restore the context
+        let drawn = self.draw_frame(renderer, viewport, visible, overlays, detail);
+        let ended = renderer.end_frame();
+
+        let missing = drawn?;
+        ended?;
+        match missing {
+            Some(node) => Err(Error::MissingNode(node)),
+            None => Ok(()),
+        }
+    }
+
+    /// Draw everything between beginning and ending a frame, returning a node missing from the
+    /// storage, if any.
+    fn draw_frame(
+        &self,
+        renderer: &mut dyn Renderer,
+        viewport: Viewport,
+        visible: Option<Rect>,
+        overlays: bool,
+        detail: Detail,
+    ) -> Result<Option<Node>, Error> {
+        renderer.clear()?;
+
+        // only draw what is visible, if we know what that is
+
+        let visible = visible.map(|rect| rect.grow(CULL_MARGIN / viewport.scale));
+        let is_visible = |rect: Rect| visible.is_none_or(|visible| visible.intersects(&rect));
+
+        // the hovered node and its neighbors stay visible, everything else gets faded
+        let highlighted: Option<(Node, HashSet<Node>)> = self
+            .hovering
+            .filter(|_| self.neighbor_highlight.is_some())
+            .map(|node| {
+                let neighbors = self.storage.neighbors(node).chain([node]).collect();
+                (node, neighbors)
+            });
+        let faded = self.neighbor_highlight.unwrap_or(1.0);
+        // as well as everything outside the focus
+        let focus = self.focused_nodes();
+        let unfocused = self.focus_style.opacity;
+        let focus_opacity = |node: &Node| match &focus {
+            Some(focus) if !focus.contains(node) => unfocused,
+            _ => 1.0,
+        };
+        // and everything not highlighted
+        let highlight_opacity = |node: &Node| match &self.highlighted {
+            Some(highlighted) if !highlighted.contains(node) => HIGHLIGHT_FADE,
+            _ => 1.0,
+        };
+
+        let node_opacity = |node: &Node| {
+            let opacity = match &highlighted {
+                Some((_, neighbors)) if !neighbors.contains(node) => faded,
+                _ => 1.0,
+            };
+            opacity
+                .min(focus_opacity(node))
+                .min(highlight_opacity(node))
+        };
+        let edge_opacity = |a: &Node, b: &Node| {
+            let opacity = match &highlighted {
+                Some((node, _)) if node != a && node != b => faded,
+                _ => 1.0,
+            };
+            opacity
+                .min(focus_opacity(a).min(focus_opacity(b)))
+                .min(highlight_opacity(a).min(highlight_opacity(b)))
+        };
+
+        // draw edges first, with reduced detail only every n-th
+
+        let edge_step = match detail {
+            Detail::Full => 1,
+            Detail::Reduced => {
+                let num_edges: usize = self.storage.edges.values().map(HashMap::len).sum();
+                num_edges
+                    .div_ceil(self.level_of_detail.max_edges.max(1))
+                    .max(1)
+            }
+        };
+
+        // edges of missing nodes are skipped, and reported after the frame got drawn
+        let mut missing = None;
+
+        let mut edge_index = 0;
+        for (from, edges) in &self.storage.edges {
+            let Some(from_state) = self.storage.nodes.get(from) else {
+                missing = Some(*from);
+                continue;
+            };
+            for (to, edge) in edges {
+                edge_index += 1;
+                if edge_index % edge_step != 0 {
+                    continue;
+                }
+
+                let Some(to_state) = self.storage.nodes.get(to) else {
+                    missing = Some(*to);
+                    continue;
+                };
+
+                let from_position = from_state.borrow().center();
+                let to_position = to_state.borrow().center();
+                let waypoints = edge.waypoints.borrow();
+                let bounds = waypoints
+                    .iter()
+                    .fold(Rect::spanning(from_position, to_position), |rect, point| {
+                        rect.extend(*point)
+                    });
+                let category = self.category(&edge.properties.category);
+                let hidden = category.is_some_and(|category| !category.visible)
+                    || self.category_hidden(&from_state.borrow().properties.category)
+                    || self.category_hidden(&to_state.borrow().properties.category);
+                let opacity = match hidden {
+                    true => 0.0,
+                    false => edge_opacity(from, to),
+                };
+                if !is_visible(bounds) || opacity <= 0.0 {
+                    continue;
+                }
+
+                renderer.draw_edge(&EdgeView {
+                    from: *from,
+                    to: *to,
+                    from_position,
+                    to_position,
+                    waypoints: &waypoints,
+                    properties: &edge.properties,
+                    selected: self.selected_edges.contains(&(*from, *to)),
+                    color: category.map(|category| category.color.as_str()),
+                    opacity,
+                })?;
+            }
+        }
+
+        // next draw nodes
+
+        for (id, node) in self.nodes_by_z() {
+            let node = node.borrow();
+            let category = self.category(&node.properties.category);
+            let opacity = match category.is_some_and(|category| !category.visible) {
+                true => 0.0,
+                false => node_opacity(id),
+            };
+            if !is_visible(node.rect()) || opacity <= 0.0 {
+                continue;
+            }
+
+            renderer.draw_node(&NodeView {
+                node: *id,
+                position: node.position,
+                size: node.size,
+                properties: &node.properties,
+                label: &node.label,
+                label_style: &self.label_style,
+                hovered: self.hovering == Some(*id),
+                selected: self.selection.contains(id),
+                color: category.map(|category| category.color.as_str()),
+                opacity,
+            })?;
+        }
+
+        if overlays {
+            for (_, _, rect) in self.handles() {
+                renderer.draw_handle(&rect)?;
+            }
+            for (_, _, _, rect) in self.waypoint_handles() {
+                renderer.draw_handle(&rect)?;
+            }
+
+            if let Some(marquee) = &self.marquee {
+                renderer.draw_selection_rect(&marquee.rect())?;
+            }
+
+            if let Some(connection) = &self.connecting {
+                if let Some(from) = self.storage.center(connection.from) {
+                    renderer.draw_pending_edge(from, connection.to)?;
+                }
+            }
+        }
+
+        Ok(missing)
+    }
+
+    /// Tell the layout about nodes and edges added since the last frame.
+    fn notify_layout(&mut self) {
+        if self.added.is_empty() && self.touched.is_empty() {
+            return;
+        }
+
+        let added = std::mem::take(&mut self.added);
+        let touched: Vec<Node> = std::mem::take(&mut self.touched)
+            .into_iter()
+            .filter(|n| !added.contains(n) && self.storage.contains(*n))
+            .collect();
+        let added: Vec<Node> = added.into_iter().collect();
+        self.layout
+            .nodes_changed(&mut self.storage, &added, &touched);
+    }
+
+    /// Advance the layout to the frame's timestamp, in milliseconds.
+    ///
+    /// The layout is stepped with a fixed time step, independent of the display's refresh rate.
+    fn tick(&mut self, timestamp: f64) {
+        let last_frame = self.last_frame.replace(timestamp);
+        self.notify_layout();
+
+        let elapsed = last_frame
+            .map_or(LAYOUT_STEP, |last| (timestamp - last) / 1000.0)
+            .clamp(0.0, MAX_FRAME_TIME);
+
+        if let Some(animation) = &mut self.animation {
+            if animation.step(&mut self.storage, elapsed) {
+                self.animation = None;
+            }
+            self.confine();
+            self.request_redraw();
+        }
+
+        self.update_tooltip(timestamp);
+        self.report_changes();
+
+        if self.layout_paused {
+            self.pending_time = 0.0;
+            return;
+        }
+
+        self.pending_time += elapsed;
+
+        self.storage.visible = self.visible_rect();
+        let mut moved = false;
+        if self.reduced_motion {
+            // settle in bursts, only showing where the nodes come to rest
+            let mut steps = 0;
+            while !self.layout.is_stable() && steps < REDUCED_MOTION_STEPS {
+                moved |= self.layout.step(&mut self.storage, LAYOUT_STEP);
+                steps += 1;
+            }
+            self.pending_time = 0.0;
+            if moved {
+                self.confine();
+                if self.layout.is_stable() {
+                    self.request_redraw();
+                }
+            }
+        } else {
+            while self.pending_time >= LAYOUT_STEP {
+                moved |= self.layout.step(&mut self.storage, LAYOUT_STEP);
+                self.pending_time -= LAYOUT_STEP;
+            }
+
+            if moved {
+                self.confine();
+                self.request_redraw();
+            }
+        }
+
+        let stable = self.layout.is_stable();
+        if stable && !self.stable {
+            self.emit(|graph| &mut graph.on_stabilized, |f, graph| f(graph));
+        }
+        self.stable = stable;
+    }
+
+    pub fn run(self) -> Handle {
+        let element = self.element.clone();
+        let graph = Rc::new(RefCell::new(self));
+        let graph_ref = Rc::downgrade(&graph);
+
+        fn pointer_event<F>(
+            target: &EventTarget,
+            event_type: &'static str,
+            graph: &Rc<RefCell<Graph>>,
+            f: F,
+        ) -> EventListener
+        where
+            F: Fn(&mut Graph, &PointerEvent) + 'static,
+        {
+            let graph = graph.clone();
+            EventListener::new(target, event_type, move |evt| {
+                if let Ok(mut graph) = graph.try_borrow_mut() {
+                    if let Some(evt) = evt.dyn_ref::<PointerEvent>() {
+                        f(&mut graph, evt);
+                        graph.update_cursor();
+                    }
+                }
+            })
+        }
+
+        // touch gestures are handled by the graph, instead of scrolling or zooming the page
+        set_style(&element, "touch-action", "none");
+
+        // focusable, so that it receives keyboard events
+        if !element.has_attribute("tabindex") {
+            let _ = element.set_attribute("tabindex", "0");
+        }
+
+        let mut listeners = vec![];
+
+        listeners.push(pointer_event(
+            &element,
+            "pointerdown",
+            &graph,
+            |graph, evt| {
+                graph.pointer_down(evt);
+            },
+        ));
+
+        listeners.push(pointer_event(
+            &element,
+            "pointermove",
+            &graph,
+            |graph, evt| {
+                graph.pointer_move(evt);
+            },
+        ));
+
+        listeners.push(pointer_event(
+            &element,
+            "pointerup",
+            &graph,
+            |graph, evt| {
+                graph.pointer_up(evt);
+            },
+        ));
+
+        listeners.push(pointer_event(
+            &element,
+            "pointercancel",
+            &graph,
+            |graph, evt| {
+                graph.pointer_cancel(evt);
+            },
+        ));
+
+        listeners.push(pointer_event(
+            &element,
+            "pointerleave",
+            &graph,
+            |graph, evt| {
+                graph.pointer_cancel(evt);
+            },
+        ));
+
+        {
+            let graph = graph.clone();
+            // not passive, so that the browser doesn't navigate back on Backspace
+            let options = EventListenerOptions::enable_prevent_default();
+            listeners.push(EventListener::new_with_options(
+                &element,
+                "keydown",
+                options,
+                move |evt| {
+                    if let Ok(mut graph) = graph.try_borrow_mut() {
+                        if let Some(evt) = evt.dyn_ref::<KeyboardEvent>() {
+                            graph.key_down(evt);
+                        }
+                    }
+                },
+            ));
+        }
+
+        for (name, handler) in [
+            ("copy", Self::copy as fn(&mut Self, &ClipboardEvent)),
+            ("paste", Self::paste_event),
+        ] {
+            let graph = graph.clone();
+            // not passive, so that the data set on the clipboard is used
+            let options = EventListenerOptions::enable_prevent_default();
+            listeners.push(EventListener::new_with_options(
+                &element,
+                name,
+                options,
+                move |evt| {
+                    if let Ok(mut graph) = graph.try_borrow_mut() {
+                        if let Some(evt) = evt.dyn_ref::<ClipboardEvent>() {
+                            handler(&mut graph, evt);
+                        }
+                    }
+                },
+            ));
+        }
+
+        for (name, handler) in [
+            ("dragover", Self::drag_over as fn(&mut Self, &DragEvent)),
+            ("drop", Self::drop_data),
+        ] {
+            let graph = graph.clone();
+            // not passive, so that drops can be accepted
+            let options = EventListenerOptions::enable_prevent_default();
+            listeners.push(EventListener::new_with_options(
+                &element,
+                name,
+                options,
+                move |evt| {
+                    if let Ok(mut graph) = graph.try_borrow_mut() {
+                        if let Some(evt) = evt.dyn_ref::<DragEvent>() {
+                            handler(&mut graph, evt);
+                        }
+                    }
+                },
+            ));
+        }
+
+        {
+            let graph = graph.clone();
+            // not passive, so that the browser's menu can be prevented
+            let options = EventListenerOptions::enable_prevent_default();
+            listeners.push(EventListener::new_with_options(
+                &element,
+                "contextmenu",
+                options,
+                move |evt| {
+                    if let Ok(mut graph) = graph.try_borrow_mut() {
+                        if let Some(evt) = evt.dyn_ref::<MouseEvent>() {
+                            graph.context_menu(evt);
+                        }
+                    }
+                },
+            ));
+        }
+
+        {
+            let graph = graph.clone();
+            // in case the size didn't change, e.g. when it already was the full window
+            listeners.push(EventListener::new(
+                &element,
+                "fullscreenchange",
+                move |_| {
+                    if let Ok(mut graph) = graph.try_borrow_mut() {
+                        graph.resized();
+                    }
+                },
+            ));
+        }
+
+        {
+            let graph = graph.clone();
+            // not passive, so that scrolling the page can be prevented
+            let options = EventListenerOptions::enable_prevent_default();
+            listeners.push(EventListener::new_with_options(
+                &element,
+                "wheel",
+                options,
+                move |evt| {
+                    if let Ok(mut graph) = graph.try_borrow_mut() {
+                        if let Some(evt) = evt.dyn_ref::<WheelEvent>() {
+                            graph.wheel(evt);
+                        }
+                    }
+                },
+            ));
+        }
+
+        let resize_observer = {
+            let graph = graph.clone();
+            ResizeWatcher::observe(&element, move || {
+                if let Ok(mut graph) = graph.try_borrow_mut() {
+                    graph.resized();
+                }
+            })
+        };
+
+        if let Some(Ok(Some(media))) =
+            web_sys::window().map(|window| window.match_media(REDUCED_MOTION_QUERY))
+        {
+            let graph = graph.clone();
+            listeners.push(EventListener::new(&media, "change", move |_| {
+                if let Ok(mut graph) = graph.try_borrow_mut() {
+                    graph.set_reduced_motion(prefers_reduced_motion());
+                }
+            }));
+        }
+
+        let pixel_ratio_watcher = {
+            let graph = graph.clone();
+            PixelRatioWatcher::watch(move || {
+                if let Ok(graph) = graph.try_borrow() {
+                    graph.adjust_resolution();
+                }
+            })
+        };
+
+        let render_loop = RenderLoop::start(move |timestamp| {
+            if let Ok(mut graph) = graph.try_borrow_mut() {
+                graph.tick(timestamp);
+                if graph.dirty.replace(false) {
+                    if let Err(err) = graph.draw() {
+                        graph.report_error(err);
+                    }
+                }
+            }
+        });
+
+        Handle {
+            graph: graph_ref,
+            render_loop: Some(render_loop),
+            resize_observer,
+            _pixel_ratio_watcher: pixel_ratio_watcher,
+            listeners,
+        }
+    }
+
+    fn pointer_down(&mut self, evt: &PointerEvent) {
+        // other buttons are left to the context menu, or the browser
+        if evt.button() != 0 {
+            return;
+        }
+
+        let position = self.element_position(Position::from(&**evt));
+        self.pointers.insert(evt.pointer_id(), position);
+
+        // keep receiving events when the pointer leaves the element, until it is released
+        let _ = self.element.set_pointer_capture(evt.pointer_id());
+
+        if self.pointers.len() == 1 {
+            let handle = self.handle_at(self.viewport.to_graph(position));
+            if let Some((node, _)) = handle {
+                // only shown while editing
+                self.resizing = handle;
+                self.tap = None;
+                self.storage.held.insert(node);
+                return;
+            }
+
+            let graph_position = self.viewport.to_graph(position);
+            if let Some((a, b, handle)) = self.waypoint_handle_at(graph_position) {
+                self.bending = Some((a, b, handle, position));
+                self.tap = None;
+                self.request_redraw();
+                return;
+            }
+
+            self.tap = Some(position);
+            // touch pointers don't hover before going down
+            self.hovering = self
+                .first_node(self.viewport.to_graph(position))
+                .map(|(id, _)| *id);
+            self.dragging = self.hovering.is_some() && self.can_modify();
+
+            if let Some(from) = self.hovering.filter(|_| evt.shift_key()) {
+                if self.can_edit() && self.on_edge_create.is_some() {
+                    self.dragging = false;
+                    self.connecting = Some(Connection {
+                        from,
+                        to: self.viewport.to_graph(position),
+                    });
+                }
+            }
+
+            if self.dragging {
+                // a selected node takes the rest of the selection with it
+                let dragged: Vec<Node> = match self.hovering {
+                    Some(node) if self.selection.contains(&node) => {
+                        self.selection.iter().copied().collect()
+                    }
+                    node => node.into_iter().collect(),
+                };
+                if let Some(animation) = &mut self.animation {
+                    for node in &dragged {
+                        animation.remove(*node);
+                    }
+                }
+                self.storage.held.extend(dragged);
+            }
+
+            if self.hovering.is_none()
+                && self.background_drag == BackgroundDrag::Select
+                && self.mode.can_select()
+            {
+                let start = self.viewport.to_graph(position);
+                self.marquee = Some(Marquee {
+                    start,
+                    end: start,
+                    additive: evt.shift_key() || evt.ctrl_key() || evt.meta_key(),
+                });
+            }
+        } else {
+            // a second pointer turns any drag into a gesture of the viewport
+            self.tap = None;
+            self.dragging = false;
+            self.marquee = None;
+            self.connecting = None;
+            self.resizing = None;
+            self.bending = None;
+            self.storage.held.clear();
+        }
+        self.request_redraw();
+    }
+
+    fn pointer_move(&mut self, evt: &PointerEvent) {
+        let id = evt.pointer_id();
+        let current = self.element_position(Position::from(&**evt));
+
+        let Some(last) = self.pointers.insert(id, current) else {
+            // not pressed
+            self.pointers.remove(&id);
+            self.hover(self.viewport.to_graph(current));
+            return;
+        };
+
+        let other = self
+            .pointers
+            .iter()
+            .find(|(other, _)| **other != id)
+            .map(|(_, position)| *position);
+
+        match other {
+            None => {
+                if self
+                    .tap
+                    .is_some_and(|start| start.delta(current) > TAP_DISTANCE)
+                {
+                    self.tap = None;
+                }
+                if let Some((node, handle)) = self.resizing {
+                    self.resize_to(node, handle, self.viewport.to_graph(current));
+                } else if let Some((a, b, handle, start)) = self.bending {
+                    self.bend_to(a, b, handle, start, current);
+                } else if self.dragging {
+                    // if we are dragging, we don't lose the selection
+                    self.drag_to(self.viewport.to_graph(current));
+                } else if let Some(connection) = &mut self.connecting {
+                    connection.to = self.viewport.to_graph(current);
+                    self.request_redraw();
+                } else if let Some(marquee) = &mut self.marquee {
+                    marquee.end = self.viewport.to_graph(current);
+                    self.request_redraw();
+                } else if self.mode.can_pan() {
+                    self.pan_by(current.x - last.x, current.y - last.y);
+                }
+            }
+            Some(_) if !self.mode.can_pan() => {}
+            Some(other) => {
+                // pan by the movement of the center, zoom by the change of the distance
+                let center = |a: Position| Position {
+                    x: (a.x + other.x) / 2.0,
+                    y: (a.y + other.y) / 2.0,
+                };
+                let (from, to) = (center(last), center(current));
+                self.pan_by(to.x - from.x, to.y - from.y);
+
+                let distance = last.delta(other);
+                if distance > 0.0 {
+                    self.zoom_at(to, current.delta(other) / distance);
+                }
+            }
+        }
+    }
+
+    fn pointer_up(&mut self, evt: &PointerEvent) {
+        if self.pointers.remove(&evt.pointer_id()).is_none() || !self.pointers.is_empty() {
+            return;
+        }
+
+        let marquee = self.marquee.take();
+        let connection = self.connecting.take();
+        let resizing = self.resizing.take();
+        self.bending = None;
+        if let Some(start) = self.tap.take() {
+            if self.mode.can_select() {
+                let target = self.target_at(self.viewport.to_graph(start));
+                self.select(target, evt.ctrl_key() || evt.meta_key());
+            }
+            self.click(start, evt.time_stamp());
+        } else if let Some(marquee) = marquee {
+            self.select_rect(marquee.rect(), marquee.additive);
+        } else if let Some(connection) = connection {
+            self.connect(connection);
+        } else if let Some((node, _)) = resizing {
+            if let Some(size) = self.storage.size(node) {
+                self.emit(
+                    |graph| &mut graph.on_node_resize,
+                    |f, graph| f(graph, node, size),
+                );
+            }
+        }
+
+        self.dragging = false;
+        self.storage.held.clear();
+        if evt.pointer_type() == "touch" {
+            self.hovering = None;
+        }
+        self.request_redraw();
+    }
+
+    /// The pointer left the element, or the browser took over.
+    ///
+    /// Pressed pointers are captured, so they only leave once released.
+    fn pointer_cancel(&mut self, evt: &PointerEvent) {
+        self.pointers.remove(&evt.pointer_id());
+        if self.pointers.is_empty() {
+            self.tap = None;
+            self.marquee = None;
+            self.connecting = None;
+            self.resizing = None;
+            self.bending = None;
+            self.dragging = false;
+            self.storage.held.clear();
+        }
+        self.hovering = None;
+        self.request_redraw();
+    }
+
+    /// Update the selection for a click on a node, an edge, or the background.
+    ///
+    /// Clicking a node or edge selects only that one, clicking the background clears the
+    /// selection. With `toggle`, the node or edge is added or removed instead, and the background
+    /// is ignored.
+    fn select(&mut self, target: Target, toggle: bool) {
+        if !toggle {
+            self.selection.clear();
+            self.selected_edges.clear();
+        }
+        match target {
+            Target::Node(node) => {
+                if !self.selection.remove(&node) {
+                    self.selection.insert(node);
+                }
+            }
+            Target::Edge(a, b) => {
+                if !self.selected_edges.remove(&ordered(a, b)) {
+                    self.selected_edges.insert(ordered(a, b));
+                }
+            }
+            Target::Background(_) => {}
+        }
+        self.request_redraw();
+    }
+
+    /// The resize handles of all selected nodes, with their rectangles in graph coordinates.
+    fn handles(&self) -> Vec<(Node, ResizeHandle, Rect)> {
+        if !self.can_edit() {
+            return vec![];
+        }
+
+        let size = HANDLE_SIZE / self.viewport.scale;
+        self.selection
+            .iter()
+            .filter_map(|node| Some((*node, self.storage.rect(*node)?)))
+            .flat_map(|(node, rect)| {
+                ResizeHandle::ALL.map(|handle| (node, handle, handle.rect(rect, size)))
+            })
+            .collect()
+    }
+
+    /// The resize handle at a position, in graph coordinates.
+    fn handle_at(&self, position: Position) -> Option<(Node, ResizeHandle)> {
+        self.handles()
+            .into_iter()
+            .rev()
+            .find(|(_, _, rect)| rect.contains(position))
+            .map(|(node, handle, _)| (node, handle))
+    }
+
+    /// The handles of the edges connected to selected nodes, with their rectangles in graph
+    /// coordinates.
+    fn waypoint_handles(&self) -> Vec<(Node, Node, WaypointHandle, Rect)> {
+        if !self.can_edit() {
+            return vec![];
+        }
+
+        let size = HANDLE_SIZE / self.viewport.scale;
+        let handle = |center: Position| Rect {
+            position: Position {
+                x: center.x - size / 2.0,
+                y: center.y - size / 2.0,
+            },
+            size: Size {
+                width: size,
+                height: size,
+            },
+        };
+
+        let mut result = vec![];
+        for (a, b, _) in self.storage.edges() {
+            if !self.selection.contains(&a) && !self.selection.contains(&b) {
+                continue;
+            }
+            let Some(points) = self.storage.edge_points(a, b) else {
+                continue;
+            };
+
+            for (index, pair) in points.windows(2).enumerate() {
+                let middle = Position {
+                    x: (pair[0].x + pair[1].x) / 2.0,
+                    y: (pair[0].y + pair[1].y) / 2.0,
+                };
+                result.push((a, b, WaypointHandle::Insert(index), handle(middle)));
+            }
+            for (index, point) in points[1..points.len() - 1].iter().enumerate() {
+                result.push((a, b, WaypointHandle::Waypoint(index), handle(*point)));
+            }
+        }
+        result
+    }
+
+    /// The waypoint handle at a position, in graph coordinates.
+    fn waypoint_handle_at(&self, position: Position) -> Option<(Node, Node, WaypointHandle)> {
+        self.waypoint_handles()
+            .into_iter()
+            .rev()
+            .find(|(_, _, _, rect)| rect.contains(position))
+            .map(|(a, b, handle, _)| (a, b, handle))
+    }
+
+    /// Move a waypoint of an edge, stored in the order of the edge, to a position in graph
+    /// coordinates.
+    /// Move the waypoint of a handle to a position of the pointer, in element pixels.
+    ///
+    /// The waypoint of an insert handle is only added once the pointer moved away from where it
+    /// was pressed, so that clicking the handle doesn't change the edge.
+    fn bend_to(
+        &mut self,
+        a: Node,
+        b: Node,
+        handle: WaypointHandle,
+        start: Position,
+        current: Position,
+    ) {
+        let position = self.viewport.to_graph(current);
+        match handle {
+            WaypointHandle::Waypoint(index) => self.move_waypoint(a, b, index, position),
+            WaypointHandle::Insert(index) if start.delta(current) > TAP_DISTANCE => {
+                if let Some((state, _)) = self.storage.edge(a, b) {
+                    state.waypoints.borrow_mut().insert(index, position);
+                }
+                self.bending = Some((a, b, WaypointHandle::Waypoint(index), start));
+                self.request_redraw();
+            }
+            WaypointHandle::Insert(_) => {}
+        }
+    }
+
+    fn move_waypoint(&mut self, a: Node, b: Node, index: usize, position: Position) {
+        let Some((state, _)) = self.storage.edge(a, b) else {
+            return;
+        };
+        if let Some(point) = state.waypoints.borrow_mut().get_mut(index) {
+            *point = position;
+        }
+        self.request_redraw();
+    }
+
+    /// Move the border of a node to a position, in graph coordinates.
+    fn resize_to(&mut self, node: Node, handle: ResizeHandle, position: Position) {
+        let Some(state) = self.storage.nodes.get(&node).cloned() else {
+            return;
+        };
+
+        {
+            let mut state = state.borrow_mut();
+            let rect = handle.resize(state.rect(), position, &self.resize_limits);
+            state.position = rect.position;
+            state.size = rect.size;
+            state.fit_label = false;
+            if let Ok(ctx) = self.context() {
+                self.layout_label(&ctx, &mut state);
+            }
+        }
+
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    /// Create the edge the user has drawn, if it ends on another node and the callback accepts it.
+    fn connect(&mut self, connection: Connection) {
+        let Some(to) = self.first_node(connection.to).map(|(id, _)| *id) else {
+            return;
+        };
+        if to == connection.from {
+            return;
+        }
+
+        let from = connection.from;
+        if let Some(Some(edge)) = self.emit(
+            |graph| &mut graph.on_edge_create,
+            |f, graph| f(graph, from, to),
+        ) {
+            // the callback may have removed one of the nodes
+            let _ = self.add_edge(from, to, edge);
+        }
+    }
+
+    /// Select all nodes fully inside a rectangle, in graph coordinates.
+    ///
+    /// Unless `additive`, this replaces the current selection.
+    fn select_rect(&mut self, rect: Rect, additive: bool) {
+        if !additive {
+            self.selection.clear();
+            self.selected_edges.clear();
+        }
+        self.selection
+            .extend(self.storage.nodes.iter().filter_map(|(id, node)| {
+                let node = node.borrow().rect();
+                (rect.contains(node.position)
+                    && rect.contains(Position {
+                        x: node.right(),
+                        y: node.bottom(),
+                    }))
+                .then_some(*id)
+            }));
+        self.request_redraw();
+    }
+
+    /// Report a click at a position, in element coordinates.
+    fn click(&mut self, position: Position, timestamp: f64) {
+        let double = self.last_click.is_some_and(|(time, last)| {
+            timestamp - time < DOUBLE_CLICK_TIME && last.delta(position) < TAP_DISTANCE
+        });
+        // a third click starts over
+        self.last_click = (!double).then_some((timestamp, position));
+
+        let position = self.viewport.to_graph(position);
+        if let Some(node) = self.hovering {
+            self.emit(|graph| &mut graph.on_node_click, |f, graph| f(graph, node));
+            if double {
+                self.emit(
+                    |graph| &mut graph.on_node_dblclick,
+                    |f, graph| f(graph, node),
+                );
+            }
+        } else if let Some((a, b)) = self.first_edge(position) {
+            self.emit(|graph| &mut graph.on_edge_click, |f, graph| f(graph, a, b));
+        } else {
+            self.emit(
+                |graph| &mut graph.on_background_click,
+                |f, graph| f(graph, position),
+            );
+            if double && self.can_edit() {
+                if let Some(Some((node, size))) = self.emit(
+                    |graph| &mut graph.on_node_create,
+                    |f, graph| f(graph, position),
+                ) {
+                    let node = self.add_node(position, size, node);
+                    self.storage.set_center(node, position);
+                }
+            }
+        }
+    }
+
+    /// The edge closest to a position in graph coordinates, if it is close enough to hit it.
+    fn first_edge(&self, position: Position) -> Option<(Node, Node)> {
+        let max = EDGE_HIT_DISTANCE / self.viewport.scale;
+        let focus = self.hidden_by_focus();
+        self.storage
+            .edges()
+            .filter(|(a, b, properties)| {
+                focus
+                    .as_ref()
+                    .is_none_or(|focus| focus.contains(a) && focus.contains(b))
+                    && !self.category_hidden(&properties.category)
+                    && !self.node_hidden(*a)
+                    && !self.node_hidden(*b)
+            })
+            .filter_map(|(a, b, _)| {
+                let distance = self
+                    .storage
+                    .edge_points(a, b)?
+                    .windows(2)
+                    .map(|pair| position.segment_distance(pair[0], pair[1]))
+                    .fold(f64::INFINITY, f64::min);
+                (distance <= max).then_some((distance, a, b))
+            })
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .map(|(_, a, b)| (a, b))
+    }
+
+    /// Track the node below a pointer which isn't pressed, in graph coordinates.
+    fn hover(&mut self, position: Position) {
+        if let Some((node, hovered)) = self
+            .hovering
+            .and_then(|n| Some((n, self.storage.nodes.get(&n)?)))
+        {
+            if !self.hits(node, &hovered.borrow(), position) {
+                self.hovering = None;
+                self.request_redraw();
+            }
+        }
+
+        if self.hovering.is_none() {
+            self.hovering = self.first_node(position).map(|(id, _)| *id);
+            if self.hovering.is_some() {
+                self.request_redraw();
+            }
+        }
+
+        self.hover_handle = self.handle_at(position).map(|(_, handle)| handle);
+    }
+
+    fn first_node(
+        &self,
+        position: impl Into<Position>,
+    ) -> Option<(&Node, &Rc<RefCell<NodeState>>)> {
+        let position = position.into();
+        let focus = self.hidden_by_focus();
+        self.nodes_by_z()
+            .into_iter()
+            .rev()
+            .filter(|(id, _)| focus.as_ref().is_none_or(|focus| focus.contains(id)))
+            .filter(|(_, n)| !self.category_hidden(&n.borrow().properties.category))
+            .find(|(id, n)| self.hits(**id, &n.borrow(), position))
+    }
+
+    /// The nodes in focus, if all other nodes are hidden.
+    fn hidden_by_focus(&self) -> Option<HashSet<Node>> {
+        match self.focus_style.opacity > 0.0 {
+            true => None,
+            false => self.focused_nodes(),
+        }
+    }
+
+    /// The nodes within the depth of the focused node, if there is one.
+    fn focused_nodes(&self) -> Option<HashSet<Node>> {
+        let (node, depth) = self.focus?;
+
+        let mut nodes = HashSet::from([node]);
+        let mut frontier = vec![node];
+        for _ in 0..depth {
+            frontier = frontier
+                .iter()
+                .flat_map(|node| self.storage.neighbors(*node))
+                .filter(|neighbor| nodes.insert(*neighbor))
+                .collect();
+        }
+        Some(nodes)
+    }
+
+    /// Move the dragged nodes, so that the node below the pointer is centered at a position in
+    /// graph coordinates.
+    ///
+    /// All dragged nodes keep their offsets to each other, also when hitting the bounds.
+    fn drag_to(&mut self, position: Position) {
+        let Some(anchor) = self.hovering.and_then(|n| self.storage.center(n)) else {
+            return;
+        };
+        let nodes: Vec<_> = self
+            .storage
+            .held
+            .iter()
+            .filter_map(|n| self.storage.nodes.get(n))
+            .collect();
+
+        let (mut dx, mut dy) = (position.x - anchor.x, position.y - anchor.y);
+        let group = nodes
+            .iter()
+            .map(|node| node.borrow().rect())
+            .reduce(|a, b| a.union(&b));
+        if let (Some(bounds), Some(group)) = (self.bounds.rect(self.visible_rect()), group) {
+            let confined = bounds.confine(Rect {
+                position: Position {
+                    x: group.position.x + dx,
+                    y: group.position.y + dy,
+                },
+                size: group.size,
+            });
+            dx = confined.x - group.position.x;
+            dy = confined.y - group.position.y;
+        }
+
+        for node in nodes {
+            let mut node = node.borrow_mut();
+            node.position.x += dx;
+            node.position.y += dy;
+        }
+        self.layout.reheat();
+        self.request_redraw();
+    }
+
+    fn key_down(&mut self, evt: &KeyboardEvent) {
+        if !matches!(evt.key().as_str(), "Delete" | "Backspace") || !self.can_edit() {
+            return;
+        }
+        if self.selection.is_empty() && self.selected_edges.is_empty() {
+            return;
+        }
+        evt.prevent_default();
+
+        let nodes: Vec<Node> = self.selection.iter().copied().collect();
+        let edges: Vec<(Node, Node)> = self.selected_edges.iter().copied().collect();
+        let allowed = self
+            .emit(
+                |graph| &mut graph.on_delete,
+                |f, graph| f(graph, &nodes, &edges),
+            )
+            .unwrap_or(true);
+        if allowed {
+            self.delete_selection();
+        }
+    }
+
+    fn copy(&mut self, evt: &ClipboardEvent) {
+        if self.selection.is_empty() {
+            return;
+        }
+        if let Some(data) = evt.clipboard_data() {
+            if data
+                .set_data("text/plain", &self.copy_selection().to_json())
+                .is_ok()
+            {
+                evt.prevent_default();
+            }
+        }
+    }
+
+    fn paste_event(&mut self, evt: &ClipboardEvent) {
+        if !self.can_edit() {
+            return;
+        }
+        let Some(json) = evt
+            .clipboard_data()
+            .and_then(|data| data.get_data("text/plain").ok())
+        else {
+            return;
+        };
+
+        // other content is left to the page
+        if let Ok(subgraph) = Subgraph::from_json(&json) {
+            evt.prevent_default();
+            let offset = PASTE_OFFSET / self.viewport.scale;
+            self.paste(
+                &subgraph,
+                Position {
+                    x: offset,
+                    y: offset,
+                },
+            );
+        }
+    }
+
+    fn drag_over(&mut self, evt: &DragEvent) {
+        if self.on_drop.is_some() && self.can_edit() {
+            // accept the drop
+            evt.prevent_default();
+        }
+    }
+
+    fn drop_data(&mut self, evt: &DragEvent) {
+        if self.on_drop.is_none() || !self.can_edit() {
+            return;
+        }
+        let Some(data) = evt.data_transfer() else {
+            return;
+        };
+        evt.prevent_default();
+
+        let position = self
+            .viewport
+            .to_graph(self.element_position(Position::from(&**evt)));
+        self.emit(
+            |graph| &mut graph.on_drop,
+            |f, graph| f(graph, position, data),
+        );
+    }
+
+    fn context_menu(&mut self, evt: &MouseEvent) {
+        if self.on_context_menu.is_none() {
+            return;
+        }
+        evt.prevent_default();
+
+        let client = Position::from(evt);
+        let target = self.target_at(self.viewport.to_graph(self.element_position(client)));
+        self.emit(
+            |graph| &mut graph.on_context_menu,
+            |f, graph| f(graph, target, client),
+        );
+    }
+
+    /// The topmost element at a position, in graph coordinates.
+    pub fn target_at(&self, position: Position) -> Target {
+        if let Some((node, _)) = self.first_node(position) {
+            Target::Node(*node)
+        } else if let Some((a, b)) = self.first_edge(position) {
+            Target::Edge(a, b)
+        } else {
+            Target::Background(position)
+        }
+    }
+
+    fn wheel(&mut self, evt: &WheelEvent) {
+        if !self.mode.can_pan() {
+            return;
+        }
+        evt.prevent_default();
+
+        let delta = match evt.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => evt.delta_y() * WHEEL_LINE_HEIGHT,
+            _ => evt.delta_y(),
+        };
+        let position = self.element_position(Position::from(&**evt));
+        self.zoom_at(position, (-delta * WHEEL_ZOOM_SPEED).exp());
+    }
+
+    /// Convert a pointer position from the client area to the element.
+    fn element_position(&self, position: Position) -> Position {
+        let rect = self.element.get_bounding_client_rect();
+        Position {
+            x: position.x - rect.left(),
+            y: position.y - rect.top(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Position {
+    pub fn delta(&self, other: Position) -> f64 {
+        sqrt(pow(other.x - self.x, 2.0) + pow(other.y - self.y, 2.0))
+    }
+
+    /// The distance to the closest point of the line segment from `a` to `b`.
+    pub fn segment_distance(&self, a: Position, b: Position) -> f64 {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let length = dx * dx + dy * dy;
+        if length <= 0.0 {
+            return self.delta(a);
+        }
+
+        let t = (((self.x - a.x) * dx + (self.y - a.y) * dy) / length).clamp(0.0, 1.0);
+        self.delta(Position {
+            x: a.x + t * dx,
+            y: a.y + t * dy,
+        })
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.x, self.y)
+    }
+}
+
+impl From<(f64, f64)> for Position {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<&MouseEvent> for Position {
+    fn from(value: &MouseEvent) -> Self {
+        Self {
+            x: value.client_x() as _,
+            y: value.client_y() as _,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl From<(f64, f64)> for Size {
+    fn from((width, height): (f64, f64)) -> Self {
+        Self { width, height }
+    }
+}
+
+/// An axis aligned rectangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    /// The top left corner.
+    pub position: Position,
+    pub size: Size,
+}
+
+impl Rect {
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rect) -> Self {
+        self.extend(other.position).extend(Position {
+            x: other.right(),
+            y: other.bottom(),
+        })
+    }
+
+    /// Grow the rectangle, so that it contains a position.
+    pub fn extend(&self, position: Position) -> Self {
+        let top_left = Position {
+            x: self.position.x.min(position.x),
+            y: self.position.y.min(position.y),
+        };
+        let bottom_right = Position {
+            x: self.right().max(position.x),
+            y: self.bottom().max(position.y),
+        };
+        Self::spanning(top_left, bottom_right)
+    }
+
+    /// The smallest rectangle containing both points.
+    pub fn spanning(a: Position, b: Position) -> Self {
+        Self {
+            position: Position {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+            },
+            size: Size {
+                width: (a.x - b.x).abs(),
+                height: (a.y - b.y).abs(),
+            },
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.position.x + self.size.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.position.y + self.size.height
+    }
+
+    pub fn center(&self) -> Position {
+        Position {
+            x: self.position.x + self.size.width / 2.0,
+            y: self.position.y + self.size.height / 2.0,
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.x >= self.position.x
+            && position.y >= self.position.y
+            && position.x <= self.right()
+            && position.y <= self.bottom()
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.position.x <= other.right()
+            && other.position.x <= self.right()
+            && self.position.y <= other.bottom()
+            && other.position.y <= self.bottom()
+    }
+
+    /// If a position is within the ellipse filling the rectangle.
+    pub fn ellipse_contains(&self, position: Position) -> bool {
+        let center = self.center();
+        let (rx, ry) = (self.size.width / 2.0, self.size.height / 2.0);
+        if rx <= 0.0 || ry <= 0.0 {
+            return false;
+        }
+        let (dx, dy) = ((position.x - center.x) / rx, (position.y - center.y) / ry);
+        dx * dx + dy * dy <= 1.0
+    }
+
+    /// Grow the rectangle by the amount, in all directions.
+    pub fn grow(&self, amount: f64) -> Self {
+        Self {
+            position: Position {
+                x: self.position.x - amount,
+                y: self.position.y - amount,
+            },
+            size: Size {
+                width: self.size.width + amount * 2.0,
+                height: self.size.height + amount * 2.0,
+            },
+        }
+    }
+
+    /// The position of the other rectangle, moved inside this one.
+    ///
+    /// On an axis where it doesn't fit, it gets centered.
+    pub fn confine(&self, other: Rect) -> Position {
+        fn axis(start: f64, length: f64, other_start: f64, other_length: f64) -> f64 {
+            if other_length > length {
+                start + (length - other_length) / 2.0
+            } else {
+                other_start.clamp(start, start + length - other_length)
+            }
+        }
+
+        Position {
+            x: axis(
+                self.position.x,
+                self.size.width,
+                other.position.x,
+                other.size.width,
+            ),
+            y: axis(
+                self.position.y,
+                self.size.height,
+                other.position.y,
+                other.size.height,
+            ),
+        }
+    }
+}
+
+/// An element of the graph the user interacted with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Node(Node),
+    /// An edge, by the nodes it connects.
+    Edge(Node, Node),
+    /// The background, at a position in graph coordinates.
+    Background(Position),
+}
+
+/// Which gestures are available to the user.
+///
+/// Each mode includes the gestures of the previous ones. Hovering and the click callbacks, like
+/// [`Graph::on_node_click`], are available in all modes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InteractionMode {
+    /// Only looking at the graph, the viewport can't be changed.
+    View,
+    /// Panning and zooming.
+    Pan,
+    /// Selecting and dragging nodes.
+    #[default]
+    Select,
+    /// Creating edges and nodes, and resizing nodes, see [`Graph::on_edge_create`],
+    /// [`Graph::on_node_create`], and [`Graph::on_node_resize`].
+    Edit,
+}
+
+impl InteractionMode {
+    pub fn can_pan(&self) -> bool {
+        !matches!(self, Self::View)
+    }
+
+    pub fn can_select(&self) -> bool {
+        matches!(self, Self::Select | Self::Edit)
+    }
+
+    pub fn can_edit(&self) -> bool {
+        matches!(self, Self::Edit)
+    }
+}
+
+/// The CSS cursors shown over the graph, by what the pointer does.
+///
+/// Resize handles always show a matching resize cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursors {
+    /// Over the background, or nodes which can't be dragged.
+    pub default: String,
+    /// Over a node which can be dragged.
+    pub node: String,
+    /// While dragging nodes.
+    pub dragging: String,
+    /// While drawing an edge.
+    pub connecting: String,
+}
+
+impl Default for Cursors {
+    fn default() -> Self {
+        Self {
+            default: "default".into(),
+            node: "grab".into(),
+            dragging: "grabbing".into(),
+            connecting: "crosshair".into(),
+        }
+    }
+}
+
+/// How [`Graph::focus`] shows the neighborhood of a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusStyle {
+    /// The opacity of nodes and edges outside the focus, `0` hides them.
+    pub opacity: f64,
+    /// Center the view on the focused node.
+    pub recenter: bool,
+}
+
+impl Default for FocusStyle {
+    fn default() -> Self {
+        Self {
+            opacity: 0.1,
+            recenter: true,
+        }
+    }
+}
+
+/// What dragging the background of the graph does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackgroundDrag {
+    /// Move the viewport.
+    #[default]
+    Pan,
+    /// Draw a rectangle, selecting all nodes inside once released.
+    ///
+    /// With Shift, Ctrl, or Cmd pressed when starting, the nodes are added to the selection.
+    Select,
+}
+
+/// A selection rectangle being drawn, in graph coordinates.
+struct Marquee {
+    start: Position,
+    end: Position,
+    /// If nodes get added to the selection, instead of replacing it
+    additive: bool,
+}
+
+impl Marquee {
+    fn rect(&self) -> Rect {
+        Rect::spanning(self.start, self.end)
+    }
+}
+
+/// The limits of the size of nodes, when resized by the user.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResizeLimits {
+    pub min: Size,
+    pub max: Size,
+}
+
+impl Default for ResizeLimits {
+    fn default() -> Self {
+        Self {
+            min: Size {
+                width: 20.0,
+                height: 20.0,
+            },
+            max: Size {
+                width: f64::INFINITY,
+                height: f64::INFINITY,
+            },
+        }
+    }
+}
+
+/// A handle on an edge, in the order of the edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WaypointHandle {
+    /// Moves an existing waypoint.
+    Waypoint(usize),
+    /// Adds a waypoint in the middle of a segment, before the waypoint with that index.
+    Insert(usize),
+}
+
+/// A handle on the border of a node, by its place on each axis: start, center, or end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ResizeHandle {
+    x: Side,
+    y: Side,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Start,
+    Center,
+    End,
+}
+
+impl Side {
+    fn factor(&self) -> f64 {
+        match self {
+            Self::Start => 0.0,
+            Self::Center => 0.5,
+            Self::End => 1.0,
+        }
+    }
+
+    /// Move this side of an axis to `to`, returning the new start and length.
+    fn resize(&self, start: f64, length: f64, to: f64, min: f64, max: f64) -> (f64, f64) {
+        let limit = |length: f64| length.max(min).min(max);
+        match self {
+            Self::Start => {
+                let end = start + length;
+                let length = limit(end - to);
+                (end - length, length)
+            }
+            Self::Center => (start, length),
+            Self::End => (start, limit(to - start)),
+        }
+    }
+}
+
+impl ResizeHandle {
+    const ALL: [Self; 8] = [
+        Self::new(Side::Start, Side::Start),
+        Self::new(Side::Center, Side::Start),
+        Self::new(Side::End, Side::Start),
+        Self::new(Side::End, Side::Center),
+        Self::new(Side::End, Side::End),
+        Self::new(Side::Center, Side::End),
+        Self::new(Side::Start, Side::End),
+        Self::new(Side::Start, Side::Center),
+    ];
+
+    const fn new(x: Side, y: Side) -> Self {
+        Self { x, y }
+    }
+
+    /// The CSS cursor for moving the handle.
+    fn cursor(&self) -> &'static str {
+        match (self.x, self.y) {
+            (Side::Center, _) => "ns-resize",
+            (_, Side::Center) => "ew-resize",
+            (x, y) if x == y => "nwse-resize",
+            _ => "nesw-resize",
+        }
+    }
+
+    /// The rectangle of the handle, on the border of the node.
+    fn rect(&self, node: Rect, size: f64) -> Rect {
+        Rect {
+            position: Position {
+                x: node.position.x + node.size.width * self.x.factor() - size / 2.0,
+                y: node.position.y + node.size.height * self.y.factor() - size / 2.0,
+            },
+            size: Size {
+                width: size,
+                height: size,
+            },
+        }
+    }
+
+    /// The node, with the handle moved to a position.
+    fn resize(&self, node: Rect, to: Position, limits: &ResizeLimits) -> Rect {
+        let (x, width) = self.x.resize(
+            node.position.x,
+            node.size.width,
+            to.x,
+            limits.min.width,
+            limits.max.width,
+        );
+        let (y, height) = self.y.resize(
+            node.position.y,
+            node.size.height,
+            to.y,
+            limits.min.height,
+            limits.max.height,
+        );
+        Rect {
+            position: Position { x, y },
+            size: Size { width, height },
+        }
+    }
+}
+
+/// An edge being drawn by the user.
+struct Connection {
+    from: Node,
+    /// The end of the edge, in graph coordinates
+    to: Position,
+}
+
+/// How the size of a node is determined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeSize {
+    /// A fixed size, the label gets wrapped to fit into it.
+    Fixed(Size),
+    /// Size the node to fit its wrapped label.
+    FitLabel,
+}
+
+impl From<Size> for NodeSize {
+    fn from(value: Size) -> Self {
+        Self::Fixed(value)
+    }
+}
+
+impl From<(f64, f64)> for NodeSize {
+    fn from(value: (f64, f64)) -> Self {
+        Self::Fixed(value.into())
+    }
+}
+
+/// A `requestAnimationFrame` callback, receiving the frame's timestamp.
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+/// A self re-scheduling `requestAnimationFrame` loop, which can be cancelled.
+///
+/// The scheduled closure only holds a weak reference to the loop, so that dropping the loop
+/// breaks the cycle and releases everything the closure captured.
+pub(crate) struct RenderLoop {
+    closure: RefCell<Option<FrameCallback>>,
+    frame: Cell<Option<i32>>,
+}
+
+impl RenderLoop {
+    pub(crate) fn start<F>(mut f: F) -> Rc<Self>
+    where
+        F: FnMut(f64) + 'static,
+    {
+        let render_loop = Rc::new(Self {
+            closure: RefCell::new(None),
+            frame: Cell::new(None),
+        });
+
+        let weak = Rc::downgrade(&render_loop);
+        *render_loop.closure.borrow_mut() = Some(Closure::new(move |timestamp| {
+            let Some(render_loop) = weak.upgrade() else {
+                return;
+            };
+            render_loop.frame.set(None);
+
+            f(timestamp);
+
+            render_loop.schedule();
+        }));
+
+        render_loop.schedule();
+
+        render_loop
+    }
+
+    fn schedule(&self) {
+        let Some(window) = web_sys::window() else {
+            log::warn!("Failed to request animation frame: not running in a browser");
+            return;
+        };
+        if let Some(closure) = self.closure.borrow().as_ref() {
+            match window.request_animation_frame(closure.as_ref().unchecked_ref()) {
+                Ok(frame) => self.frame.set(Some(frame)),
+                Err(err) => log::warn!("Failed to request animation frame: {err:?}"),
+            }
+        }
+    }
+
+    pub(crate) fn cancel(&self) {
+        if let (Some(frame), Some(window)) = (self.frame.take(), web_sys::window()) {
+            let _ = window.cancel_animation_frame(frame);
+        }
+        // dropping the closure releases the captured graph
+        drop(self.closure.borrow_mut().take());
+    }
+}
+
+/// Calls a function whenever the size of an element changes, until dropped.
+struct ResizeWatcher {
+    observer: ResizeObserver,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl ResizeWatcher {
+    /// Start observing, or `None` if the browser doesn't support it.
+    fn observe<F>(element: &Element, f: F) -> Option<Self>
+    where
+        F: FnMut() + 'static,
+    {
+        let closure = Closure::<dyn FnMut()>::new(f);
+        match ResizeObserver::new(closure.as_ref().unchecked_ref()) {
+            Ok(observer) => {
+                observer.observe(element);
+                Some(Self {
+                    observer,
+                    _closure: closure,
+                })
+            }
+            Err(err) => {
+                log::warn!("Failed to observe the size of the element: {err:?}");
+                None
+            }
+        }
+    }
+}
+
+impl Drop for ResizeWatcher {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+/// Calls a function whenever the device pixel ratio changes, e.g. when moving the window to
+/// another screen or zooming the page, until dropped.
+struct PixelRatioWatcher {
+    /// The listener of the current ratio, which is replaced when the ratio changes
+    _listener: Rc<RefCell<Option<EventListener>>>,
+}
+
+impl PixelRatioWatcher {
+    fn watch<F>(f: F) -> Self
+    where
+        F: Fn() + 'static,
+    {
+        let listener = Rc::new(RefCell::new(None));
+        Self::listen(Rc::new(f), Rc::downgrade(&listener));
+        Self {
+            _listener: listener,
+        }
+    }
+
+    /// Listen for the current ratio to no longer match, then listen again for the new one.
+    fn listen(f: Rc<dyn Fn()>, slot: Weak<RefCell<Option<EventListener>>>) {
+        let Some(listener) = slot.upgrade() else {
+            return;
+        };
+
+        let query = format!("(resolution: {}dppx)", device_pixel_ratio());
+        let Some(Ok(Some(media))) = web_sys::window().map(|window| window.match_media(&query))
+        else {
+            log::warn!("Failed to watch the device pixel ratio");
+            return;
+        };
+
+        *listener.borrow_mut() = Some(EventListener::once(&media, "change", move |_| {
+            f();
+            Self::listen(f, slot);
+        }));
+    }
+}
+
+/// The ratio of device pixels to CSS pixels, or `1.0` when not running in a browser.
+fn device_pixel_ratio() -> f64 {
+    web_sys::window().map_or(1.0, |window| window.device_pixel_ratio())
+}
+
+/// If the user prefers reduced motion, which is `false` when not running in a browser.
+fn prefers_reduced_motion() -> bool {
+    match web_sys::window().map(|window| window.match_media(REDUCED_MOTION_QUERY)) {
+        Some(Ok(Some(media))) => media.matches(),
+        _ => false,
+    }
+}
+
+/// The document, failing when not running in a browser, e.g. when rendering server side.
+pub(crate) fn document() -> Result<Document, Error> {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or(Error::MissingWindow)
+}
+
+/// A handle to a running graph.
+///
+/// Dropping the handle stops the render loop and removes all event listeners.
+pub struct Handle {
+    graph: Weak<RefCell<Graph>>,
+    render_loop: Option<Rc<RenderLoop>>,
+    resize_observer: Option<ResizeWatcher>,
+    _pixel_ratio_watcher: PixelRatioWatcher,
+    listeners: Vec<EventListener>,
+}
+
+impl Handle {
+    /// Stop the graph and release all its resources.
+    ///
+    /// Once this function returns, no further frame will be rendered and no event will be
+    /// processed. This is the same as dropping the handle, but makes the intent explicit.
+    pub fn shutdown(mut self) {
+        self.cleanup();
+    }
+
+    /// Access the running graph, e.g. to change its configuration.
+    ///
+    /// Returns `None` if the graph was shut down, or is currently busy.
+    pub fn with_graph<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Graph) -> R,
+    {
+        let graph = self.graph.upgrade()?;
+        let mut graph = graph.try_borrow_mut().ok()?;
+        Some(f(&mut graph))
+    }
+
+    /// A reference to the graph, which can be handed out to the application.
+    pub fn controller(&self) -> GraphController {
+        GraphController {
+            graph: self.graph.clone(),
+        }
+    }
+
+    /// The centers of the nodes by keys, see [`Graph::export_positions`].
+    pub fn export_positions<K, F>(&self, key: F) -> Option<HashMap<K, Position>>
+    where
+        K: Eq + Hash,
+        F: Fn(Node) -> Option<K>,
+    {
+        self.with_graph(|graph| graph.export_positions(key))
+    }
+
+    /// Change the graph from one state to another, see [`Graph::apply_diff`].
+    pub fn apply_diff(
+        &self,
+        old: &GraphData,
+        new: &GraphData,
+        nodes: &mut HashMap<String, Node>,
+    ) -> Option<Result<(), GraphDataError>> {
+        self.with_graph(|graph| graph.apply_diff(old, new, nodes))
+    }
+
+    /// Move nodes to centers by keys, see [`Graph::apply_positions`].
+    pub fn apply_positions<K, F>(&self, positions: &HashMap<K, Position>, node: F) -> Option<()>
+    where
+        F: Fn(&K) -> Option<Node>,
+    {
+        self.with_graph(|graph| graph.apply_positions(positions, node))
+    }
+
+    /// Freeze the layout, see [`Graph::pause_layout`].
+    pub fn pause_layout(&self) {
+        self.with_graph(|graph| graph.pause_layout());
+    }
+
+    pub fn resume_layout(&self) {
+        self.with_graph(|graph| graph.resume_layout());
+    }
+
+    /// Advance the layout by `n` steps, see [`Graph::step_layout`].
+    pub fn step(&self, n: usize) {
+        self.with_graph(|graph| graph.step_layout(n));
+    }
+
+    fn cleanup(&mut self) {
+        if let Some(render_loop) = self.render_loop.take() {
+            render_loop.cancel();
+        }
+        self.resize_observer.take();
+        self.listeners.clear();
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+/// A reference to a running graph, for the application to change it.
+///
+/// Unlike the [`Handle`], it doesn't keep the graph running. Once the graph was shut down, or
+/// while it is busy (e.g. when called from one of its callbacks), all methods do nothing and
+/// return `None`.
+#[derive(Clone)]
+pub struct GraphController {
+    graph: Weak<RefCell<Graph>>,
+}
+
+impl GraphController {
+    /// Access the running graph, see [`Handle::with_graph`].
+    pub fn with_graph<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Graph) -> R,
+    {
+        let graph = self.graph.upgrade()?;
+        let mut graph = graph.try_borrow_mut().ok()?;
+        Some(f(&mut graph))
+    }
+
+    /// If the graph was shut down, so that it can't be accessed anymore.
+    pub fn is_stopped(&self) -> bool {
+        self.graph.strong_count() == 0
+    }
+
+    /// Add a node, see [`Graph::add_node`].
+    pub fn add_node(
+        &self,
+        position: impl Into<Position>,
+        size: impl Into<NodeSize>,
+        properties: NodeProperties,
+    ) -> Option<Node> {
+        self.with_graph(|graph| graph.add_node(position, size, properties))
+    }
+
+    pub fn remove_node(&self, node: Node) -> Option<()> {
+        self.with_graph(|graph| graph.remove_node(node))
+    }
+
+    /// Change the properties of a node, see [`Graph::set_properties`].
+    pub fn update_node(&self, node: Node, properties: NodeProperties) -> Option<()> {
+        self.with_graph(|graph| graph.set_properties(node, properties))
+    }
+
+    pub fn add_edge(&self, a: Node, b: Node, edge: EdgeProperties) -> Option<Result<(), Error>> {
+        self.with_graph(|graph| graph.add_edge(a, b, edge))
+    }
+
+    pub fn remove_edge(&self, a: Node, b: Node) -> Option<()> {
+        self.with_graph(|graph| graph.remove_edge(a, b))
+    }
+
+    /// Zoom around the center, see [`Graph::zoom`].
+    pub fn zoom(&self, factor: f64) -> Option<()> {
+        self.with_graph(|graph| graph.zoom(factor))
+    }
+
+    /// Show all nodes, see [`Graph::fit`].
+    pub fn fit(&self, margin: f64) -> Option<()> {
+        self.with_graph(|graph| graph.fit(margin))
+    }
+
+    /// Export as a PNG image, see [`Graph::render_image`].
+    pub async fn export_png(&self, options: ExportOptions) -> Result<Blob, Error> {
+        let canvas = self
+            .with_graph(|graph| graph.render_image(&options))
+            .ok_or(Error::Stopped)??;
+        to_png(&canvas).await
+    }
+
+    /// Put a PNG image on the clipboard, see [`Graph::render_image`].
+    ///
+    /// Browsers only allow this while handling a user gesture, like a click. So this must be
+    /// called from the event handler, while the returned future may be awaited later.
+    pub fn copy_image(&self, options: ExportOptions) -> impl Future<Output = Result<(), Error>> {
+        let written = self
+            .with_graph(|graph| graph.render_image(&options))
+            .ok_or(Error::Stopped)
+            .and_then(|canvas| write_png_to_clipboard(&canvas?));
+        async move {
+            JsFuture::from(written?).await?;
+            Ok(())
+        }
+    }
+
+    /// Enter fullscreen, see [`Graph::request_fullscreen`].
+    pub fn request_fullscreen(&self) -> Option<Result<(), Error>> {
+        self.with_graph(|graph| graph.request_fullscreen())
+    }
+
+    pub fn exit_fullscreen(&self) -> Option<()> {
+        self.with_graph(|graph| graph.exit_fullscreen())
+    }
+
+    /// Enter fullscreen, or leave it if the graph already is in fullscreen.
+    pub fn toggle_fullscreen(&self) -> Option<Result<(), Error>> {
+        self.with_graph(|graph| match graph.is_fullscreen() {
+            true => {
+                graph.exit_fullscreen();
+                Ok(())
+            }
+            false => graph.request_fullscreen(),
+        })
+    }
+
+    pub fn selection(&self) -> Option<BTreeSet<Node>> {
+        self.with_graph(|graph| graph.selection().clone())
+    }
+
+    pub fn select(&self, nodes: impl IntoIterator<Item = Node>) -> Option<()> {
+        self.with_graph(|graph| graph.set_selection(nodes))
+    }
+}
+
+impl PartialEq for GraphController {
+    fn eq(&self, other: &Self) -> bool {
+        self.graph.ptr_eq(&other.graph)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeProperties {
+    /// The distance the layout tries to keep between the two nodes.
+    ///
+    /// By default, this is [`PhysicsConfig::edge_length`].
+    pub length: Option<f64>,
+    /// How strongly the edge pulls towards its length, relative to other edges.
+    pub strength: f64,
+    /// The category, see [`Graph::set_category`].
+    pub category: Option<String>,
+}
+
+impl Default for EdgeProperties {
+    fn default() -> Self {
+        Self {
+            length: None,
+            strength: 1.0,
+            category: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeProperties {
+    pub label: String,
+    /// The category, see [`Graph::set_category`].
+    pub category: Option<String>,
+}
+
+/// How the nodes and edges of a category are shown.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Category {
+    /// The CSS color nodes are filled with, and edges are drawn with.
+    pub color: String,
+    /// If the nodes and edges are shown at all.
+    ///
+    /// Edges of hidden nodes are hidden as well.
+    pub visible: bool,
+}
+
+impl Category {
+    /// A visible category, using a color.
+    pub fn new(color: impl Into<String>) -> Self {
+        Self {
+            color: color.into(),
+            visible: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Node {
+    pub(crate) id: usize,
+}
+
+/// Set an inline style property of an HTML or SVG element.
+fn set_style(element: &Element, name: &str, value: &str) {
+    let style = if let Some(element) = element.dyn_ref::<HtmlElement>() {
+        element.style()
+    } else if let Some(element) = element.dyn_ref::<SvgElement>() {
+        element.style()
+    } else {
+        return;
+    };
+    let _ = style.set_property(name, value);
+}
+
+/// The size of a node, which fits its label without a fixed size.
+fn node_size(node: &NodeData) -> NodeSize {
+    match node.size {
+        Some(size) => NodeSize::Fixed(size),
+        None => NodeSize::FitLabel,
+    }
+}
+
+/// The nodes of an undirected edge, the smaller one first.
+fn ordered(a: Node, b: Node) -> (Node, Node) {
+    (a.min(b), a.max(b))
 }
@@ -0,0 +1,365 @@
+//! Keeping graphs too large for memory in IndexedDB, loading them a neighborhood at a time.
+//!
+//! A [`GraphStore`] holds the nodes and edges of a [`GraphData`] as records, keyed by the keys
+//! of the nodes. A [`LazyLoader`] adds them to a running graph, e.g. expanding a node when it
+//! gets double clicked. The colors of categories and the waypoints of edges aren't stored.
+
+use crate::data::{EdgeData, GraphData, NodeData};
+use crate::graph::{
+    EdgeProperties, Error, GraphController, Node, NodeProperties, NodeSize, Position, Size,
+};
+use js_sys::{Array, Object, Promise, Reflect};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbObjectStore, IdbObjectStoreParameters, IdbOpenDbRequest, IdbRequest,
+    IdbTransaction, IdbTransactionMode,
+};
+
+/// The version of the database layout.
+const VERSION: u32 = 1;
+/// The object store of the nodes, keyed by their keys.
+const NODES: &str = "nodes";
+/// The object store of the edges, keyed by the keys of their nodes.
+const EDGES: &str = "edges";
+
+/// Nodes and edges, stored in an IndexedDB database.
+pub struct GraphStore {
+    db: IdbDatabase,
+}
+
+impl GraphStore {
+    /// Open a database, creating it if it doesn't exist yet.
+    pub async fn open(name: &str) -> Result<Self, Error> {
+        let factory = web_sys::window()
+            .ok_or(Error::MissingWindow)?
+            .indexed_db()?
+            .ok_or(Error::MissingWindow)?;
+        let request = factory.open_with_u32(name, VERSION)?;
+
+        let upgrade = {
+            let request = request.clone();
+            Closure::<dyn FnMut()>::new(move || {
+                if let Err(err) = create_stores(&request) {
+                    log::warn!("Failed to create the object stores: {err:?}");
+                }
+            })
+        };
+        request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+        let db = wait(&request).await;
+        request.set_onupgradeneeded(None);
+
+        Ok(Self {
+            db: db?.unchecked_into(),
+        })
+    }
+
+    /// Add the nodes and edges, replacing the ones with the same keys.
+    pub async fn put(&self, data: &GraphData) -> Result<(), Error> {
+        let transaction = self.db.transaction_with_str_sequence_and_mode(
+            &Array::of2(&NODES.into(), &EDGES.into()),
+            IdbTransactionMode::Readwrite,
+        )?;
+
+        let nodes = transaction.object_store(NODES)?;
+        for node in &data.nodes {
+            nodes.put(&node_record(node)?)?;
+        }
+        let edges = transaction.object_store(EDGES)?;
+        for edge in &data.edges {
+            edges.put(&edge_record(edge)?)?;
+        }
+
+        complete(&transaction).await
+    }
+
+    /// Remove all nodes and edges.
+    pub async fn clear(&self) -> Result<(), Error> {
+        let transaction = self.db.transaction_with_str_sequence_and_mode(
+            &Array::of2(&NODES.into(), &EDGES.into()),
+            IdbTransactionMode::Readwrite,
+        )?;
+        transaction.object_store(NODES)?.clear()?;
+        transaction.object_store(EDGES)?.clear()?;
+        complete(&transaction).await
+    }
+
+    fn store(&self, name: &str) -> Result<IdbObjectStore, Error> {
+        Ok(self.db.transaction_with_str(name)?.object_store(name)?)
+    }
+
+    /// A stored node.
+    pub async fn node(&self, key: &str) -> Result<Option<NodeData>, Error> {
+        let request = self.store(NODES)?.get(&key.into())?;
+        Ok(read_node(&wait(&request).await?))
+    }
+
+    /// The edges from and to a node.
+    pub async fn edges(&self, key: &str) -> Result<Vec<EdgeData>, Error> {
+        let store = self.store(EDGES)?;
+        let from = store.index("from")?.get_all_with_key(&key.into())?;
+        let to = store.index("to")?.get_all_with_key(&key.into())?;
+
+        let mut edges = vec![];
+        for request in [from, to] {
+            let records: Array = wait(&request).await?.unchecked_into();
+            edges.extend(records.iter().filter_map(|record| read_edge(&record)));
+        }
+        Ok(edges)
+    }
+
+    /// A node, its neighbors, and the edges between them and the node.
+    pub async fn neighborhood(&self, key: &str) -> Result<GraphData, Error> {
+        let edges = self.edges(key).await?;
+        let keys: BTreeSet<&str> = edges
+            .iter()
+            .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+            .chain([key])
+            .collect();
+
+        let store = self.store(NODES)?;
+        let requests = keys
+            .iter()
+            .map(|key| store.get(&(*key).into()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut nodes = vec![];
+        for request in requests {
+            nodes.extend(read_node(&wait(&request).await?));
+        }
+
+        let found: BTreeSet<&str> = nodes.iter().map(|node| node.key.as_str()).collect();
+        let edges = edges
+            .iter()
+            .filter(|edge| found.contains(edge.from.as_str()) && found.contains(edge.to.as_str()))
+            .cloned()
+            .collect();
+
+        Ok(GraphData {
+            nodes,
+            edges,
+            categories: Default::default(),
+        })
+    }
+}
+
+fn create_stores(request: &IdbOpenDbRequest) -> Result<(), JsValue> {
+    let db: IdbDatabase = request.result()?.unchecked_into();
+
+    let parameters = IdbObjectStoreParameters::new();
+    parameters.set_key_path(&"key".into());
+    db.create_object_store_with_optional_parameters(NODES, &parameters)?;
+
+    let parameters = IdbObjectStoreParameters::new();
+    parameters.set_key_path(&Array::of2(&"from".into(), &"to".into()));
+    let edges = db.create_object_store_with_optional_parameters(EDGES, &parameters)?;
+    edges.create_index_with_str("from", "from")?;
+    edges.create_index_with_str("to", "to")?;
+
+    Ok(())
+}
+
+/// Wait for a request to succeed, returning its result.
+async fn wait(request: &IdbRequest) -> Result<JsValue, Error> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let result = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+
+    match result {
+        Ok(_) => Ok(request.result()?),
+        Err(_) => Err(match request.error() {
+            Ok(Some(err)) => Error::Web(err.message()),
+            _ => Error::Web("IndexedDB request failed".into()),
+        }),
+    }
+}
+
+/// Wait for a transaction to be committed.
+async fn complete(transaction: &IdbTransaction) -> Result<(), Error> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        transaction.set_oncomplete(Some(&resolve));
+        transaction.set_onerror(Some(&reject));
+        transaction.set_onabort(Some(&reject));
+    });
+    let result = JsFuture::from(promise).await;
+    transaction.set_oncomplete(None);
+    transaction.set_onerror(None);
+    transaction.set_onabort(None);
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(match transaction.error() {
+            Some(err) => Error::Web(err.message()),
+            None => Error::Web("IndexedDB transaction failed".into()),
+        }),
+    }
+}
+
+fn set(record: &Object, name: &str, value: impl Into<JsValue>) -> Result<(), Error> {
+    Reflect::set(record, &name.into(), &value.into())?;
+    Ok(())
+}
+
+fn node_record(node: &NodeData) -> Result<JsValue, Error> {
+    let record = Object::new();
+    set(&record, "key", node.key.as_str())?;
+    set(&record, "label", node.label.as_str())?;
+    if let Some(category) = &node.category {
+        set(&record, "category", category.as_str())?;
+    }
+    set(&record, "x", node.position.x)?;
+    set(&record, "y", node.position.y)?;
+    if let Some(size) = node.size {
+        set(&record, "width", size.width)?;
+        set(&record, "height", size.height)?;
+    }
+    Ok(record.into())
+}
+
+fn edge_record(edge: &EdgeData) -> Result<JsValue, Error> {
+    let record = Object::new();
+    set(&record, "from", edge.from.as_str())?;
+    set(&record, "to", edge.to.as_str())?;
+    if let Some(length) = edge.length {
+        set(&record, "length", length)?;
+    }
+    set(&record, "strength", edge.strength)?;
+    if let Some(category) = &edge.category {
+        set(&record, "category", category.as_str())?;
+    }
+    Ok(record.into())
+}
+
+fn get(record: &JsValue, name: &str) -> JsValue {
+    Reflect::get(record, &name.into()).unwrap_or(JsValue::UNDEFINED)
+}
+
+/// A stored node, or `None` if there is none, or it's invalid.
+fn read_node(record: &JsValue) -> Option<NodeData> {
+    if !record.is_object() {
+        return None;
+    }
+    let size = match (
+        get(record, "width").as_f64(),
+        get(record, "height").as_f64(),
+    ) {
+        (Some(width), Some(height)) => Some(Size { width, height }),
+        _ => None,
+    };
+    Some(NodeData {
+        key: get(record, "key").as_string()?,
+        label: get(record, "label").as_string().unwrap_or_default(),
+        category: get(record, "category").as_string(),
+        position: Position {
+            x: get(record, "x").as_f64().unwrap_or_default(),
+            y: get(record, "y").as_f64().unwrap_or_default(),
+        },
+        size,
+    })
+}
+
+fn read_edge(record: &JsValue) -> Option<EdgeData> {
+    Some(EdgeData {
+        from: get(record, "from").as_string()?,
+        to: get(record, "to").as_string()?,
+        length: get(record, "length").as_f64(),
+        strength: get(record, "strength")
+            .as_f64()
+            .unwrap_or(EdgeProperties::default().strength),
+        category: get(record, "category").as_string(),
+        waypoints: vec![],
+    })
+}
+
+/// Adds the nodes of a [`GraphStore`] to a running graph, when they are needed.
+pub struct LazyLoader {
+    store: GraphStore,
+    graph: GraphController,
+    /// The nodes added so far, by their keys
+    nodes: RefCell<HashMap<String, Node>>,
+}
+
+impl LazyLoader {
+    pub fn new(store: GraphStore, graph: GraphController) -> Self {
+        Self {
+            store,
+            graph,
+            nodes: Default::default(),
+        }
+    }
+
+    pub fn store(&self) -> &GraphStore {
+        &self.store
+    }
+
+    /// The node of a key, once it was added.
+    pub fn node(&self, key: &str) -> Option<Node> {
+        self.nodes.borrow().get(key).copied()
+    }
+
+    /// The key of an added node.
+    pub fn key(&self, node: Node) -> Option<String> {
+        self.nodes
+            .borrow()
+            .iter()
+            .find_map(|(key, added)| (*added == node).then(|| key.clone()))
+    }
+
+    /// Add a node and its neighbors, along with the edges between them, returning the nodes
+    /// which weren't added before.
+    ///
+    /// Neighbors of a node which was added before start at its position, so that they spread
+    /// out from there. Otherwise, nodes start at their stored positions.
+    pub async fn expand(&self, key: &str) -> Result<Vec<Node>, Error> {
+        let data = self.store.neighborhood(key).await?;
+
+        self.graph
+            .with_graph(|graph| {
+                let mut nodes = self.nodes.borrow_mut();
+                let origin = nodes
+                    .get(key)
+                    .and_then(|node| graph.storage().center(*node));
+
+                let mut added = vec![];
+                for node in &data.nodes {
+                    if nodes.contains_key(&node.key) {
+                        continue;
+                    }
+                    let position = origin.unwrap_or(node.position);
+                    let size = match node.size {
+                        Some(size) => NodeSize::Fixed(size),
+                        None => NodeSize::FitLabel,
+                    };
+                    let properties = NodeProperties {
+                        label: node.label.clone(),
+                        category: node.category.clone(),
+                    };
+                    let handle = graph.add_node(position, size, properties);
+                    graph.set_center(handle, position);
+                    nodes.insert(node.key.clone(), handle);
+                    added.push(handle);
+                }
+
+                for edge in &data.edges {
+                    if let (Some(a), Some(b)) = (nodes.get(&edge.from), nodes.get(&edge.to)) {
+                        let properties = EdgeProperties {
+                            length: edge.length,
+                            strength: edge.strength,
+                            category: edge.category.clone(),
+                        };
+                        // skipped if one of the nodes was removed in the meantime
+                        let _ = graph.add_edge(*a, *b, properties);
+                    }
+                }
+
+                added
+            })
+            .ok_or(Error::Stopped)
+    }
+}
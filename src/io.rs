@@ -0,0 +1,57 @@
+//! Reading and writing graphs in the formats of other tools, as [`crate::data::GraphData`].
+//!
+//! Each format is behind its own feature, e.g. `io-graphml`. Files of the enabled formats can be
+//! dropped onto a graph, see [`import::import_dropped_files`].
+
+#[cfg(feature = "io-csv")]
+pub mod csv;
+#[cfg(feature = "io-dot")]
+pub mod dot;
+#[cfg(feature = "io-gexf")]
+pub mod gexf;
+#[cfg(feature = "io-graphml")]
+pub mod graphml;
+pub mod import;
+#[cfg(feature = "io-json")]
+pub mod json;
+
+/// Escape text for XML content and attribute values.
+#[cfg(any(feature = "io-gexf", feature = "io-graphml"))]
+fn escape_xml(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Parse an XML document, using the parser of the browser.
+#[cfg(feature = "io-graphml")]
+fn parse_xml(xml: &str) -> Option<web_sys::Document> {
+    let parser = web_sys::DomParser::new().ok()?;
+    let document = parser
+        .parse_from_string(xml, web_sys::SupportedType::ApplicationXml)
+        .ok()?;
+    // browsers report errors as a document containing the message
+    match document.get_elements_by_tag_name("parsererror").length() {
+        0 => Some(document),
+        _ => None,
+    }
+}
+
+/// The child elements with a local name, ignoring their namespace.
+#[cfg(feature = "io-graphml")]
+fn children(element: &web_sys::Element, name: &str) -> Vec<web_sys::Element> {
+    let children = element.children();
+    (0..children.length())
+        .filter_map(|i| children.item(i))
+        .filter(|child| child.local_name() == name)
+        .collect()
+}
@@ -0,0 +1,211 @@
+//! Comma separated values, as an edge list or an adjacency matrix.
+//!
+//! Nodes are created for every key mentioned, labeled by their keys, and without a position so
+//! that the layout places them. Values may be quoted with `"`, and empty lines are skipped.
+
+use crate::data::{EdgeData, GraphData, NodeData};
+use crate::graph::{EdgeProperties, Position};
+use std::collections::HashSet;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CsvError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: &'static str },
+}
+
+/// Read an edge list, with rows of `source,target` and an optional `weight`.
+///
+/// The weight is used as the strength of the edge. A first row starting with `source,target`
+/// or `from,to` is skipped as the header.
+pub fn read_edges(csv: &str) -> Result<GraphData, CsvError> {
+    let mut records = records(csv)?.into_iter().peekable();
+    // skip the header
+    records.next_if(|(_, record)| {
+        let column = |i: usize| record.get(i).map(|value| value.to_lowercase());
+        matches!(
+            (column(0).as_deref(), column(1).as_deref()),
+            (Some("source"), Some("target")) | (Some("from"), Some("to"))
+        )
+    });
+
+    let mut builder = Builder::default();
+    for (line, record) in records {
+        let error = |message| CsvError::Syntax { line, message };
+        let (Some(from), Some(to)) = (record.first(), record.get(1)) else {
+            return Err(error("expected a source and a target"));
+        };
+        if from.is_empty() || to.is_empty() {
+            return Err(error("expected a source and a target"));
+        }
+        let strength = match record.get(2).filter(|weight| !weight.is_empty()) {
+            Some(weight) => weight.parse().map_err(|_| error("invalid weight"))?,
+            None => EdgeProperties::default().strength,
+        };
+        builder.edge(from, to, strength);
+    }
+
+    Ok(builder.data)
+}
+
+/// Read an adjacency matrix, with the keys of the nodes in the first row and column.
+///
+/// Each non-zero value adds an edge from the node of its row to the node of its column, with
+/// the value as its strength. A symmetric pair of values only adds a single edge.
+pub fn read_matrix(csv: &str) -> Result<GraphData, CsvError> {
+    let mut records = records(csv)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(GraphData::default());
+    };
+    // the first cell is the corner, above the keys of the rows
+    let keys = header.get(1..).unwrap_or_default();
+
+    let mut builder = Builder::default();
+    for key in keys {
+        builder.node(key);
+    }
+
+    let mut rows = vec![];
+    for (line, record) in records {
+        let error = |message| CsvError::Syntax { line, message };
+        let Some((key, values)) = record.split_first() else {
+            continue;
+        };
+        if values.len() != keys.len() {
+            return Err(error("expected a value for each column"));
+        }
+        let values = values
+            .iter()
+            .map(|value| match value.is_empty() {
+                true => Ok(0.0),
+                false => value.parse::<f64>().map_err(|_| error("invalid value")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push((key.clone(), values));
+    }
+
+    for (i, (from, values)) in rows.iter().enumerate() {
+        for (j, (to, value)) in keys.iter().zip(values).enumerate() {
+            if *value == 0.0 || from == to {
+                continue;
+            }
+            // already added from the other side
+            let mirrored = rows
+                .get(j)
+                .filter(|(key, _)| key == to)
+                .and_then(|(_, values)| values.get(i));
+            if j < i && keys.get(i) == Some(from) && mirrored == Some(value) {
+                continue;
+            }
+            builder.edge(from, to, *value);
+        }
+    }
+
+    Ok(builder.data)
+}
+
+/// Collects nodes on the fly, by their keys.
+#[derive(Default)]
+struct Builder {
+    data: GraphData,
+    keys: HashSet<String>,
+}
+
+impl Builder {
+    fn node(&mut self, key: &str) {
+        if self.keys.insert(key.to_string()) {
+            self.data.nodes.push(NodeData {
+                key: key.to_string(),
+                label: key.to_string(),
+                category: None,
+                position: Position { x: 0.0, y: 0.0 },
+                size: None,
+            });
+        }
+    }
+
+    fn edge(&mut self, from: &str, to: &str, strength: f64) {
+        self.node(from);
+        self.node(to);
+        self.data.edges.push(EdgeData {
+            from: from.to_string(),
+            to: to.to_string(),
+            length: None,
+            strength,
+            category: None,
+            waypoints: vec![],
+        });
+    }
+}
+
+/// Split into records of values, each with the line it starts on, skipping empty lines.
+///
+/// Unquoted values are trimmed.
+fn records(csv: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut records = vec![];
+    let mut chars = csv.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start = line;
+        let mut record = vec![];
+        let mut value = String::new();
+        let mut quoted = false;
+
+        loop {
+            match chars.next() {
+                Some('"') if value.trim().is_empty() && !quoted => {
+                    value.clear();
+                    quoted = true;
+                    loop {
+                        match chars.next() {
+                            Some('"') if chars.next_if_eq(&'"').is_some() => value.push('"'),
+                            Some('"') => break,
+                            Some(c) => {
+                                if c == '\n' {
+                                    line += 1;
+                                }
+                                value.push(c);
+                            }
+                            None => {
+                                return Err(CsvError::Syntax {
+                                    line: start,
+                                    message: "unterminated quote",
+                                })
+                            }
+                        }
+                    }
+                }
+                Some(',') => {
+                    record.push(finish(&mut value, quoted));
+                    quoted = false;
+                }
+                Some('\r') => {}
+                Some('\n') | None => {
+                    line += 1;
+                    record.push(finish(&mut value, quoted));
+                    break;
+                }
+                Some(c) => {
+                    if !quoted {
+                        value.push(c);
+                    }
+                }
+            }
+        }
+
+        if record.iter().any(|value| !value.is_empty()) {
+            records.push((start, record));
+        }
+    }
+
+    Ok(records)
+}
+
+/// Take the collected value, trimming it unless it was quoted.
+fn finish(value: &mut String, quoted: bool) -> String {
+    let value = std::mem::take(value);
+    match quoted {
+        true => value,
+        false => value.trim().to_string(),
+    }
+}
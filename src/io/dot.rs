@@ -0,0 +1,423 @@
+//! Graphviz [DOT](https://graphviz.org/doc/info/lang.html), reading a subset of it.
+//!
+//! Nodes and edges are read from the graph, including the ones of its subgraphs. Of the
+//! attributes, only the `label` of nodes and the `weight` of edges are used, as the label and
+//! the strength. Defaults set by `node [...]` and `edge [...]` apply to the statements following
+//! them, within the same subgraph. Ports and the attributes of graphs are ignored.
+//!
+//! Nodes have no position, so that the layout places them.
+
+use crate::data::{EdgeData, GraphData, NodeData};
+use crate::graph::{EdgeProperties, Position};
+use std::collections::HashMap;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum DotError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: &'static str },
+}
+
+/// Read the nodes and edges of a DOT graph.
+pub fn read(dot: &str) -> Result<GraphData, DotError> {
+    let mut parser = Parser {
+        tokens: tokenize(dot)?,
+        position: 0,
+        nodes: vec![],
+        index: HashMap::new(),
+        edges: vec![],
+    };
+    parser.graph()?;
+
+    Ok(GraphData {
+        nodes: parser.nodes,
+        edges: parser.edges,
+        categories: Default::default(),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An identifier, number, or string. Only unquoted ones can be keywords.
+    Id {
+        value: String,
+        quoted: bool,
+    },
+    Open,
+    Close,
+    OpenAttributes,
+    CloseAttributes,
+    Semicolon,
+    Comma,
+    Equals,
+    Colon,
+    /// `--` or `->`, which are treated the same
+    Edge,
+}
+
+/// Split into tokens, each with its line, dropping comments.
+fn tokenize(dot: &str) -> Result<Vec<(Token, usize)>, DotError> {
+    let mut tokens = vec![];
+    let mut chars = dot.chars().peekable();
+    let mut line = 1;
+    // lines starting with `#` are preprocessor output, and ignored
+    let mut line_start = true;
+    let error = |line, message| DotError::Syntax { line, message };
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '#' if line_start => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let start = line;
+                loop {
+                    match chars.next() {
+                        Some('*') if chars.next_if_eq(&'/').is_some() => break,
+                        Some('\n') => line += 1,
+                        Some(_) => {}
+                        None => return Err(error(start, "unterminated comment")),
+                    }
+                }
+                continue;
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '[' => Token::OpenAttributes,
+            ']' => Token::CloseAttributes,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            '=' => Token::Equals,
+            ':' => Token::Colon,
+            '-' if chars.next_if(|c| *c == '-' || *c == '>').is_some() => Token::Edge,
+            '"' => {
+                let start = line;
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => value.push('"'),
+                            // an escaped line break continues the string
+                            Some('\n') => line += 1,
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(c);
+                            }
+                            None => return Err(error(start, "unterminated string")),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                        None => return Err(error(start, "unterminated string")),
+                    }
+                }
+                Token::Id {
+                    value,
+                    quoted: true,
+                }
+            }
+            '<' => {
+                // an HTML string, kept as it is
+                let start = line;
+                let mut value = String::new();
+                let mut depth = 1;
+                loop {
+                    let Some(c) = chars.next() else {
+                        return Err(error(start, "unterminated HTML string"));
+                    };
+                    match c {
+                        '<' => depth += 1,
+                        '>' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '\n' => line += 1,
+                        _ => {}
+                    }
+                    value.push(c);
+                }
+                Token::Id {
+                    value,
+                    quoted: true,
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut value = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    value.push(c);
+                }
+                Token::Id {
+                    value,
+                    quoted: false,
+                }
+            }
+            _ => return Err(error(line, "unexpected character")),
+        };
+        line_start = false;
+        tokens.push((token, line));
+    }
+
+    Ok(tokens)
+}
+
+/// The default attributes of nodes and edges.
+#[derive(Clone, Default)]
+struct Defaults {
+    node: HashMap<String, String>,
+    edge: HashMap<String, String>,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    nodes: Vec<NodeData>,
+    /// The index of each node, by its key
+    index: HashMap<String, usize>,
+    edges: Vec<EdgeData>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens
+            .get(self.position + offset)
+            .map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: &'static str) -> DotError {
+        let line = self
+            .tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line);
+        DotError::Syntax { line, message }
+    }
+
+    /// If the next token is an unquoted keyword, which are case-insensitive.
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Id { value, quoted: false }) if value.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    fn expect(&mut self, token: Token, message: &'static str) -> Result<(), DotError> {
+        match self.peek() == Some(&token) {
+            true => {
+                self.next();
+                Ok(())
+            }
+            false => Err(self.error(message)),
+        }
+    }
+
+    fn id(&mut self) -> Result<String, DotError> {
+        match self.peek().cloned() {
+            Some(Token::Id { value, .. }) => {
+                self.position += 1;
+                Ok(value)
+            }
+            _ => Err(self.error("expected an identifier")),
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), DotError> {
+        if self.keyword("strict") {
+            self.next();
+        }
+        if !self.keyword("graph") && !self.keyword("digraph") {
+            return Err(self.error("expected graph or digraph"));
+        }
+        self.next();
+        if let Some(Token::Id { .. }) = self.peek() {
+            self.next();
+        }
+        self.expect(Token::Open, "expected {")?;
+        self.statements(&mut Defaults::default())?;
+        Ok(())
+    }
+
+    /// Read statements up to the closing brace, returning the nodes they contain.
+    fn statements(&mut self, defaults: &mut Defaults) -> Result<Vec<usize>, DotError> {
+        let mut nodes = vec![];
+        loop {
+            match self.peek() {
+                None => return Err(self.error("expected }")),
+                Some(Token::Close) => {
+                    self.next();
+                    return Ok(nodes);
+                }
+                Some(Token::Semicolon) => {
+                    self.next();
+                }
+                _ => nodes.extend(self.statement(defaults)?),
+            }
+        }
+    }
+
+    /// Read a statement, returning the nodes it contains.
+    fn statement(&mut self, defaults: &mut Defaults) -> Result<Vec<usize>, DotError> {
+        if self.keyword("graph") {
+            self.next();
+            self.attributes()?;
+            return Ok(vec![]);
+        }
+        if self.keyword("node") {
+            self.next();
+            defaults.node.extend(self.attributes()?);
+            return Ok(vec![]);
+        }
+        if self.keyword("edge") {
+            self.next();
+            defaults.edge.extend(self.attributes()?);
+            return Ok(vec![]);
+        }
+        if let (Some(Token::Id { .. }), Some(Token::Equals)) = (self.peek(), self.peek_at(1)) {
+            // an attribute of the graph
+            self.position += 2;
+            self.id()?;
+            return Ok(vec![]);
+        }
+
+        let mut operands = vec![self.operand(defaults)?];
+        while self.peek() == Some(&Token::Edge) {
+            self.next();
+            operands.push(self.operand(defaults)?);
+        }
+        let attributes = self.attributes()?;
+
+        if operands.len() == 1 {
+            if let Some(label) = attributes.get("label") {
+                for node in &operands[0] {
+                    let node = &mut self.nodes[*node];
+                    node.label = label.replace("\\N", &node.key);
+                }
+            }
+        } else {
+            let mut attributes = attributes;
+            for (name, value) in &defaults.edge {
+                attributes
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            let strength = attributes
+                .get("weight")
+                .and_then(|weight| weight.parse().ok())
+                .unwrap_or(EdgeProperties::default().strength);
+
+            for pair in operands.windows(2) {
+                for from in &pair[0] {
+                    for to in &pair[1] {
+                        self.edges.push(EdgeData {
+                            from: self.nodes[*from].key.clone(),
+                            to: self.nodes[*to].key.clone(),
+                            length: None,
+                            strength,
+                            category: None,
+                            waypoints: vec![],
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(operands.concat())
+    }
+
+    /// Read a node or a subgraph, returning the nodes.
+    fn operand(&mut self, defaults: &Defaults) -> Result<Vec<usize>, DotError> {
+        if self.keyword("subgraph") || self.peek() == Some(&Token::Open) {
+            if self.keyword("subgraph") {
+                self.next();
+                if let Some(Token::Id { .. }) = self.peek() {
+                    self.next();
+                }
+            }
+            self.expect(Token::Open, "expected {")?;
+            return self.statements(&mut defaults.clone());
+        }
+
+        let key = self.id()?;
+        // ports only affect where edges are attached
+        while self.peek() == Some(&Token::Colon) {
+            self.next();
+            self.id()?;
+        }
+
+        Ok(vec![self.node(key, defaults)])
+    }
+
+    /// The index of a node, which is created when it's the first mention of it.
+    fn node(&mut self, key: String, defaults: &Defaults) -> usize {
+        if let Some(index) = self.index.get(&key) {
+            return *index;
+        }
+
+        let label = match defaults.node.get("label") {
+            Some(label) => label.replace("\\N", &key),
+            None => key.clone(),
+        };
+        let index = self.nodes.len();
+        self.index.insert(key.clone(), index);
+        self.nodes.push(NodeData {
+            key,
+            label,
+            category: None,
+            position: Position { x: 0.0, y: 0.0 },
+            size: None,
+        });
+        index
+    }
+
+    /// Read any number of attribute lists.
+    fn attributes(&mut self) -> Result<HashMap<String, String>, DotError> {
+        let mut attributes = HashMap::new();
+        while self.peek() == Some(&Token::OpenAttributes) {
+            self.next();
+            loop {
+                match self.peek() {
+                    Some(Token::CloseAttributes) => {
+                        self.next();
+                        break;
+                    }
+                    Some(Token::Comma | Token::Semicolon) => {
+                        self.next();
+                    }
+                    Some(Token::Id { .. }) => {
+                        let name = self.id()?;
+                        self.expect(Token::Equals, "expected =")?;
+                        attributes.insert(name, self.id()?);
+                    }
+                    _ => return Err(self.error("expected an attribute")),
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
@@ -0,0 +1,120 @@
+//! [GEXF](https://gexf.net/), for analyzing graphs in Gephi.
+//!
+//! Positions and sizes are written as visualization attributes, with the y axis pointing up as
+//! in Gephi. Categories are written as attributes of nodes and edges, and their colors, if they
+//! are hex colors like `#ff8800`, as the colors of the nodes. Waypoints aren't written.
+
+use super::escape_xml;
+use crate::data::GraphData;
+use std::fmt::Write;
+
+/// Write a GEXF document, with a directed graph.
+pub fn write(data: &GraphData) -> String {
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push('\n');
+    xml.push_str(
+        r#"<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">"#,
+    );
+    xml.push('\n');
+    xml.push_str("  <graph defaultedgetype=\"directed\">\n");
+
+    xml.push_str("    <attributes class=\"node\">\n");
+    xml.push_str("      <attribute id=\"category\" title=\"category\" type=\"string\"/>\n");
+    xml.push_str("    </attributes>\n");
+    xml.push_str("    <attributes class=\"edge\">\n");
+    xml.push_str("      <attribute id=\"category\" title=\"category\" type=\"string\"/>\n");
+    xml.push_str("      <attribute id=\"length\" title=\"length\" type=\"double\"/>\n");
+    xml.push_str("    </attributes>\n");
+
+    xml.push_str("    <nodes>\n");
+    for node in &data.nodes {
+        let _ = writeln!(
+            xml,
+            r#"      <node id="{}" label="{}">"#,
+            escape_xml(&node.key),
+            escape_xml(&node.label)
+        );
+        if let Some(category) = &node.category {
+            write_attributes(&mut xml, &[("category", category.clone())]);
+        }
+        let color = node
+            .category
+            .as_ref()
+            .and_then(|category| data.categories.get(category))
+            .and_then(|category| hex_color(&category.color));
+        if let Some((r, g, b)) = color {
+            let _ = writeln!(xml, r#"        <viz:color r="{r}" g="{g}" b="{b}"/>"#);
+        }
+        let _ = writeln!(
+            xml,
+            r#"        <viz:position x="{}" y="{}" z="0.0"/>"#,
+            node.position.x, -node.position.y
+        );
+        if let Some(size) = node.size {
+            let _ = writeln!(
+                xml,
+                r#"        <viz:size value="{}"/>"#,
+                size.width.max(size.height) / 2.0
+            );
+        }
+        xml.push_str("      </node>\n");
+    }
+    xml.push_str("    </nodes>\n");
+
+    xml.push_str("    <edges>\n");
+    for (id, edge) in data.edges.iter().enumerate() {
+        let _ = writeln!(
+            xml,
+            r#"      <edge id="{id}" source="{}" target="{}" weight="{}">"#,
+            escape_xml(&edge.from),
+            escape_xml(&edge.to),
+            edge.strength
+        );
+        let mut attributes = vec![];
+        if let Some(category) = &edge.category {
+            attributes.push(("category", category.clone()));
+        }
+        if let Some(length) = edge.length {
+            attributes.push(("length", length.to_string()));
+        }
+        if !attributes.is_empty() {
+            write_attributes(&mut xml, &attributes);
+        }
+        xml.push_str("      </edge>\n");
+    }
+    xml.push_str("    </edges>\n");
+
+    xml.push_str("  </graph>\n</gexf>\n");
+    xml
+}
+
+fn write_attributes(xml: &mut String, attributes: &[(&str, String)]) {
+    xml.push_str("        <attvalues>\n");
+    for (id, value) in attributes {
+        let _ = writeln!(
+            xml,
+            r#"          <attvalue for="{id}" value="{}"/>"#,
+            escape_xml(value)
+        );
+    }
+    xml.push_str("        </attvalues>\n");
+}
+
+/// The components of a CSS hex color, like `#f80` or `#ff8800`.
+fn hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    let component = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        3 => {
+            let digit = |i: usize| Some(component(hex.get(i..i + 1)?)? * 17);
+            Some((digit(0)?, digit(1)?, digit(2)?))
+        }
+        6 => Some((
+            component(hex.get(0..2)?)?,
+            component(hex.get(2..4)?)?,
+            component(hex.get(4..6)?)?,
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,250 @@
+//! [GraphML](http://graphml.graphdrawing.org/), as used by yEd and Gephi.
+//!
+//! Data keys are mapped to properties by their `attr.name`:
+//!
+//! * Nodes: `label`, `category`, `x` and `y` of the center, `width` and `height`
+//! * Edges: `length`, `strength` (or `weight`), `category`
+//!
+//! Other keys are ignored. Labels and geometries of yEd nodes are read as well. Categories are
+//! only written as names, without their colors, and waypoints aren't written.
+
+use super::{children, escape_xml, parse_xml};
+use crate::data::{EdgeData, GraphData, NodeData};
+use crate::graph::{EdgeProperties, Position, Size};
+use std::collections::HashMap;
+use std::fmt::Write;
+use web_sys::Element;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum GraphmlError {
+    #[error("invalid XML")]
+    Xml,
+    #[error("not a GraphML document")]
+    Format,
+    #[error("missing attribute: {0}")]
+    MissingAttribute(&'static str),
+}
+
+/// A data key, declared by a `key` element.
+struct Key {
+    name: String,
+    default: Option<String>,
+}
+
+/// Read the first graph of a GraphML document.
+pub fn read(xml: &str) -> Result<GraphData, GraphmlError> {
+    let document = parse_xml(xml).ok_or(GraphmlError::Xml)?;
+    let root = document
+        .document_element()
+        .filter(|root| root.local_name() == "graphml")
+        .ok_or(GraphmlError::Format)?;
+
+    let mut node_keys = HashMap::new();
+    let mut edge_keys = HashMap::new();
+    for key in children(&root, "key") {
+        let (Some(id), Some(name)) = (key.get_attribute("id"), key.get_attribute("attr.name"))
+        else {
+            continue;
+        };
+        let default = children(&key, "default")
+            .first()
+            .and_then(|default| default.text_content());
+        let target = key.get_attribute("for").unwrap_or_else(|| "all".into());
+        if target == "node" || target == "all" {
+            node_keys.insert(
+                id.clone(),
+                Key {
+                    name: name.clone(),
+                    default: default.clone(),
+                },
+            );
+        }
+        if target == "edge" || target == "all" {
+            edge_keys.insert(id, Key { name, default });
+        }
+    }
+
+    let graph = children(&root, "graph")
+        .into_iter()
+        .next()
+        .ok_or(GraphmlError::Format)?;
+
+    let nodes = children(&graph, "node")
+        .iter()
+        .map(|node| read_node(node, &node_keys))
+        .collect::<Result<_, _>>()?;
+    let edges = children(&graph, "edge")
+        .iter()
+        .map(|edge| read_edge(edge, &edge_keys))
+        .collect::<Result<_, _>>()?;
+
+    Ok(GraphData {
+        nodes,
+        edges,
+        categories: Default::default(),
+    })
+}
+
+/// The values of the data elements by their key names, including the defaults.
+fn values(element: &Element, keys: &HashMap<String, Key>) -> HashMap<String, String> {
+    let mut values: HashMap<String, String> = keys
+        .values()
+        .filter_map(|key| Some((key.name.clone(), key.default.clone()?)))
+        .collect();
+    for data in children(element, "data") {
+        let key = data.get_attribute("key").and_then(|key| keys.get(&key));
+        if let (Some(key), Some(value)) = (key, data.text_content()) {
+            values.insert(key.name.clone(), value.trim().to_string());
+        }
+    }
+    values
+}
+
+fn number(values: &HashMap<String, String>, name: &str) -> Option<f64> {
+    values.get(name)?.parse().ok()
+}
+
+fn read_node(node: &Element, keys: &HashMap<String, Key>) -> Result<NodeData, GraphmlError> {
+    let key = node
+        .get_attribute("id")
+        .ok_or(GraphmlError::MissingAttribute("id"))?;
+    let values = values(node, keys);
+
+    let mut label = values.get("label").cloned();
+    let mut size = match (number(&values, "width"), number(&values, "height")) {
+        (Some(width), Some(height)) => Some(Size { width, height }),
+        _ => None,
+    };
+    let mut position = Position {
+        x: number(&values, "x").unwrap_or_default(),
+        y: number(&values, "y").unwrap_or_default(),
+    };
+
+    // yEd keeps the label and geometry in its own elements, with the position of the top left
+    if let Some(geometry) = descendant(node, "Geometry") {
+        let attribute = |name| geometry.get_attribute(name)?.parse::<f64>().ok();
+        if let (Some(x), Some(y), Some(width), Some(height)) = (
+            attribute("x"),
+            attribute("y"),
+            attribute("width"),
+            attribute("height"),
+        ) {
+            position = Position {
+                x: x + width / 2.0,
+                y: y + height / 2.0,
+            };
+            size = Some(Size { width, height });
+        }
+    }
+    if label.is_none() {
+        label = descendant(node, "NodeLabel").and_then(|label| label.text_content());
+    }
+
+    Ok(NodeData {
+        key,
+        label: label.unwrap_or_default().trim().to_string(),
+        category: values.get("category").cloned(),
+        position,
+        size,
+    })
+}
+
+fn read_edge(edge: &Element, keys: &HashMap<String, Key>) -> Result<EdgeData, GraphmlError> {
+    let from = edge
+        .get_attribute("source")
+        .ok_or(GraphmlError::MissingAttribute("source"))?;
+    let to = edge
+        .get_attribute("target")
+        .ok_or(GraphmlError::MissingAttribute("target"))?;
+    let values = values(edge, keys);
+
+    Ok(EdgeData {
+        from,
+        to,
+        length: number(&values, "length"),
+        strength: number(&values, "strength")
+            .or_else(|| number(&values, "weight"))
+            .unwrap_or(EdgeProperties::default().strength),
+        category: values.get("category").cloned(),
+        waypoints: vec![],
+    })
+}
+
+/// The first descendant with a local name, ignoring its namespace.
+fn descendant(element: &Element, name: &str) -> Option<Element> {
+    element
+        .get_elements_by_tag_name_ns(Some("*"), name)
+        .ok()?
+        .item(0)
+}
+
+/// Write a GraphML document, with a directed graph.
+pub fn write(data: &GraphData) -> String {
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push('\n');
+    xml.push_str(r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#);
+    xml.push('\n');
+
+    for (id, target, name, kind) in [
+        ("label", "node", "label", "string"),
+        ("node_category", "node", "category", "string"),
+        ("x", "node", "x", "double"),
+        ("y", "node", "y", "double"),
+        ("width", "node", "width", "double"),
+        ("height", "node", "height", "double"),
+        ("length", "edge", "length", "double"),
+        ("strength", "edge", "strength", "double"),
+        ("edge_category", "edge", "category", "string"),
+    ] {
+        let _ = writeln!(
+            xml,
+            r#"  <key id="{id}" for="{target}" attr.name="{name}" attr.type="{kind}"/>"#
+        );
+    }
+
+    xml.push_str("  <graph edgedefault=\"directed\">\n");
+
+    for node in &data.nodes {
+        let _ = writeln!(xml, r#"    <node id="{}">"#, escape_xml(&node.key));
+        write_data(&mut xml, "label", &node.label);
+        if let Some(category) = &node.category {
+            write_data(&mut xml, "node_category", category);
+        }
+        write_data(&mut xml, "x", &node.position.x.to_string());
+        write_data(&mut xml, "y", &node.position.y.to_string());
+        if let Some(size) = node.size {
+            write_data(&mut xml, "width", &size.width.to_string());
+            write_data(&mut xml, "height", &size.height.to_string());
+        }
+        xml.push_str("    </node>\n");
+    }
+
+    for edge in &data.edges {
+        let _ = writeln!(
+            xml,
+            r#"    <edge source="{}" target="{}">"#,
+            escape_xml(&edge.from),
+            escape_xml(&edge.to)
+        );
+        if let Some(length) = edge.length {
+            write_data(&mut xml, "length", &length.to_string());
+        }
+        write_data(&mut xml, "strength", &edge.strength.to_string());
+        if let Some(category) = &edge.category {
+            write_data(&mut xml, "edge_category", category);
+        }
+        xml.push_str("    </edge>\n");
+    }
+
+    xml.push_str("  </graph>\n</graphml>\n");
+    xml
+}
+
+fn write_data(xml: &mut String, key: &str, value: &str) {
+    let _ = writeln!(
+        xml,
+        r#"      <data key="{key}">{}</data>"#,
+        escape_xml(value)
+    );
+}
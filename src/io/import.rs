@@ -0,0 +1,206 @@
+//! Importing files dropped onto a graph, in any of the enabled formats.
+//!
+//! The format of a file is detected by its extension, falling back to its content:
+//!
+//! * `.json`, with the `io-json` feature
+//! * `.graphml` or `.xml`, with the `io-graphml` feature
+//! * `.dot` or `.gv`, with the `io-dot` feature
+//! * `.csv`, with the `io-csv` feature, as an adjacency matrix if its first value is empty, and
+//!   an edge list otherwise
+
+use crate::data::{GraphData, GraphDataError};
+use crate::graph::{GraphController, Node};
+use gloo_events::{EventListener, EventListenerOptions, EventListenerPhase};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{DragEvent, File};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Json,
+    Graphml,
+    Dot,
+    Csv,
+}
+
+impl FileFormat {
+    /// Detect the format of a file, by its name or otherwise by its content.
+    pub fn detect(name: &str, content: &str) -> Option<Self> {
+        let extension = name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("json") => return Some(Self::Json),
+            Some("graphml" | "xml") => return Some(Self::Graphml),
+            Some("dot" | "gv") => return Some(Self::Dot),
+            Some("csv") => return Some(Self::Csv),
+            _ => {}
+        }
+
+        let content = content.trim_start();
+        let keyword = content
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if content.starts_with('{') {
+            Some(Self::Json)
+        } else if content.starts_with('<') {
+            Some(Self::Graphml)
+        } else if matches!(keyword.as_str(), "strict" | "graph" | "digraph") {
+            Some(Self::Dot)
+        } else {
+            None
+        }
+    }
+
+    /// Read a graph in this format.
+    pub fn read(self, content: &str) -> Result<GraphData, ImportError> {
+        match self {
+            #[cfg(feature = "io-json")]
+            Self::Json => Ok(super::json::read(content)?),
+            #[cfg(feature = "io-graphml")]
+            Self::Graphml => Ok(super::graphml::read(content)?),
+            #[cfg(feature = "io-dot")]
+            Self::Dot => Ok(super::dot::read(content)?),
+            #[cfg(feature = "io-csv")]
+            Self::Csv => match content.trim_start().starts_with(',') {
+                true => Ok(super::csv::read_matrix(content)?),
+                false => Ok(super::csv::read_edges(content)?),
+            },
+            #[allow(unreachable_patterns)]
+            format => {
+                let _ = content;
+                Err(ImportError::Unsupported(format))
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("unknown format of {0}")]
+    UnknownFormat(String),
+    #[error("the {0:?} format isn't enabled")]
+    Unsupported(FileFormat),
+    #[error("failed to read the file: {0}")]
+    Read(String),
+    #[cfg(feature = "io-json")]
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "io-graphml")]
+    #[error(transparent)]
+    Graphml(#[from] super::graphml::GraphmlError),
+    #[cfg(feature = "io-dot")]
+    #[error(transparent)]
+    Dot(#[from] super::dot::DotError),
+    #[cfg(feature = "io-csv")]
+    #[error(transparent)]
+    Csv(#[from] super::csv::CsvError),
+    #[error(transparent)]
+    Data(#[from] GraphDataError),
+    #[error("the graph isn't running")]
+    Stopped,
+}
+
+/// A file which got added to the graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedFile {
+    pub name: String,
+    pub format: FileFormat,
+    /// The added nodes, by their keys in the file.
+    pub nodes: HashMap<String, Node>,
+}
+
+/// Read a file and detect its format.
+pub async fn read_file(file: &File) -> Result<(FileFormat, GraphData), ImportError> {
+    let content = JsFuture::from(file.text())
+        .await
+        .ok()
+        .and_then(|content| content.as_string())
+        .ok_or_else(|| ImportError::Read(file.name()))?;
+    let format = FileFormat::detect(&file.name(), &content)
+        .ok_or_else(|| ImportError::UnknownFormat(file.name()))?;
+    Ok((format, format.read(&content)?))
+}
+
+/// Add the graphs of files dropped onto a graph, calling the function with the outcome of each.
+///
+/// Files are only accepted while the graph isn't read-only. Drops of files don't reach the
+/// [`crate::graph::Graph::on_drop`] callback. Dropping the returned handle stops accepting them.
+pub fn import_dropped_files<F>(graph: GraphController, f: F) -> FileDropHandle
+where
+    F: FnMut(Result<ImportedFile, ImportError>) + 'static,
+{
+    let Some(element) = graph.with_graph(|graph| graph.element().clone()) else {
+        return FileDropHandle { _listeners: vec![] };
+    };
+    let f = Rc::new(RefCell::new(f));
+
+    // captured, so that file drops can be taken from the graph's own handlers
+    let options = EventListenerOptions {
+        phase: EventListenerPhase::Capture,
+        passive: false,
+    };
+
+    let accepts = {
+        let graph = graph.clone();
+        move |evt: &DragEvent| {
+            let files = evt
+                .data_transfer()
+                .is_some_and(|data| data.types().includes(&"Files".into(), 0));
+            files && graph.with_graph(|graph| !graph.is_read_only()) == Some(true)
+        }
+    };
+
+    let drag_over = {
+        let accepts = accepts.clone();
+        EventListener::new_with_options(&element, "dragover", options, move |evt| {
+            if let Some(evt) = evt.dyn_ref::<DragEvent>().filter(|evt| accepts(evt)) {
+                evt.prevent_default();
+                evt.stop_immediate_propagation();
+            }
+        })
+    };
+
+    let drop = EventListener::new_with_options(&element, "drop", options, move |evt| {
+        let Some(evt) = evt.dyn_ref::<DragEvent>().filter(|evt| accepts(evt)) else {
+            return;
+        };
+        evt.prevent_default();
+        evt.stop_immediate_propagation();
+
+        let Some(files) = evt.data_transfer().and_then(|data| data.files()) else {
+            return;
+        };
+        for file in (0..files.length()).filter_map(|i| files.item(i)) {
+            let graph = graph.clone();
+            let f = f.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = read_file(&file).await.and_then(|(format, data)| {
+                    let nodes = graph
+                        .with_graph(|graph| graph.add_data(&data))
+                        .ok_or(ImportError::Stopped)??;
+                    Ok(ImportedFile {
+                        name: file.name(),
+                        format,
+                        nodes,
+                    })
+                });
+                (f.borrow_mut())(result);
+            });
+        }
+    });
+
+    FileDropHandle {
+        _listeners: vec![drag_over, drop],
+    }
+}
+
+/// Keeps accepting files dropped onto a graph, until dropped.
+pub struct FileDropHandle {
+    _listeners: Vec<EventListener>,
+}
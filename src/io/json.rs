@@ -0,0 +1,13 @@
+//! JSON, as the serialized [`GraphData`], including the categories and waypoints.
+
+use crate::data::GraphData;
+
+/// Read a graph, as written by [`write()`].
+pub fn read(json: &str) -> Result<GraphData, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+pub fn write(data: &GraphData) -> String {
+    // the data only contains maps with string keys, so it can't fail
+    serde_json::to_string(data).unwrap_or_default()
+}
@@ -0,0 +1,137 @@
+use crate::graph::{Position, Size};
+use web_sys::CanvasRenderingContext2d;
+
+/// How node labels are rendered and wrapped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelStyle {
+    /// CSS font definition.
+    pub font: String,
+    /// CSS color of the text.
+    pub color: String,
+    /// Distance between two lines of text.
+    pub line_height: f64,
+    /// Labels wider than this get wrapped.
+    pub max_width: f64,
+    /// Space between the label and the border of the node.
+    pub padding: f64,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        Self {
+            font: "14px sans-serif".to_string(),
+            color: "black".to_string(),
+            line_height: 18.0,
+            max_width: 150.0,
+            padding: 6.0,
+        }
+    }
+}
+
+/// A label, wrapped into lines.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Label {
+    lines: Vec<String>,
+    /// The width of the widest line.
+    width: f64,
+}
+
+impl Label {
+    /// Wrap the text into lines no wider than `max_width`.
+    ///
+    /// Lines are broken at whitespace. Words which don't fit on a line of their own get broken
+    /// at character boundaries.
+    pub(crate) fn layout(
+        ctx: &CanvasRenderingContext2d,
+        style: &LabelStyle,
+        text: &str,
+        max_width: f64,
+    ) -> Self {
+        ctx.save();
+        ctx.set_font(&style.font);
+
+        let measure = |s: &str| ctx.measure_text(s).map(|m| m.width()).unwrap_or_default();
+
+        let mut lines = Vec::new();
+        let mut current = String::new();
+
+        for word in text.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{current} {word}")
+            };
+
+            if measure(&candidate) <= max_width {
+                current = candidate;
+                continue;
+            }
+
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+
+            // the word alone might still be too wide
+            for c in word.chars() {
+                current.push(c);
+                if measure(&current) > max_width && current.chars().count() > 1 {
+                    current.pop();
+                    lines.push(std::mem::take(&mut current));
+                    current.push(c);
+                }
+            }
+        }
+
+        if !current.is_empty() {
+            lines.push(current);
+        }
+
+        let width = lines.iter().map(|l| measure(l)).fold(0.0, f64::max);
+
+        ctx.restore();
+
+        Self { lines, width }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The width of the widest line.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// The size a node needs to fit this label, including padding.
+    pub fn size(&self, style: &LabelStyle) -> Size {
+        Size {
+            width: self.width + style.padding * 2.0,
+            height: self.lines.len().max(1) as f64 * style.line_height + style.padding * 2.0,
+        }
+    }
+
+    /// Draw the label, centered on the position.
+    pub(crate) fn draw(
+        &self,
+        ctx: &CanvasRenderingContext2d,
+        style: &LabelStyle,
+        center: Position,
+    ) {
+        if self.lines.is_empty() {
+            return;
+        }
+
+        ctx.save();
+        ctx.set_font(&style.font);
+        ctx.set_fill_style_str(&style.color);
+        ctx.set_text_align("center");
+        ctx.set_text_baseline("middle");
+
+        let top = center.y - (self.lines.len() - 1) as f64 * style.line_height / 2.0;
+        for (i, line) in self.lines.iter().enumerate() {
+            let _ = ctx.fill_text(line, center.x, top + i as f64 * style.line_height);
+        }
+
+        ctx.restore();
+    }
+}
@@ -0,0 +1,128 @@
+//! Layouts, positioning the nodes of a graph.
+
+mod align;
+mod bipartite;
+mod circular;
+mod constraint;
+mod force;
+mod grid;
+mod layered;
+mod metrics;
+mod overlap;
+mod random;
+mod stress;
+mod tree;
+#[cfg(feature = "worker")]
+pub mod worker;
+
+pub use align::*;
+pub use bipartite::*;
+pub use circular::*;
+pub use constraint::*;
+pub use force::*;
+pub use grid::*;
+pub use layered::*;
+pub use metrics::*;
+pub use overlap::*;
+pub use random::*;
+pub use stress::*;
+pub use tree::*;
+
+use crate::graph::{Node, Position};
+use crate::storage::GraphStorage;
+use std::collections::HashMap;
+
+/// Positions the nodes of a graph.
+///
+/// A layout is stepped once per frame. It may converge over many steps, like a physics
+/// simulation, or position everything at once.
+pub trait Layout {
+    /// Advance the layout by `dt` seconds.
+    ///
+    /// Returns `true` if any node was moved.
+    fn step(&mut self, graph: &mut GraphStorage, dt: f64) -> bool;
+
+    /// Apply the physics parameters, layouts which aren't simulations ignore them.
+    fn set_physics(&mut self, _physics: &PhysicsConfig) {}
+
+    /// Restart a simulation which came to rest, e.g. because the graph changed.
+    fn reheat(&mut self) {}
+
+    /// Nodes were added, or existing nodes got new edges.
+    ///
+    /// `added` are the new nodes, `touched` existing nodes with new edges. By default, this
+    /// reheats the layout.
+    fn nodes_changed(&mut self, _graph: &mut GraphStorage, _added: &[Node], _touched: &[Node]) {
+        self.reheat();
+    }
+
+    /// If the layout came to rest, and won't move any node until reheated.
+    fn is_stable(&self) -> bool {
+        true
+    }
+}
+
+/// A layout computing the positions of all nodes at once.
+///
+/// Arrangements are applied using [`crate::graph::Graph::layout`].
+pub trait Arrangement {
+    /// Compute the center position of the nodes.
+    ///
+    /// Nodes missing from the result keep their current position.
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position>;
+}
+
+/// Move all positions, so that their bounding box is centered on `center`.
+pub(crate) fn recenter(positions: &mut HashMap<Node, Position>, center: Position) {
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in positions.values() {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+
+    if positions.is_empty() {
+        return;
+    }
+
+    let dx = center.x - (min_x + max_x) / 2.0;
+    let dy = center.y - (min_y + max_y) / 2.0;
+    for p in positions.values_mut() {
+        p.x += dx;
+        p.y += dy;
+    }
+}
+
+/// A layout which never moves any node.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StaticLayout;
+
+impl Layout for StaticLayout {
+    fn step(&mut self, _graph: &mut GraphStorage, _dt: f64) -> bool {
+        false
+    }
+}
+
+impl<L: Layout + ?Sized> Layout for Box<L> {
+    fn step(&mut self, graph: &mut GraphStorage, dt: f64) -> bool {
+        (**self).step(graph, dt)
+    }
+
+    fn set_physics(&mut self, physics: &PhysicsConfig) {
+        (**self).set_physics(physics)
+    }
+
+    fn reheat(&mut self) {
+        (**self).reheat()
+    }
+
+    fn nodes_changed(&mut self, graph: &mut GraphStorage, added: &[Node], touched: &[Node]) {
+        (**self).nodes_changed(graph, added, touched)
+    }
+
+    fn is_stable(&self) -> bool {
+        (**self).is_stable()
+    }
+}
@@ -0,0 +1,153 @@
+use super::Arrangement;
+use crate::graph::{Node, Position, Size};
+use crate::storage::GraphStorage;
+use std::collections::HashMap;
+
+/// Which border, or center, of nodes to line up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    /// The horizontal centers, lining nodes up vertically.
+    Center,
+    Right,
+    Top,
+    /// The vertical centers, lining nodes up horizontally.
+    Middle,
+    Bottom,
+}
+
+/// An axis along which nodes are distributed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// Lines up nodes, at the outermost border or the average center of them.
+///
+/// This is applied to the selected nodes using [`crate::graph::Graph::align_selection`]. Other
+/// nodes keep their positions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Align {
+    pub nodes: Vec<Node>,
+    pub alignment: Alignment,
+}
+
+impl Arrangement for Align {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let boxes = boxes(graph, &self.nodes);
+        if boxes.is_empty() {
+            return HashMap::new();
+        }
+
+        let n = boxes.len() as f64;
+        let fold = |f: fn(&(Node, Position, Size)) -> f64, g: fn(f64, f64) -> f64| {
+            boxes.iter().map(f).reduce(g).unwrap_or_default()
+        };
+
+        let target = match self.alignment {
+            Alignment::Left => fold(|(_, c, s)| c.x - s.width / 2.0, f64::min),
+            Alignment::Center => fold(|(_, c, _)| c.x, |a, b| a + b) / n,
+            Alignment::Right => fold(|(_, c, s)| c.x + s.width / 2.0, f64::max),
+            Alignment::Top => fold(|(_, c, s)| c.y - s.height / 2.0, f64::min),
+            Alignment::Middle => fold(|(_, c, _)| c.y, |a, b| a + b) / n,
+            Alignment::Bottom => fold(|(_, c, s)| c.y + s.height / 2.0, f64::max),
+        };
+
+        boxes
+            .into_iter()
+            .map(|(node, center, size)| {
+                let center = match self.alignment {
+                    Alignment::Left => Position {
+                        x: target + size.width / 2.0,
+                        ..center
+                    },
+                    Alignment::Center => Position {
+                        x: target,
+                        ..center
+                    },
+                    Alignment::Right => Position {
+                        x: target - size.width / 2.0,
+                        ..center
+                    },
+                    Alignment::Top => Position {
+                        y: target + size.height / 2.0,
+                        ..center
+                    },
+                    Alignment::Middle => Position {
+                        y: target,
+                        ..center
+                    },
+                    Alignment::Bottom => Position {
+                        y: target - size.height / 2.0,
+                        ..center
+                    },
+                };
+                (node, center)
+            })
+            .collect()
+    }
+}
+
+/// Spreads nodes along an axis, with the same space between each pair of neighbors.
+///
+/// The outermost nodes keep their positions, the others keep their order. This is applied to
+/// the selected nodes using [`crate::graph::Graph::distribute_selection`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribute {
+    pub nodes: Vec<Node>,
+    pub axis: Axis,
+}
+
+impl Arrangement for Distribute {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let axis = self.axis;
+        // center and length along the axis
+        let along = move |center: Position, size: Size| match axis {
+            Axis::Horizontal => (center.x, size.width),
+            Axis::Vertical => (center.y, size.height),
+        };
+
+        let mut boxes = boxes(graph, &self.nodes);
+        if boxes.len() < 3 {
+            return HashMap::new();
+        }
+        boxes.sort_by(|a, b| along(a.1, a.2).0.total_cmp(&along(b.1, b.2).0));
+
+        let (first, first_length) = along(boxes[0].1, boxes[0].2);
+        let (last, last_length) = along(boxes[boxes.len() - 1].1, boxes[boxes.len() - 1].2);
+        let start = first - first_length / 2.0;
+        let end = last + last_length / 2.0;
+        let lengths: f64 = boxes.iter().map(|(_, c, s)| along(*c, *s).1).sum();
+        let gap = (end - start - lengths) / (boxes.len() - 1) as f64;
+
+        let mut offset = start;
+        boxes
+            .into_iter()
+            .map(|(node, center, size)| {
+                let length = along(center, size).1;
+                let middle = offset + length / 2.0;
+                offset += length + gap;
+                let center = match axis {
+                    Axis::Horizontal => Position {
+                        x: middle,
+                        ..center
+                    },
+                    Axis::Vertical => Position {
+                        y: middle,
+                        ..center
+                    },
+                };
+                (node, center)
+            })
+            .collect()
+    }
+}
+
+/// The center and size of all existing nodes.
+fn boxes(graph: &GraphStorage, nodes: &[Node]) -> Vec<(Node, Position, Size)> {
+    nodes
+        .iter()
+        .filter_map(|node| Some((*node, graph.center(*node)?, graph.size(*node)?)))
+        .collect()
+}
@@ -0,0 +1,108 @@
+use super::{recenter, Arrangement};
+use crate::graph::{Node, Position};
+use crate::storage::GraphStorage;
+use std::collections::{BTreeSet, HashMap};
+use std::f64::consts::TAU;
+
+/// Places all nodes evenly on a circle.
+///
+/// The radius is chosen so that nodes don't overlap, but is at least [`Self::min_radius`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircularLayout {
+    /// Order nodes so that neighbors are close to each other, reducing edge crossings.
+    ///
+    /// Otherwise, nodes are placed in the order they were added.
+    pub ordered: bool,
+    pub min_radius: f64,
+    /// Space between two nodes on the circle.
+    pub spacing: f64,
+}
+
+impl Default for CircularLayout {
+    fn default() -> Self {
+        Self {
+            ordered: true,
+            min_radius: 100.0,
+            spacing: 20.0,
+        }
+    }
+}
+
+impl Arrangement for CircularLayout {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let nodes = match self.ordered {
+            true => neighborhood_order(graph),
+            false => {
+                let mut nodes: Vec<Node> = graph.nodes().collect();
+                nodes.sort();
+                nodes
+            }
+        };
+
+        // give each node a share of the circumference, based on its size
+
+        let extents: Vec<f64> = nodes
+            .iter()
+            .map(|n| {
+                let diagonal = graph.size(*n).map_or(0.0, |s| s.width.hypot(s.height));
+                diagonal + self.spacing
+            })
+            .collect();
+        let circumference: f64 = extents.iter().sum();
+        let radius = (circumference / TAU).max(self.min_radius);
+
+        let mut result = HashMap::with_capacity(nodes.len());
+        let mut along = 0.0;
+        for (node, extent) in nodes.iter().zip(&extents) {
+            let angle = (along + extent / 2.0) / circumference * TAU;
+            result.insert(
+                *node,
+                Position {
+                    x: radius * angle.cos(),
+                    y: radius * angle.sin(),
+                },
+            );
+            along += extent;
+        }
+
+        if let Some(center) = graph.centroid() {
+            recenter(&mut result, center);
+        }
+
+        result
+    }
+}
+
+/// Order nodes by a depth first traversal, starting with the most connected node of each
+/// component.
+fn neighborhood_order(graph: &GraphStorage) -> Vec<Node> {
+    let mut candidates: Vec<(usize, Node)> = graph
+        .nodes()
+        .map(|n| (graph.neighbors(n).count(), n))
+        .collect();
+    // most connected first, ties by age
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let mut visited = BTreeSet::new();
+    let mut result = Vec::with_capacity(candidates.len());
+
+    for (_, start) in candidates {
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            result.push(node);
+
+            let mut next: Vec<Node> = graph
+                .neighbors(node)
+                .filter(|n| !visited.contains(n))
+                .collect();
+            // visit the lowest ID first
+            next.sort_by(|a, b| b.cmp(a));
+            stack.extend(next);
+        }
+    }
+
+    result
+}
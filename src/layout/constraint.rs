@@ -0,0 +1,52 @@
+use crate::graph::Node;
+use crate::storage::GraphStorage;
+
+/// A restriction on where a simulated layout may move a node.
+///
+/// Constraints are added using [`crate::graph::Graph::add_constraint`]. They are respected by
+/// the [`super::ForceLayout`], dragging a node is still possible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constraint {
+    /// Keep the center of the node at this x coordinate.
+    FixedX(f64),
+    /// Keep the center of the node at this y coordinate.
+    FixedY(f64),
+    /// Keep the node in a horizontal layer, layers being [`super::PhysicsConfig::layer_spacing`]
+    /// apart, starting at zero.
+    Layer(usize),
+    /// Keep the node at the same y coordinate as another node.
+    AlignHorizontal(Node),
+    /// Keep the node at the same x coordinate as another node.
+    AlignVertical(Node),
+}
+
+/// The coordinates a node is locked to, by its constraints.
+///
+/// Later constraints override earlier ones on the same axis.
+pub(crate) fn locks(
+    graph: &GraphStorage,
+    node: Node,
+    layer_spacing: f64,
+) -> (Option<f64>, Option<f64>) {
+    let (mut x, mut y) = (None, None);
+
+    for constraint in graph.constraints(node) {
+        match *constraint {
+            Constraint::FixedX(value) => x = Some(value),
+            Constraint::FixedY(value) => y = Some(value),
+            Constraint::Layer(layer) => y = Some(layer as f64 * layer_spacing),
+            Constraint::AlignHorizontal(other) => {
+                if let Some(center) = graph.center(other) {
+                    y = Some(center.y);
+                }
+            }
+            Constraint::AlignVertical(other) => {
+                if let Some(center) = graph.center(other) {
+                    x = Some(center.x);
+                }
+            }
+        }
+    }
+
+    (x, y)
+}
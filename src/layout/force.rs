@@ -0,0 +1,598 @@
+//! Force-directed layout simulation.
+//!
+//! Nodes are bodies repelling each other, while edges are springs pulling connected nodes
+//! towards their rest length. Forces change the velocity of a body, which is damped each step.
+
+mod quadtree;
+
+use super::constraint::locks;
+use super::{Layout, Rng};
+use crate::graph::{Node, Position, Size};
+use crate::storage::GraphStorage;
+use quadtree::QuadTree;
+use std::collections::{BTreeSet, HashMap};
+
+/// Bodies slower than this are considered to be at rest.
+const REST_VELOCITY: f64 = 0.01;
+/// Number of bodies above which the repulsion is approximated using Barnes–Hut.
+const BARNES_HUT_THRESHOLD: usize = 64;
+/// Accuracy of the Barnes–Hut approximation, lower is more accurate but slower.
+const THETA: f64 = 0.9;
+/// Lower bound of the distance between two bodies, preventing infinite forces.
+const MIN_DISTANCE: f64 = 1.0;
+
+/// The default layout, a force-directed simulation.
+#[derive(Debug)]
+pub struct ForceLayout {
+    velocities: HashMap<Node, Position>,
+    physics: PhysicsConfig,
+    /// The temperature of the simulation, scaling all forces
+    alpha: f64,
+    rng: Rng,
+    /// The nodes being simulated after an incremental change, or `None` for all nodes
+    active: Option<BTreeSet<Node>>,
+}
+
+/// The parameters of the force-directed simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicsConfig {
+    /// Stiffness of the springs.
+    pub stiffness: f64,
+    /// Strength of the repulsion between two nodes.
+    pub repulsion: f64,
+    /// Fraction of the velocity kept for the next step.
+    pub damping: f64,
+    /// Maximum speed of a node, in pixels per step.
+    pub max_velocity: f64,
+    /// The rest length of edges without an explicit length.
+    pub edge_length: f64,
+    /// Number of simulation steps per frame.
+    pub iterations: usize,
+    /// Fraction by which the temperature of the simulation cools down each step.
+    pub alpha_decay: f64,
+    /// The temperature below which the simulation stops.
+    pub alpha_min: f64,
+    /// The total movement of all nodes per step, below which the simulation stops.
+    pub epsilon: f64,
+    /// Distance between two layers of [`super::Constraint::Layer`].
+    pub layer_spacing: f64,
+    /// Lay out nodes added to a stable graph locally, instead of reheating the whole graph.
+    ///
+    /// New nodes are placed next to their neighbors, and only they and their neighbors are
+    /// simulated until they settle.
+    pub incremental: bool,
+    /// Seed for the random jitter, the same seed leads to the same layout.
+    pub seed: u64,
+    pub gravity: Gravity,
+    pub collision: Collision,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            stiffness: 0.02,
+            repulsion: 20_000.0,
+            damping: 0.85,
+            max_velocity: 50.0,
+            edge_length: 100.0,
+            iterations: 1,
+            alpha_decay: 0.01,
+            alpha_min: 0.001,
+            epsilon: 0.5,
+            layer_spacing: 100.0,
+            incremental: true,
+            seed: 0,
+            gravity: Default::default(),
+            collision: Default::default(),
+        }
+    }
+}
+
+/// A weak force pulling all nodes towards a focal point.
+///
+/// This keeps disconnected parts of the graph from drifting away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gravity {
+    /// Fraction of the distance to the focal point, applied as force each step.
+    pub strength: f64,
+    /// The point nodes are pulled towards.
+    ///
+    /// By default, this is the center of the visible area, or the centroid of the graph if it
+    /// isn't displayed.
+    pub focus: Option<Position>,
+}
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self {
+            strength: 0.005,
+            focus: None,
+        }
+    }
+}
+
+impl Gravity {
+    /// No gravity at all.
+    pub fn none() -> Self {
+        Self {
+            strength: 0.0,
+            focus: None,
+        }
+    }
+}
+
+/// Pushes overlapping nodes apart, based on their actual size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collision {
+    /// Fraction of the overlap, resolved each step.
+    pub strength: f64,
+    /// Extra space to keep around each node.
+    pub padding: f64,
+}
+
+impl Default for Collision {
+    fn default() -> Self {
+        Self {
+            strength: 0.5,
+            padding: 10.0,
+        }
+    }
+}
+
+impl Collision {
+    /// Allow nodes to overlap.
+    pub fn none() -> Self {
+        Self {
+            strength: 0.0,
+            padding: 0.0,
+        }
+    }
+}
+
+impl Default for ForceLayout {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl ForceLayout {
+    pub fn new(physics: PhysicsConfig) -> Self {
+        Self {
+            velocities: Default::default(),
+            physics,
+            alpha: 1.0,
+            rng: Rng::new(physics.seed),
+            active: None,
+        }
+    }
+
+    pub fn physics(&self) -> &PhysicsConfig {
+        &self.physics
+    }
+}
+
+impl Layout for ForceLayout {
+    fn set_physics(&mut self, physics: &PhysicsConfig) {
+        if physics.seed != self.physics.seed {
+            self.rng = Rng::new(physics.seed);
+        }
+        self.physics = *physics;
+        self.reheat();
+    }
+
+    fn reheat(&mut self) {
+        self.alpha = 1.0;
+        self.active = None;
+    }
+
+    fn nodes_changed(&mut self, graph: &mut GraphStorage, added: &[Node], touched: &[Node]) {
+        if !self.physics.incremental {
+            self.reheat();
+            return;
+        }
+
+        let mut active = match (self.is_stable(), self.active.take()) {
+            (true, _) => BTreeSet::new(),
+            (false, Some(active)) => active,
+            // the whole graph is being simulated anyway
+            (false, None) => return,
+        };
+
+        for node in added {
+            self.place_near_neighbors(graph, *node, added);
+        }
+
+        for node in added.iter().chain(touched) {
+            active.insert(*node);
+            active.extend(graph.neighbors(*node));
+        }
+
+        self.active = Some(active);
+        self.alpha = 1.0;
+    }
+
+    /// If the simulation cooled down, and no longer moves any node.
+    fn is_stable(&self) -> bool {
+        self.alpha < self.physics.alpha_min
+    }
+
+    fn step(&mut self, graph: &mut GraphStorage, _dt: f64) -> bool {
+        if self.is_stable() {
+            return false;
+        }
+
+        let (nodes, mut bodies, springs) = snapshot(graph, &self.physics);
+        let focus = focus(graph, &self.physics);
+
+        if let Some(active) = &self.active {
+            // the rest of the graph stays in place, but still pushes and pulls
+            for (node, body) in nodes.iter().zip(bodies.iter_mut()) {
+                if !active.contains(node) {
+                    body.fixed = true;
+                }
+            }
+        }
+
+        let moved = self.simulate(&nodes, &mut bodies, &springs, focus);
+
+        for (node, body) in nodes.into_iter().zip(bodies) {
+            graph.set_center(node, body.position);
+        }
+
+        if self.is_stable() {
+            self.active = None;
+        }
+
+        moved
+    }
+}
+
+impl ForceLayout {
+    /// Move a new node next to its neighbors, which are already placed.
+    fn place_near_neighbors(&mut self, graph: &mut GraphStorage, node: Node, added: &[Node]) {
+        let placed: Vec<Position> = graph
+            .neighbors(node)
+            .filter(|n| !added.contains(n))
+            .filter_map(|n| graph.center(n))
+            .collect();
+        if placed.is_empty() {
+            return;
+        }
+
+        let n = placed.len() as f64;
+        let spread = self.physics.edge_length / 2.0;
+        let position = Position {
+            x: placed.iter().map(|p| p.x).sum::<f64>() / n + self.rng.range(-spread, spread),
+            y: placed.iter().map(|p| p.y).sum::<f64>() / n + self.rng.range(-spread, spread),
+        };
+        graph.set_center(node, position);
+    }
+
+    /// Run the steps of a single frame, keeping the velocities of the bodies.
+    ///
+    /// Returns `true` if any body moved.
+    pub(crate) fn simulate(
+        &mut self,
+        nodes: &[Node],
+        bodies: &mut [Body],
+        springs: &[Spring],
+        focus: Option<Position>,
+    ) -> bool {
+        for (node, body) in nodes.iter().zip(bodies.iter_mut()) {
+            body.velocity = self
+                .velocities
+                .get(node)
+                .copied()
+                .unwrap_or(Position { x: 0.0, y: 0.0 });
+        }
+
+        let mut moved = false;
+        for _ in 0..self.physics.iterations {
+            // without a visible area, the centroid moves with the nodes
+            let focus = focus.or_else(|| centroid(bodies));
+            let movement = step(
+                bodies,
+                springs,
+                &self.physics,
+                self.alpha,
+                focus,
+                &mut self.rng,
+            );
+            moved |= movement > 0.0;
+
+            self.alpha *= 1.0 - self.physics.alpha_decay;
+            if movement < self.physics.epsilon {
+                // settled early, freeze
+                self.alpha = 0.0;
+            }
+            if self.is_stable() {
+                break;
+            }
+        }
+
+        self.velocities.clear();
+        for (node, body) in nodes.iter().zip(bodies.iter()) {
+            self.velocities.insert(*node, body.velocity);
+        }
+
+        moved
+    }
+}
+
+/// Capture the nodes of the graph as bodies, and its edges as springs.
+pub(crate) fn snapshot(
+    graph: &GraphStorage,
+    physics: &PhysicsConfig,
+) -> (Vec<Node>, Vec<Body>, Vec<Spring>) {
+    let nodes: Vec<_> = graph.nodes().collect();
+    let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+    let bodies = nodes
+        .iter()
+        .map(|node| {
+            let (lock_x, lock_y) = locks(graph, *node, physics.layer_spacing);
+            Body {
+                position: graph.center(*node).unwrap_or(Position { x: 0.0, y: 0.0 }),
+                size: graph.size(*node).unwrap_or(Size {
+                    width: 0.0,
+                    height: 0.0,
+                }),
+                velocity: Position { x: 0.0, y: 0.0 },
+                fixed: graph.is_fixed(*node),
+                lock_x,
+                lock_y,
+            }
+        })
+        .collect();
+
+    // edges of missing nodes are skipped, instead of panicking on an inconsistent graph
+    let springs = graph
+        .edges()
+        .filter_map(|(from, to, edge)| {
+            Some(Spring {
+                a: *index.get(&from)?,
+                b: *index.get(&to)?,
+                length: edge.length.unwrap_or(physics.edge_length),
+                strength: edge.strength,
+            })
+        })
+        .collect();
+
+    (nodes, bodies, springs)
+}
+
+/// The point gravity pulls towards, unless it's the centroid of the bodies.
+pub(crate) fn focus(graph: &GraphStorage, physics: &PhysicsConfig) -> Option<Position> {
+    physics
+        .gravity
+        .focus
+        .or_else(|| graph.visible_rect().map(|rect| rect.center()))
+}
+
+pub(crate) struct Body {
+    /// The center of the body.
+    pub position: Position,
+    pub size: Size,
+    pub velocity: Position,
+    /// A fixed body isn't moved by the simulation, but still exerts forces on others.
+    pub fixed: bool,
+    /// The x coordinate the body is constrained to.
+    pub lock_x: Option<f64>,
+    /// The y coordinate the body is constrained to.
+    pub lock_y: Option<f64>,
+}
+
+pub(crate) struct Spring {
+    /// Index of the first body.
+    pub a: usize,
+    /// Index of the second body.
+    pub b: usize,
+    /// The distance the spring wants to keep.
+    pub length: f64,
+    /// Factor of the stiffness.
+    pub strength: f64,
+}
+
+/// Run a single step of the simulation.
+///
+/// All forces are scaled by `alpha`, and gravity pulls towards `focus`, if present. Returns the
+/// total distance moved by all bodies.
+pub(crate) fn step(
+    bodies: &mut [Body],
+    springs: &[Spring],
+    physics: &PhysicsConfig,
+    alpha: f64,
+    focus: Option<Position>,
+    rng: &mut Rng,
+) -> f64 {
+    separate(bodies, rng);
+
+    let mut forces = vec![Position { x: 0.0, y: 0.0 }; bodies.len()];
+
+    // repulsion between all pairs of bodies
+
+    if bodies.len() > BARNES_HUT_THRESHOLD {
+        let positions: Vec<_> = bodies.iter().map(|body| body.position).collect();
+        let tree = QuadTree::new(&positions);
+        for (i, position) in positions.iter().enumerate() {
+            forces[i] = tree.force(i, *position, physics.repulsion, THETA);
+        }
+    } else {
+        for i in 0..bodies.len() {
+            for j in (i + 1)..bodies.len() {
+                let (dx, dy, distance) = delta(bodies[j].position, bodies[i].position);
+                let force = physics.repulsion / (distance * distance);
+
+                forces[i].x += dx / distance * force;
+                forces[i].y += dy / distance * force;
+                forces[j].x -= dx / distance * force;
+                forces[j].y -= dy / distance * force;
+            }
+        }
+    }
+
+    // springs pulling towards their rest length
+
+    for spring in springs {
+        let (dx, dy, distance) = delta(bodies[spring.a].position, bodies[spring.b].position);
+        let force = physics.stiffness * spring.strength * (distance - spring.length);
+
+        forces[spring.a].x += dx / distance * force;
+        forces[spring.a].y += dy / distance * force;
+        forces[spring.b].x -= dx / distance * force;
+        forces[spring.b].y -= dy / distance * force;
+    }
+
+    // gravity towards the focal point, growing with the distance
+
+    let strength = physics.gravity.strength;
+    if let Some(focus) = focus.filter(|_| strength > 0.0) {
+        for (body, force) in bodies.iter().zip(&mut forces) {
+            force.x += (focus.x - body.position.x) * strength;
+            force.y += (focus.y - body.position.y) * strength;
+        }
+    }
+
+    // collisions, pushing overlapping bodies apart
+
+    if physics.collision.strength > 0.0 {
+        collide(bodies, &mut forces, physics.collision);
+    }
+
+    // integrate
+
+    let mut movement = 0.0;
+
+    for (body, force) in bodies.iter_mut().zip(forces) {
+        if body.fixed {
+            body.velocity = Position { x: 0.0, y: 0.0 };
+            continue;
+        }
+
+        let mut vx = (body.velocity.x + force.x * alpha) * physics.damping;
+        let mut vy = (body.velocity.y + force.y * alpha) * physics.damping;
+        if body.lock_x.is_some() {
+            vx = 0.0;
+        }
+        if body.lock_y.is_some() {
+            vy = 0.0;
+        }
+
+        let speed = (vx * vx + vy * vy).sqrt();
+        if speed > physics.max_velocity {
+            vx = vx / speed * physics.max_velocity;
+            vy = vy / speed * physics.max_velocity;
+        }
+
+        body.velocity = Position { x: vx, y: vy };
+
+        if speed > REST_VELOCITY {
+            body.position.x += vx;
+            body.position.y += vy;
+            movement += speed.min(physics.max_velocity);
+        }
+
+        // snap onto the constrained axes
+        if let Some(x) = body.lock_x {
+            movement += (x - body.position.x).abs();
+            body.position.x = x;
+        }
+        if let Some(y) = body.lock_y {
+            movement += (y - body.position.y).abs();
+            body.position.y = y;
+        }
+    }
+
+    movement
+}
+
+/// Move bodies sharing the same position apart by a small random offset.
+///
+/// Otherwise, they would only be pushed apart along the same fixed direction.
+fn separate(bodies: &mut [Body], rng: &mut Rng) {
+    let mut order: Vec<usize> = (0..bodies.len()).collect();
+    order.sort_by(|a, b| {
+        let (a, b) = (bodies[*a].position, bodies[*b].position);
+        a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+    });
+
+    for pair in order.windows(2) {
+        let (a, b) = (bodies[pair[0]].position, bodies[pair[1]].position);
+        if a == b && !bodies[pair[1]].fixed {
+            let body = &mut bodies[pair[1]];
+            body.position.x += rng.range(-MIN_DISTANCE, MIN_DISTANCE);
+            body.position.y += rng.range(-MIN_DISTANCE, MIN_DISTANCE);
+        }
+    }
+}
+
+/// The center of all bodies.
+fn centroid(bodies: &[Body]) -> Option<Position> {
+    if bodies.is_empty() {
+        return None;
+    }
+    let (x, y) = bodies.iter().fold((0.0, 0.0), |(x, y), body| {
+        (x + body.position.x, y + body.position.y)
+    });
+    let n = bodies.len() as f64;
+    Some(Position { x: x / n, y: y / n })
+}
+
+/// Add forces separating overlapping bodies, along the axis of the smaller overlap.
+///
+/// Bodies are swept along the x axis, so only bodies overlapping horizontally are compared.
+fn collide(bodies: &[Body], forces: &mut [Position], collision: Collision) {
+    let padding = collision.padding;
+    let left = |i: usize| bodies[i].position.x - bodies[i].size.width / 2.0;
+
+    let mut order: Vec<usize> = (0..bodies.len()).collect();
+    order.sort_by(|a, b| left(*a).total_cmp(&left(*b)));
+
+    for (n, &i) in order.iter().enumerate() {
+        let a = &bodies[i];
+        let right = a.position.x + a.size.width / 2.0 + padding;
+
+        for &j in &order[n + 1..] {
+            if left(j) >= right {
+                break;
+            }
+            let b = &bodies[j];
+
+            let dx = b.position.x - a.position.x;
+            let dy = b.position.y - a.position.y;
+            let overlap_x = (a.size.width + b.size.width) / 2.0 + padding - dx.abs();
+            let overlap_y = (a.size.height + b.size.height) / 2.0 + padding - dy.abs();
+            if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                continue;
+            }
+
+            // each body takes half of the push
+            let (fx, fy) = if overlap_x < overlap_y {
+                let sign = if dx < 0.0 { -1.0 } else { 1.0 };
+                (sign * overlap_x * collision.strength / 2.0, 0.0)
+            } else {
+                let sign = if dy < 0.0 { -1.0 } else { 1.0 };
+                (0.0, sign * overlap_y * collision.strength / 2.0)
+            };
+
+            forces[i].x -= fx;
+            forces[i].y -= fy;
+            forces[j].x += fx;
+            forces[j].y += fy;
+        }
+    }
+}
+
+/// The vector from `from` to `to`, and its length.
+fn delta(from: Position, to: Position) -> (f64, f64, f64) {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance < MIN_DISTANCE {
+        // nudge overlapping bodies apart in a deterministic direction
+        (MIN_DISTANCE, 0.0, MIN_DISTANCE)
+    } else {
+        (dx, dy, distance)
+    }
+}
@@ -0,0 +1,176 @@
+//! A quadtree for the Barnes–Hut approximation of the repulsion between all bodies.
+//!
+//! Each cell of the tree stores the total mass and the center of mass of the bodies it contains.
+//! Cells which are far enough away are treated as a single body, reducing the cost of computing
+//! the repulsion from O(n²) to O(n log n).
+
+use crate::graph::Position;
+
+/// Cells smaller than this aren't divided any further, so that (almost) coincident bodies don't
+/// cause unbounded recursion.
+const MIN_CELL_SIZE: f64 = 1e-3;
+
+pub(crate) struct QuadTree {
+    cells: Vec<Cell>,
+}
+
+struct Cell {
+    /// Top left corner.
+    x: f64,
+    y: f64,
+    size: f64,
+
+    mass: f64,
+    /// Sum of the positions, weighted by mass. Divided by the mass, this is the center of mass.
+    weighted_x: f64,
+    weighted_y: f64,
+
+    /// Index of the first of four children, if divided.
+    children: Option<usize>,
+    /// The body contained in an undivided cell.
+    body: Option<usize>,
+}
+
+impl Cell {
+    fn new(x: f64, y: f64, size: f64) -> Self {
+        Self {
+            x,
+            y,
+            size,
+            mass: 0.0,
+            weighted_x: 0.0,
+            weighted_y: 0.0,
+            children: None,
+            body: None,
+        }
+    }
+
+    fn quadrant(&self, position: Position) -> usize {
+        let half = self.size / 2.0;
+        let right = position.x >= self.x + half;
+        let bottom = position.y >= self.y + half;
+        (right as usize) + (bottom as usize) * 2
+    }
+}
+
+impl QuadTree {
+    pub fn new(positions: &[Position]) -> Self {
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in positions {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+
+        let size = (max_x - min_x).max(max_y - min_y).max(MIN_CELL_SIZE) * 1.01;
+
+        let mut tree = Self {
+            cells: vec![Cell::new(min_x, min_y, size)],
+        };
+
+        for (i, position) in positions.iter().enumerate() {
+            tree.insert(0, i, *position, positions);
+        }
+
+        tree
+    }
+
+    fn insert(&mut self, cell: usize, body: usize, position: Position, positions: &[Position]) {
+        let mut cell = cell;
+
+        loop {
+            {
+                let c = &mut self.cells[cell];
+                c.mass += 1.0;
+                c.weighted_x += position.x;
+                c.weighted_y += position.y;
+            }
+
+            if let Some(children) = self.cells[cell].children {
+                cell = children + self.cells[cell].quadrant(position);
+                continue;
+            }
+
+            let existing = match self.cells[cell].body {
+                None if self.cells[cell].mass <= 1.0 => {
+                    self.cells[cell].body = Some(body);
+                    return;
+                }
+                // a cell which is too small to divide collects all bodies as mass only
+                _ if self.cells[cell].size < MIN_CELL_SIZE => return,
+                existing => existing,
+            };
+
+            // divide the cell and push the existing body down
+
+            let children = self.divide(cell);
+            self.cells[cell].body = None;
+
+            if let Some(existing) = existing {
+                let existing_position = positions[existing];
+                let child = children + self.cells[cell].quadrant(existing_position);
+                let c = &mut self.cells[child];
+                c.mass += 1.0;
+                c.weighted_x += existing_position.x;
+                c.weighted_y += existing_position.y;
+                c.body = Some(existing);
+            }
+
+            cell = children + self.cells[cell].quadrant(position);
+        }
+    }
+
+    fn divide(&mut self, cell: usize) -> usize {
+        let Cell { x, y, size, .. } = self.cells[cell];
+        let half = size / 2.0;
+
+        let first = self.cells.len();
+        self.cells.extend([
+            Cell::new(x, y, half),
+            Cell::new(x + half, y, half),
+            Cell::new(x, y + half, half),
+            Cell::new(x + half, y + half, half),
+        ]);
+        self.cells[cell].children = Some(first);
+
+        first
+    }
+
+    /// The repulsion force acting on a body.
+    ///
+    /// `theta` controls the accuracy: cells with `size / distance < theta` are approximated by
+    /// their center of mass.
+    pub fn force(&self, body: usize, position: Position, strength: f64, theta: f64) -> Position {
+        let mut force = Position { x: 0.0, y: 0.0 };
+        let mut stack = vec![0];
+
+        while let Some(cell) = stack.pop() {
+            let c = &self.cells[cell];
+            if c.mass == 0.0 || c.body == Some(body) {
+                continue;
+            }
+
+            let center = Position {
+                x: c.weighted_x / c.mass,
+                y: c.weighted_y / c.mass,
+            };
+            let (dx, dy, distance) = super::delta(center, position);
+
+            match c.children {
+                Some(children) if c.size / distance >= theta => {
+                    stack.extend(children..children + 4);
+                }
+                _ => {
+                    // a leaf, or far enough away to be treated as a single body
+                    let f = strength * c.mass / (distance * distance);
+                    force.x += dx / distance * f;
+                    force.y += dy / distance * f;
+                }
+            }
+        }
+
+        force
+    }
+}
@@ -0,0 +1,91 @@
+use super::{recenter, Arrangement};
+use crate::graph::{Node, Position, Size};
+use crate::storage::GraphStorage;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// The order in which nodes fill the grid, row by row.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GridOrder {
+    /// In the order they were added.
+    #[default]
+    Added,
+    /// Alphabetically, by label.
+    Label,
+    /// The most connected nodes first.
+    Degree,
+}
+
+/// Places nodes in the cells of a grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridLayout {
+    /// The number of columns, by default the grid is roughly square.
+    pub columns: Option<usize>,
+    /// The size of a cell, by default fitting the largest node.
+    pub cell_size: Option<Size>,
+    /// Space between two cells.
+    pub spacing: f64,
+    pub order: GridOrder,
+}
+
+impl Default for GridLayout {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            cell_size: None,
+            spacing: 20.0,
+            order: GridOrder::Added,
+        }
+    }
+}
+
+impl Arrangement for GridLayout {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let mut nodes: Vec<Node> = graph.nodes().collect();
+        nodes.sort();
+        match self.order {
+            GridOrder::Added => {}
+            GridOrder::Label => nodes.sort_by_cached_key(|n| {
+                graph
+                    .properties(*n)
+                    .map(|p| p.label.clone())
+                    .unwrap_or_default()
+            }),
+            GridOrder::Degree => nodes.sort_by_cached_key(|n| Reverse(graph.neighbors(*n).count())),
+        }
+
+        let columns = self
+            .columns
+            .unwrap_or_else(|| (nodes.len() as f64).sqrt().ceil() as usize)
+            .max(1);
+
+        let cell = self.cell_size.unwrap_or_else(|| {
+            nodes.iter().filter_map(|n| graph.size(*n)).fold(
+                Size::from((0.0, 0.0)),
+                |cell, size| Size {
+                    width: cell.width.max(size.width),
+                    height: cell.height.max(size.height),
+                },
+            )
+        });
+
+        let mut result: HashMap<Node, Position> = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let (row, column) = (i / columns, i % columns);
+                let position = Position {
+                    x: column as f64 * (cell.width + self.spacing),
+                    y: row as f64 * (cell.height + self.spacing),
+                };
+                (node, position)
+            })
+            .collect();
+
+        if let Some(center) = graph.centroid() {
+            recenter(&mut result, center);
+        }
+
+        result
+    }
+}
@@ -0,0 +1,352 @@
+use super::{recenter, Arrangement};
+use crate::graph::{Node, Position};
+use crate::storage::GraphStorage;
+use std::collections::HashMap;
+
+/// The direction in which layers are stacked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Sources at the top, layers going down.
+    #[default]
+    TopDown,
+    /// Sources on the left, layers going right.
+    LeftRight,
+}
+
+/// A layered (Sugiyama-style) arrangement for directed, mostly acyclic graphs.
+///
+/// Nodes are assigned to layers so that edges point from one layer to the next, nodes within a
+/// layer are ordered to reduce edge crossings, and finally nodes are positioned close to their
+/// neighbors.
+///
+/// Edges are followed in the direction they were created. Cycles are broken by ignoring the
+/// edges closing them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayeredLayout {
+    pub direction: Direction,
+    /// Space between two layers.
+    pub layer_spacing: f64,
+    /// Space between two nodes of the same layer.
+    pub node_spacing: f64,
+    /// Number of passes for reducing crossings.
+    pub sweeps: usize,
+}
+
+impl Default for LayeredLayout {
+    fn default() -> Self {
+        Self {
+            direction: Direction::TopDown,
+            layer_spacing: 80.0,
+            node_spacing: 30.0,
+            sweeps: 12,
+        }
+    }
+}
+
+/// Number of passes positioning nodes within their layer.
+const COORDINATE_PASSES: usize = 8;
+
+impl Arrangement for LayeredLayout {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let mut nodes: Vec<Node> = graph.nodes().collect();
+        nodes.sort();
+        let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        let mut out = vec![Vec::new(); nodes.len()];
+        for (source, target, _) in graph.directed_edges() {
+            if let (Some(&source), Some(&target)) = (index.get(&source), index.get(&target)) {
+                out[source].push(target);
+            }
+        }
+        for targets in &mut out {
+            targets.sort();
+        }
+
+        let edges = acyclic_edges(&out);
+        let rank = longest_path_ranks(nodes.len(), &edges);
+
+        // split edges spanning multiple layers using dummy vertices
+
+        let mut vertex_rank = rank.clone();
+        let mut up = vec![Vec::new(); nodes.len()];
+        let mut down = vec![Vec::new(); nodes.len()];
+        for (from, to) in edges {
+            let mut prev = from;
+            for r in (rank[from] + 1)..rank[to] {
+                let dummy = vertex_rank.len();
+                vertex_rank.push(r);
+                up.push(vec![prev]);
+                down.push(vec![]);
+                down[prev].push(dummy);
+                prev = dummy;
+            }
+            down[prev].push(to);
+            up[to].push(prev);
+        }
+
+        let num_layers = vertex_rank.iter().max().map_or(0, |r| r + 1);
+        let mut layers = vec![Vec::new(); num_layers];
+        for (v, r) in vertex_rank.iter().enumerate() {
+            layers[*r].push(v);
+        }
+
+        let layers = self.order(layers, &up, &down);
+
+        // sizes: along the layer, and across it
+
+        let sizes: Vec<(f64, f64)> = (0..vertex_rank.len())
+            .map(|v| match nodes.get(v).and_then(|n| graph.size(*n)) {
+                Some(size) => match self.direction {
+                    Direction::TopDown => (size.width, size.height),
+                    Direction::LeftRight => (size.height, size.width),
+                },
+                None => (0.0, 0.0),
+            })
+            .collect();
+
+        let along = self.coordinates(&layers, &up, &down, &sizes);
+
+        // stack the layers
+
+        let mut result = HashMap::with_capacity(nodes.len());
+        let mut offset = 0.0;
+        for layer in &layers {
+            let thickness = layer.iter().map(|v| sizes[*v].1).fold(0.0, f64::max);
+            let across = offset + thickness / 2.0;
+            for v in layer {
+                if let Some(node) = nodes.get(*v) {
+                    let position = match self.direction {
+                        Direction::TopDown => Position {
+                            x: along[*v],
+                            y: across,
+                        },
+                        Direction::LeftRight => Position {
+                            x: across,
+                            y: along[*v],
+                        },
+                    };
+                    result.insert(*node, position);
+                }
+            }
+            offset += thickness + self.layer_spacing;
+        }
+
+        if let Some(center) = graph.centroid() {
+            recenter(&mut result, center);
+        }
+
+        result
+    }
+}
+
+impl LayeredLayout {
+    /// Order vertices within their layers, using the barycenter heuristic.
+    fn order(
+        &self,
+        mut layers: Vec<Vec<usize>>,
+        up: &[Vec<usize>],
+        down: &[Vec<usize>],
+    ) -> Vec<Vec<usize>> {
+        let mut position = vec![0.0; up.len()];
+        let update = |layers: &Vec<Vec<usize>>, position: &mut Vec<f64>| {
+            for layer in layers {
+                for (i, v) in layer.iter().enumerate() {
+                    position[*v] = i as f64;
+                }
+            }
+        };
+        update(&layers, &mut position);
+
+        let mut best = layers.clone();
+        let mut best_crossings = crossings(&layers, down, &position);
+
+        for sweep in 0..self.sweeps {
+            let downwards = sweep % 2 == 0;
+            let ranks: Vec<usize> = match downwards {
+                true => (1..layers.len()).collect(),
+                false => (0..layers.len().saturating_sub(1)).rev().collect(),
+            };
+
+            for r in ranks {
+                let neighbors = if downwards { up } else { down };
+                let mut keyed: Vec<(f64, usize)> = layers[r]
+                    .iter()
+                    .map(|v| {
+                        let n = &neighbors[*v];
+                        let key = match n.is_empty() {
+                            true => position[*v],
+                            false => n.iter().map(|u| position[*u]).sum::<f64>() / n.len() as f64,
+                        };
+                        (key, *v)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+                layers[r] = keyed.into_iter().map(|(_, v)| v).collect();
+                for (i, v) in layers[r].iter().enumerate() {
+                    position[*v] = i as f64;
+                }
+            }
+
+            let c = crossings(&layers, down, &position);
+            if c < best_crossings {
+                best_crossings = c;
+                best = layers.clone();
+            }
+        }
+
+        best
+    }
+
+    /// Position vertices within their layer, close to their neighbors.
+    fn coordinates(
+        &self,
+        layers: &[Vec<usize>],
+        up: &[Vec<usize>],
+        down: &[Vec<usize>],
+        sizes: &[(f64, f64)],
+    ) -> Vec<f64> {
+        let mut x = vec![0.0; up.len()];
+
+        // start packed, centered on zero
+
+        for layer in layers {
+            let mut offset = 0.0;
+            for v in layer {
+                x[*v] = offset + sizes[*v].0 / 2.0;
+                offset += sizes[*v].0 + self.node_spacing;
+            }
+            let shift = (offset - self.node_spacing) / 2.0;
+            for v in layer {
+                x[*v] -= shift;
+            }
+        }
+
+        // pull towards the neighbors, keeping the order and spacing
+
+        for _ in 0..COORDINATE_PASSES {
+            for layer in layers {
+                let desired: Vec<f64> = layer
+                    .iter()
+                    .map(|v| {
+                        let neighbors = up[*v].iter().chain(down[*v].iter());
+                        let (sum, count) = neighbors.fold((0.0, 0), |(s, c), u| (s + x[*u], c + 1));
+                        match count {
+                            0 => x[*v],
+                            _ => sum / count as f64,
+                        }
+                    })
+                    .collect();
+
+                let mut prev: Option<usize> = None;
+                for (v, d) in layer.iter().zip(&desired) {
+                    x[*v] = match prev {
+                        Some(p) => {
+                            d.max(x[p] + (sizes[p].0 + sizes[*v].0) / 2.0 + self.node_spacing)
+                        }
+                        None => *d,
+                    };
+                    prev = Some(*v);
+                }
+
+                // the left to right pass only pushes right, compensate by shifting back
+                let drift = layer
+                    .iter()
+                    .zip(&desired)
+                    .map(|(v, d)| x[*v] - d)
+                    .sum::<f64>()
+                    / layer.len().max(1) as f64;
+                for v in layer {
+                    x[*v] -= drift;
+                }
+            }
+        }
+
+        x
+    }
+}
+
+/// Drop edges which would close a cycle, using a depth first search.
+fn acyclic_edges(out: &[Vec<usize>]) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Active,
+        Done,
+    }
+
+    let mut state = vec![State::New; out.len()];
+    let mut edges = Vec::new();
+
+    for start in 0..out.len() {
+        if state[start] != State::New {
+            continue;
+        }
+
+        // (vertex, index of the next outgoing edge)
+        let mut stack = vec![(start, 0)];
+        state[start] = State::Active;
+
+        while let Some((v, i)) = stack.pop() {
+            if let Some(&w) = out[v].get(i) {
+                stack.push((v, i + 1));
+                match state[w] {
+                    State::New => {
+                        edges.push((v, w));
+                        state[w] = State::Active;
+                        stack.push((w, 0));
+                    }
+                    State::Done => edges.push((v, w)),
+                    // a back edge, closing a cycle
+                    State::Active => {}
+                }
+            } else {
+                state[v] = State::Done;
+            }
+        }
+    }
+
+    edges
+}
+
+/// Assign each vertex the length of the longest path reaching it.
+fn longest_path_ranks(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0usize; n];
+    let mut out = vec![Vec::new(); n];
+    for (from, to) in edges {
+        incoming[*to] += 1;
+        out[*from].push(*to);
+    }
+
+    let mut rank = vec![0; n];
+    let mut queue: Vec<usize> = (0..n).filter(|v| incoming[*v] == 0).collect();
+    while let Some(v) = queue.pop() {
+        for w in &out[v] {
+            rank[*w] = rank[*w].max(rank[v] + 1);
+            incoming[*w] -= 1;
+            if incoming[*w] == 0 {
+                queue.push(*w);
+            }
+        }
+    }
+
+    rank
+}
+
+/// Count the edge crossings between all adjacent layers.
+fn crossings(layers: &[Vec<usize>], down: &[Vec<usize>], position: &[f64]) -> usize {
+    let mut count = 0;
+    for layer in layers {
+        let edges: Vec<(f64, f64)> = layer
+            .iter()
+            .flat_map(|v| down[*v].iter().map(|w| (position[*v], position[*w])))
+            .collect();
+        for (i, a) in edges.iter().enumerate() {
+            for b in &edges[i + 1..] {
+                if (a.0 - b.0) * (a.1 - b.1) < 0.0 {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
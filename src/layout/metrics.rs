@@ -0,0 +1,96 @@
+use crate::graph::{Node, Position, Rect};
+use crate::storage::GraphStorage;
+
+/// Quality measures of the current layout, for comparing layouts and tuning parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LayoutMetrics {
+    /// Mean length of all edges, between node centers.
+    pub edge_length_mean: f64,
+    /// Variance of the edge lengths, lower means more uniform.
+    pub edge_length_variance: f64,
+    /// Number of pairs of edges crossing each other.
+    pub edge_crossings: usize,
+    /// Number of pairs of nodes overlapping each other.
+    pub node_overlaps: usize,
+    /// Width divided by height of the bounding box of all nodes.
+    pub aspect_ratio: f64,
+}
+
+impl LayoutMetrics {
+    /// Measure the layout of the graph.
+    ///
+    /// Counting edge crossings compares all pairs of edges, so this gets expensive for large
+    /// graphs.
+    pub fn measure(graph: &GraphStorage) -> Self {
+        let edges: Vec<(Node, Node, Position, Position)> = graph
+            .edges()
+            .filter_map(|(a, b, _)| Some((a, b, graph.center(a)?, graph.center(b)?)))
+            .collect();
+
+        let lengths: Vec<f64> = edges.iter().map(|(_, _, a, b)| a.delta(*b)).collect();
+        let n = lengths.len().max(1) as f64;
+        let edge_length_mean = lengths.iter().sum::<f64>() / n;
+        let edge_length_variance = lengths
+            .iter()
+            .map(|l| (l - edge_length_mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        let mut edge_crossings = 0;
+        for (i, (a1, b1, p1, q1)) in edges.iter().enumerate() {
+            for (a2, b2, p2, q2) in &edges[i + 1..] {
+                // edges sharing a node only touch
+                if a1 == a2 || a1 == b2 || b1 == a2 || b1 == b2 {
+                    continue;
+                }
+                if segments_cross(*p1, *q1, *p2, *q2) {
+                    edge_crossings += 1;
+                }
+            }
+        }
+
+        let mut rects: Vec<Rect> = graph.nodes().filter_map(|n| graph.rect(n)).collect();
+        rects.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+
+        let mut node_overlaps = 0;
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                if b.position.x >= a.right() {
+                    break;
+                }
+                // touching isn't overlapping
+                let overlaps_y = a.position.y < b.bottom() && b.position.y < a.bottom();
+                if overlaps_y {
+                    node_overlaps += 1;
+                }
+            }
+        }
+
+        let aspect_ratio = graph
+            .bounds()
+            .filter(|bounds| bounds.size.height > 0.0)
+            .map_or(0.0, |bounds| bounds.size.width / bounds.size.height);
+
+        Self {
+            edge_length_mean,
+            edge_length_variance,
+            edge_crossings,
+            node_overlaps,
+            aspect_ratio,
+        }
+    }
+}
+
+/// If the segments `p1-q1` and `p2-q2` properly cross each other.
+fn segments_cross(p1: Position, q1: Position, p2: Position, q2: Position) -> bool {
+    fn orientation(a: Position, b: Position, c: Position) -> f64 {
+        (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+    }
+
+    let d1 = orientation(p2, q2, p1);
+    let d2 = orientation(p2, q2, q1);
+    let d3 = orientation(p1, q1, p2);
+    let d4 = orientation(p1, q1, q2);
+
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
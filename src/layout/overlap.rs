@@ -0,0 +1,103 @@
+use super::Arrangement;
+use crate::graph::{Node, Position, Size};
+use crate::storage::GraphStorage;
+use std::collections::HashMap;
+
+/// Pushes overlapping nodes apart, keeping their relative positions.
+///
+/// This can be run after any layout, using [`crate::graph::Graph::remove_overlaps`]. Each pass
+/// sweeps over the nodes sorted by their left edge, so that only horizontally overlapping nodes
+/// are compared. Overlapping pairs are moved apart along the axis needing the smaller move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OverlapRemoval {
+    /// Space to keep between two nodes.
+    pub padding: f64,
+    /// Maximum number of passes, in case overlaps can't be resolved.
+    pub max_passes: usize,
+}
+
+impl Default for OverlapRemoval {
+    fn default() -> Self {
+        Self {
+            padding: 10.0,
+            max_passes: 50,
+        }
+    }
+}
+
+impl Arrangement for OverlapRemoval {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let mut nodes: Vec<Node> = graph.nodes().collect();
+        nodes.sort();
+
+        let mut boxes: Vec<(Position, Size)> = nodes
+            .iter()
+            .filter_map(|n| Some((graph.center(*n)?, graph.size(*n)?)))
+            .collect();
+
+        for _ in 0..self.max_passes {
+            if !self.pass(&mut boxes) {
+                break;
+            }
+        }
+
+        nodes
+            .into_iter()
+            .zip(boxes)
+            .map(|(node, (center, _))| (node, center))
+            .collect()
+    }
+}
+
+impl OverlapRemoval {
+    /// Run a single pass, returns `true` if any overlap was found.
+    fn pass(&self, boxes: &mut [(Position, Size)]) -> bool {
+        let padding = self.padding;
+        let left = |(center, size): &(Position, Size)| center.x - size.width / 2.0;
+
+        let mut order: Vec<usize> = (0..boxes.len()).collect();
+        order.sort_by(|a, b| left(&boxes[*a]).total_cmp(&left(&boxes[*b])));
+
+        let mut moves = vec![Position { x: 0.0, y: 0.0 }; boxes.len()];
+        let mut found = false;
+
+        for (n, &i) in order.iter().enumerate() {
+            let (a, a_size) = boxes[i];
+            let right = a.x + a_size.width / 2.0 + padding;
+
+            for &j in &order[n + 1..] {
+                if left(&boxes[j]) >= right {
+                    break;
+                }
+                let (b, b_size) = boxes[j];
+
+                let dx = b.x - a.x;
+                let dy = b.y - a.y;
+                let overlap_x = (a_size.width + b_size.width) / 2.0 + padding - dx.abs();
+                let overlap_y = (a_size.height + b_size.height) / 2.0 + padding - dy.abs();
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+                found = true;
+
+                // each node moves half of the way, keeping their order on that axis
+                if overlap_x < overlap_y {
+                    let shift = overlap_x / 2.0 * if dx < 0.0 { -1.0 } else { 1.0 };
+                    moves[i].x -= shift;
+                    moves[j].x += shift;
+                } else {
+                    let shift = overlap_y / 2.0 * if dy < 0.0 { -1.0 } else { 1.0 };
+                    moves[i].y -= shift;
+                    moves[j].y += shift;
+                }
+            }
+        }
+
+        for ((center, _), delta) in boxes.iter_mut().zip(moves) {
+            center.x += delta.x;
+            center.y += delta.y;
+        }
+
+        found
+    }
+}
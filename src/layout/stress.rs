@@ -0,0 +1,178 @@
+use super::{recenter, Arrangement, Rng};
+use crate::graph::{Node, Position};
+use crate::storage::GraphStorage;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Positions nodes so that their distances approximate their distances in the graph.
+///
+/// This minimizes the stress of the layout using stress majorization, starting from the
+/// current positions. Compared to a force-directed layout, it preserves the global structure
+/// of the graph much better, but computes all pairs of distances, which limits it to graphs of
+/// up to a few thousand nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressLayout {
+    /// The length of edges without an explicit length.
+    pub edge_length: f64,
+    /// Maximum number of iterations.
+    pub iterations: usize,
+    /// The relative improvement of the stress, below which the iteration stops.
+    pub epsilon: f64,
+    /// Seed for separating nodes which share a position.
+    pub seed: u64,
+}
+
+impl Default for StressLayout {
+    fn default() -> Self {
+        Self {
+            edge_length: 100.0,
+            iterations: 300,
+            epsilon: 1e-4,
+            seed: 0,
+        }
+    }
+}
+
+impl Arrangement for StressLayout {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let mut nodes: Vec<Node> = graph.nodes().collect();
+        nodes.sort();
+        let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let n = nodes.len();
+
+        let mut adjacent = vec![Vec::new(); n];
+        for (a, b, edge) in graph.edges() {
+            let (Some(&a), Some(&b)) = (index.get(&a), index.get(&b)) else {
+                continue;
+            };
+            let length = edge.length.unwrap_or(self.edge_length);
+            adjacent[a].push((b, length));
+            adjacent[b].push((a, length));
+        }
+
+        let mut distances: Vec<Vec<f64>> = (0..n).map(|i| shortest_paths(&adjacent, i)).collect();
+
+        // keep disconnected parts apart, but don't care much about how far
+        let max = distances
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|d| d.is_finite())
+            .fold(0.0, f64::max);
+        for d in distances.iter_mut().flatten() {
+            if !d.is_finite() {
+                *d = max + self.edge_length;
+            }
+        }
+
+        let mut rng = Rng::new(self.seed);
+        let mut positions: Vec<Position> = nodes
+            .iter()
+            .map(|node| {
+                let center = graph.center(*node).unwrap_or(Position { x: 0.0, y: 0.0 });
+                // separate coincident nodes, the gradient is undefined otherwise
+                Position {
+                    x: center.x + rng.range(-1.0, 1.0),
+                    y: center.y + rng.range(-1.0, 1.0),
+                }
+            })
+            .collect();
+
+        let mut last = stress(&positions, &distances);
+        for _ in 0..self.iterations {
+            for i in 0..n {
+                let (mut x, mut y, mut total) = (0.0, 0.0, 0.0);
+                for j in 0..n {
+                    if i == j {
+                        continue;
+                    }
+                    let d = distances[i][j];
+                    if d <= 0.0 {
+                        continue;
+                    }
+                    let w = 1.0 / (d * d);
+                    let (pi, pj) = (positions[i], positions[j]);
+                    let dist = pi.delta(pj).max(f64::EPSILON);
+
+                    x += w * (pj.x + d * (pi.x - pj.x) / dist);
+                    y += w * (pj.y + d * (pi.y - pj.y) / dist);
+                    total += w;
+                }
+                if total > 0.0 {
+                    positions[i] = Position {
+                        x: x / total,
+                        y: y / total,
+                    };
+                }
+            }
+
+            let current = stress(&positions, &distances);
+            if last <= 0.0 || (last - current) / last < self.epsilon {
+                break;
+            }
+            last = current;
+        }
+
+        let mut result: HashMap<Node, Position> = nodes.into_iter().zip(positions).collect();
+        if let Some(center) = graph.centroid() {
+            recenter(&mut result, center);
+        }
+
+        result
+    }
+}
+
+/// The weighted stress of the layout.
+fn stress(positions: &[Position], distances: &[Vec<f64>]) -> f64 {
+    let mut stress = 0.0;
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            let d = distances[i][j];
+            if d > 0.0 {
+                let diff = positions[i].delta(positions[j]) - d;
+                stress += diff * diff / (d * d);
+            }
+        }
+    }
+    stress
+}
+
+/// Distances from `start` to all vertices, using Dijkstra's algorithm.
+fn shortest_paths(adjacent: &[Vec<(usize, f64)>], start: usize) -> Vec<f64> {
+    #[derive(PartialEq)]
+    struct Entry(f64, usize);
+
+    impl Eq for Entry {}
+
+    impl PartialOrd for Entry {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Entry {
+        fn cmp(&self, other: &Self) -> Ordering {
+            // reversed, for a min-heap
+            other.0.total_cmp(&self.0)
+        }
+    }
+
+    let mut distances = vec![f64::INFINITY; adjacent.len()];
+    distances[start] = 0.0;
+
+    let mut queue = BinaryHeap::from([Entry(0.0, start)]);
+    while let Some(Entry(distance, v)) = queue.pop() {
+        if distance > distances[v] {
+            continue;
+        }
+        for (w, length) in &adjacent[v] {
+            let next = distance + length;
+            if next < distances[*w] {
+                distances[*w] = next;
+                queue.push(Entry(next, *w));
+            }
+        }
+    }
+
+    distances
+}
@@ -0,0 +1,239 @@
+use super::Arrangement;
+use crate::graph::{Node, Position};
+use crate::storage::GraphStorage;
+use std::collections::{BTreeSet, HashMap};
+use std::f64::consts::TAU;
+
+/// A tidy tree, with the root at the top and each level below its parent.
+///
+/// The tree is a breadth first spanning tree of the nodes reachable from the root, so edges are
+/// treated as undirected. Parents are centered above their children. Nodes not reachable from
+/// the root keep their position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeLayout {
+    pub root: Node,
+    /// Space between two levels.
+    pub level_spacing: f64,
+    /// Space between two neighboring subtrees.
+    pub sibling_spacing: f64,
+}
+
+impl TreeLayout {
+    pub fn new(root: Node) -> Self {
+        Self {
+            root,
+            level_spacing: 60.0,
+            sibling_spacing: 20.0,
+        }
+    }
+}
+
+impl Arrangement for TreeLayout {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let Some(tree) = Tree::new(graph, self.root) else {
+            return HashMap::new();
+        };
+
+        // the height of each level, and the horizontal offset of each subtree
+
+        let mut heights = vec![0.0f64; tree.depth() + 1];
+        for (node, depth) in &tree.depth {
+            let height = graph.size(*node).map_or(0.0, |s| s.height);
+            heights[*depth] = heights[*depth].max(height);
+        }
+
+        let mut x = HashMap::new();
+        self.place(graph, &tree, self.root, 0.0, &mut x);
+
+        let mut result = HashMap::with_capacity(x.len());
+        for (node, x) in x {
+            let depth = tree.depth[&node];
+            let above: f64 = heights[..depth].iter().sum();
+            let y = above + depth as f64 * self.level_spacing + heights[depth] / 2.0;
+            result.insert(node, Position { x, y });
+        }
+
+        keep_root(&mut result, graph, self.root);
+
+        result
+    }
+}
+
+impl TreeLayout {
+    /// Place the subtree at `left`, returning its width.
+    fn place(
+        &self,
+        graph: &GraphStorage,
+        tree: &Tree,
+        node: Node,
+        left: f64,
+        x: &mut HashMap<Node, f64>,
+    ) -> f64 {
+        let own = graph.size(node).map_or(0.0, |s| s.width);
+
+        let mut width = 0.0;
+        let mut first_last = None;
+        for child in tree.children(node) {
+            if width > 0.0 {
+                width += self.sibling_spacing;
+            }
+            width += self.place(graph, tree, *child, left + width, x);
+            let cx = x[child];
+            first_last = Some((first_last.map_or(cx, |(first, _)| first), cx));
+        }
+
+        match first_last {
+            Some((first, last)) => {
+                let center = (first + last) / 2.0;
+                if own > width {
+                    // the parent is wider than its children, shift them to the middle
+                    let shift = (own - width) / 2.0;
+                    shift_subtree(tree, node, shift, x);
+                    x.insert(node, center + shift);
+                    own
+                } else {
+                    x.insert(node, center);
+                    width
+                }
+            }
+            None => {
+                x.insert(node, left + own / 2.0);
+                own
+            }
+        }
+    }
+}
+
+/// A radial tree, with the root in the center and each level on a ring around it.
+///
+/// Each subtree gets a slice of its ring, proportional to its number of leaves. Like with
+/// [`TreeLayout`], nodes not reachable from the root keep their position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RadialTreeLayout {
+    pub root: Node,
+    /// Distance between two rings.
+    pub level_spacing: f64,
+}
+
+impl RadialTreeLayout {
+    pub fn new(root: Node) -> Self {
+        Self {
+            root,
+            level_spacing: 120.0,
+        }
+    }
+}
+
+impl Arrangement for RadialTreeLayout {
+    fn arrange(&self, graph: &GraphStorage) -> HashMap<Node, Position> {
+        let Some(tree) = Tree::new(graph, self.root) else {
+            return HashMap::new();
+        };
+
+        let mut leaves = HashMap::new();
+        count_leaves(&tree, self.root, &mut leaves);
+
+        let mut result = HashMap::new();
+        let mut pending = vec![(self.root, 0.0, TAU)];
+        while let Some((node, start, end)) = pending.pop() {
+            let radius = tree.depth[&node] as f64 * self.level_spacing;
+            let angle = (start + end) / 2.0;
+            result.insert(
+                node,
+                Position {
+                    x: radius * angle.cos(),
+                    y: radius * angle.sin(),
+                },
+            );
+
+            let total = leaves[&node] as f64;
+            let mut from = start;
+            for child in tree.children(node) {
+                let to = from + (end - start) * leaves[child] as f64 / total;
+                pending.push((*child, from, to));
+                from = to;
+            }
+        }
+
+        keep_root(&mut result, graph, self.root);
+
+        result
+    }
+}
+
+/// Move all positions, so that the root stays where it currently is.
+fn keep_root(positions: &mut HashMap<Node, Position>, graph: &GraphStorage, root: Node) {
+    let (Some(current), Some(new)) = (graph.center(root), positions.get(&root).copied()) else {
+        return;
+    };
+    for p in positions.values_mut() {
+        p.x += current.x - new.x;
+        p.y += current.y - new.y;
+    }
+}
+
+fn count_leaves(tree: &Tree, node: Node, leaves: &mut HashMap<Node, usize>) -> usize {
+    let count = match tree.children(node) {
+        [] => 1,
+        children => children
+            .iter()
+            .map(|c| count_leaves(tree, *c, leaves))
+            .sum(),
+    };
+    leaves.insert(node, count);
+    count
+}
+
+fn shift_subtree(tree: &Tree, node: Node, shift: f64, x: &mut HashMap<Node, f64>) {
+    for child in tree.children(node) {
+        if let Some(cx) = x.get_mut(child) {
+            *cx += shift;
+        }
+        shift_subtree(tree, *child, shift, x);
+    }
+}
+
+/// A breadth first spanning tree.
+struct Tree {
+    children: HashMap<Node, Vec<Node>>,
+    depth: HashMap<Node, usize>,
+}
+
+impl Tree {
+    fn new(graph: &GraphStorage, root: Node) -> Option<Self> {
+        if !graph.contains(root) {
+            return None;
+        }
+
+        let mut children = HashMap::<Node, Vec<Node>>::new();
+        let mut depth = HashMap::from([(root, 0)]);
+        let mut visited = BTreeSet::from([root]);
+        let mut level = vec![root];
+
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            for parent in level {
+                let mut neighbors: Vec<Node> = graph.neighbors(parent).collect();
+                neighbors.sort();
+                for child in neighbors {
+                    if visited.insert(child) {
+                        depth.insert(child, depth[&parent] + 1);
+                        children.entry(parent).or_default().push(child);
+                        next.push(child);
+                    }
+                }
+            }
+            level = next;
+        }
+
+        Some(Self { children, depth })
+    }
+
+    fn children(&self, node: Node) -> &[Node] {
+        self.children.get(&node).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn depth(&self) -> usize {
+        self.depth.values().copied().max().unwrap_or_default()
+    }
+}
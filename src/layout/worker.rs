@@ -0,0 +1,350 @@
+//! Running the force-directed simulation inside a web worker.
+//!
+//! Each frame, the main thread ships a snapshot of the graph to the worker, which runs the
+//! simulation and sends the new positions back. While the worker is busy, the main thread keeps
+//! rendering, applying the positions once they arrive.
+//!
+//! The worker needs to load the application's WASM module and call [`layout_worker_main`]. On
+//! the main thread, the graph is configured using a [`WorkerLayout`]:
+//!
+//! ```ignore
+//! let worker = web_sys::Worker::new("./layout-worker.js")?;
+//! graph.set_layout(WorkerLayout::new(worker));
+//! ```
+
+use super::force::{focus, snapshot, Body, Spring};
+use super::{ForceLayout, Layout, PhysicsConfig};
+use crate::graph::{Error, Node, Position, Size};
+use crate::storage::GraphStorage;
+use gloo_events::EventListener;
+use js_sys::{Array, Float64Array, Object, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent, Worker};
+
+const TYPE_STEP: &str = "layout-step";
+const TYPE_POSITIONS: &str = "layout-positions";
+
+/// Number of values per node sent to the worker.
+const NODE_STRIDE: usize = 8;
+/// Number of values per spring sent to the worker.
+const SPRING_STRIDE: usize = 4;
+/// Number of values per node sent back from the worker.
+const POSITION_STRIDE: usize = 3;
+
+/// Positions received from the worker, and if the simulation is stable.
+type Received = Rc<RefCell<Option<(Vec<f64>, bool)>>>;
+
+/// A [`ForceLayout`] running in a worker, which runs [`layout_worker_main`].
+pub struct WorkerLayout {
+    worker: Worker,
+    physics: PhysicsConfig,
+
+    /// If a snapshot was sent, and no positions were received yet
+    in_flight: bool,
+    /// If the next snapshot must reheat the simulation
+    reheat: bool,
+    stable: bool,
+
+    received: Received,
+    _listener: EventListener,
+}
+
+impl WorkerLayout {
+    pub fn new(worker: Worker) -> Self {
+        let received = Received::default();
+
+        let listener = {
+            let received = received.clone();
+            EventListener::new(&worker, "message", move |evt| {
+                let Some(evt) = evt.dyn_ref::<MessageEvent>() else {
+                    return;
+                };
+                match read_positions(&evt.data()) {
+                    Ok(Some(positions)) => *received.borrow_mut() = Some(positions),
+                    Ok(None) => {}
+                    Err(err) => log::warn!("Failed to read layout positions: {err}"),
+                }
+            })
+        };
+
+        Self {
+            worker,
+            physics: Default::default(),
+            in_flight: false,
+            reheat: true,
+            stable: false,
+            received,
+            _listener: listener,
+        }
+    }
+
+    fn send(&self, graph: &GraphStorage) -> Result<(), Error> {
+        let (nodes, bodies, springs) = snapshot(graph, &self.physics);
+
+        let mut values = Vec::with_capacity(nodes.len() * NODE_STRIDE);
+        for (node, body) in nodes.iter().zip(&bodies) {
+            values.extend([
+                node.id as f64,
+                body.position.x,
+                body.position.y,
+                body.size.width,
+                body.size.height,
+                if body.fixed { 1.0 } else { 0.0 },
+                body.lock_x.unwrap_or(f64::NAN),
+                body.lock_y.unwrap_or(f64::NAN),
+            ]);
+        }
+        let nodes = Float64Array::from(values.as_slice());
+
+        let mut values = Vec::with_capacity(springs.len() * SPRING_STRIDE);
+        for spring in &springs {
+            values.extend([
+                spring.a as f64,
+                spring.b as f64,
+                spring.length,
+                spring.strength,
+            ]);
+        }
+        let springs = Float64Array::from(values.as_slice());
+
+        let focus = focus(graph, &self.physics).map_or(JsValue::NULL, |focus| {
+            Array::of2(&focus.x.into(), &focus.y.into()).into()
+        });
+
+        let msg = Object::new();
+        for (key, value) in [
+            ("type", JsValue::from_str(TYPE_STEP)),
+            ("physics", physics_to_js(&self.physics)),
+            ("nodes", nodes.clone().into()),
+            ("springs", springs.clone().into()),
+            ("focus", focus),
+            ("reheat", self.reheat.into()),
+        ] {
+            Reflect::set(&msg, &key.into(), &value)?;
+        }
+
+        self.worker
+            .post_message_with_transfer(&msg, &Array::of2(&nodes.buffer(), &springs.buffer()))?;
+
+        Ok(())
+    }
+}
+
+impl Layout for WorkerLayout {
+    fn step(&mut self, graph: &mut GraphStorage, _dt: f64) -> bool {
+        let mut moved = false;
+
+        if let Some((positions, stable)) = self.received.borrow_mut().take() {
+            self.in_flight = false;
+            self.stable = stable && !self.reheat;
+
+            for chunk in positions.chunks_exact(POSITION_STRIDE) {
+                let node = Node {
+                    id: chunk[0] as usize,
+                };
+                // the node might have been grabbed in the meantime
+                if !graph.is_fixed(node) {
+                    graph.set_center(
+                        node,
+                        Position {
+                            x: chunk[1],
+                            y: chunk[2],
+                        },
+                    );
+                    moved = true;
+                }
+            }
+        }
+
+        if !self.in_flight && !self.stable {
+            match self.send(graph) {
+                Ok(()) => {
+                    self.in_flight = true;
+                    self.reheat = false;
+                }
+                Err(err) => log::warn!("Failed to send layout snapshot: {err}"),
+            }
+        }
+
+        moved
+    }
+
+    fn set_physics(&mut self, physics: &PhysicsConfig) {
+        self.physics = *physics;
+        self.reheat();
+    }
+
+    fn reheat(&mut self) {
+        self.reheat = true;
+        self.stable = false;
+    }
+
+    fn is_stable(&self) -> bool {
+        self.stable
+    }
+}
+
+/// Run the simulation side, must be called from inside the worker.
+///
+/// This registers a message handler for the lifetime of the worker.
+pub fn layout_worker_main() -> Result<(), Error> {
+    let scope = js_sys::global().dyn_into::<DedicatedWorkerGlobalScope>()?;
+
+    let layout = RefCell::new(ForceLayout::default());
+
+    EventListener::new(&scope.clone(), "message", move |evt| {
+        if let Some(evt) = evt.dyn_ref::<MessageEvent>() {
+            if let Err(err) = handle_message(&scope, &mut layout.borrow_mut(), &evt.data()) {
+                log::warn!("Failed to handle message: {err}");
+            }
+        }
+    })
+    .forget();
+
+    Ok(())
+}
+
+fn handle_message(
+    scope: &DedicatedWorkerGlobalScope,
+    layout: &mut ForceLayout,
+    msg: &JsValue,
+) -> Result<(), Error> {
+    let get = |key: &str| Reflect::get(msg, &key.into());
+
+    if get("type")?.as_string().as_deref() != Some(TYPE_STEP) {
+        return Ok(());
+    }
+
+    let physics = physics_from_js(&get("physics")?)?;
+    if layout.physics() != &physics {
+        layout.set_physics(&physics);
+    }
+    if get("reheat")?.is_truthy() {
+        layout.reheat();
+    }
+
+    let values = get("nodes")?.dyn_into::<Float64Array>()?.to_vec();
+    let (nodes, mut bodies): (Vec<_>, Vec<_>) = values
+        .chunks_exact(NODE_STRIDE)
+        .map(|chunk| {
+            let node = Node {
+                id: chunk[0] as usize,
+            };
+            let body = Body {
+                position: Position {
+                    x: chunk[1],
+                    y: chunk[2],
+                },
+                size: Size {
+                    width: chunk[3],
+                    height: chunk[4],
+                },
+                velocity: Position { x: 0.0, y: 0.0 },
+                fixed: chunk[5] > 0.0,
+                lock_x: Some(chunk[6]).filter(|x| !x.is_nan()),
+                lock_y: Some(chunk[7]).filter(|y| !y.is_nan()),
+            };
+            (node, body)
+        })
+        .unzip();
+
+    let values = get("springs")?.dyn_into::<Float64Array>()?.to_vec();
+    let springs: Vec<_> = values
+        .chunks_exact(SPRING_STRIDE)
+        .map(|chunk| Spring {
+            a: chunk[0] as usize,
+            b: chunk[1] as usize,
+            length: chunk[2],
+            strength: chunk[3],
+        })
+        .collect();
+
+    let focus = get("focus")?
+        .dyn_into::<Array>()
+        .ok()
+        .map(|focus| Position {
+            x: focus.get(0).as_f64().unwrap_or_default(),
+            y: focus.get(1).as_f64().unwrap_or_default(),
+        });
+
+    if !layout.is_stable() {
+        layout.simulate(&nodes, &mut bodies, &springs, focus);
+    }
+
+    let mut values = Vec::with_capacity(nodes.len() * POSITION_STRIDE);
+    for (node, body) in nodes.iter().zip(&bodies) {
+        values.extend([node.id as f64, body.position.x, body.position.y]);
+    }
+    let positions = Float64Array::from(values.as_slice());
+
+    let msg = Object::new();
+    Reflect::set(&msg, &"type".into(), &TYPE_POSITIONS.into())?;
+    Reflect::set(&msg, &"positions".into(), &positions)?;
+    Reflect::set(&msg, &"stable".into(), &layout.is_stable().into())?;
+    scope.post_message_with_transfer(&msg, &Array::of1(&positions.buffer()))?;
+
+    Ok(())
+}
+
+fn read_positions(msg: &JsValue) -> Result<Option<(Vec<f64>, bool)>, Error> {
+    let get = |key: &str| Reflect::get(msg, &key.into());
+
+    if get("type")?.as_string().as_deref() != Some(TYPE_POSITIONS) {
+        return Ok(None);
+    }
+
+    let positions = get("positions")?.dyn_into::<Float64Array>()?.to_vec();
+    let stable = get("stable")?.is_truthy();
+
+    Ok(Some((positions, stable)))
+}
+
+fn physics_to_js(physics: &PhysicsConfig) -> JsValue {
+    let values = [
+        physics.stiffness,
+        physics.repulsion,
+        physics.damping,
+        physics.max_velocity,
+        physics.edge_length,
+        physics.iterations as f64,
+        physics.alpha_decay,
+        physics.alpha_min,
+        physics.epsilon,
+        // exact up to 2^53
+        physics.seed as f64,
+        physics.gravity.strength,
+        physics.collision.strength,
+        physics.collision.padding,
+    ];
+    Float64Array::from(values.as_slice()).into()
+}
+
+fn physics_from_js(value: &JsValue) -> Result<PhysicsConfig, Error> {
+    let values = value.clone().dyn_into::<Float64Array>()?.to_vec();
+    let [stiffness, repulsion, damping, max_velocity, edge_length, iterations, alpha_decay, alpha_min, epsilon, seed, gravity, collision, padding] =
+        values[..]
+    else {
+        return Err(Error::Web("invalid physics configuration".into()));
+    };
+
+    let mut physics = PhysicsConfig {
+        stiffness,
+        repulsion,
+        damping,
+        max_velocity,
+        edge_length,
+        iterations: iterations as usize,
+        alpha_decay,
+        alpha_min,
+        epsilon,
+        seed: seed as u64,
+        ..Default::default()
+    };
+    physics.gravity.strength = gravity;
+    physics.collision.strength = collision;
+    physics.collision.padding = padding;
+
+    Ok(physics)
+}
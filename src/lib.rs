@@ -1,5 +1,24 @@
+pub mod bridge;
+pub mod clipboard;
+pub mod data;
+pub mod export;
 pub mod graph;
+pub mod io;
+pub mod label;
+pub mod layout;
+pub mod linked;
+pub mod minimap;
+pub mod persist;
 pub mod prelude;
+pub mod render;
+pub mod source;
+pub mod storage;
+pub mod transition;
+pub mod view;
 
 #[cfg(feature = "yew")]
 pub mod component;
+#[cfg(feature = "indexeddb")]
+pub mod indexeddb;
+#[cfg(feature = "url-hash")]
+pub mod url_hash;
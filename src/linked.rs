@@ -0,0 +1,278 @@
+//! Additional, read-only views of a running graph, with their own viewport.
+
+use crate::graph::{
+    Error, GraphController, Position, Rect, RenderLoop, Size, Target, MAX_SCALE, MIN_SCALE,
+    WHEEL_LINE_HEIGHT, WHEEL_ZOOM_SPEED,
+};
+use crate::render::{Backend, Renderer};
+use crate::view::Viewport;
+use gloo_events::{EventListener, EventListenerOptions};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, MouseEvent, PointerEvent, WheelEvent};
+
+/// The margin kept around the nodes when first showing the graph, in canvas pixels.
+const FIT_MARGIN: f64 = 20.0;
+
+/// Shows the nodes and edges of a graph displayed elsewhere, as a read-only overview next to
+/// the graph's own element.
+///
+/// All changes to the graph, including the selection, show up in all views. Only the viewport
+/// is independent: the view can be panned by dragging the background, and zoomed with the
+/// mouse wheel. Clicking a node selects it in the graph. Everything else, like moving nodes,
+/// editing, hovering, or the callbacks of the graph, is only handled by the graph's own
+/// element.
+///
+/// The view is only drawn again when the graph requested a redraw, see
+/// [`crate::graph::Graph::request_redraw`], or when its own viewport or size changed.
+pub struct LinkedView {
+    element: Element,
+    renderer: Box<dyn Renderer>,
+    graph: GraphController,
+    /// Until the graph was shown for the first time, then it gets fit into the view
+    viewport: Option<Viewport>,
+    /// The displayed size of the element, in CSS pixels
+    size: Size,
+    /// The last position of a pressed pointer, relative to the element
+    pressed: Option<Position>,
+    /// The redraws of the graph when it was last drawn, or `None` if the view changed since
+    drawn: Option<u64>,
+}
+
+impl LinkedView {
+    /// Create a view in an element, which must match the backend.
+    pub fn new(element: Element, backend: Backend, graph: GraphController) -> Result<Self, Error> {
+        let renderer = backend.create_renderer(&element)?;
+        Ok(Self {
+            element,
+            renderer,
+            graph,
+            viewport: None,
+            size: Size {
+                width: 0.0,
+                height: 0.0,
+            },
+            pressed: None,
+            drawn: None,
+        })
+    }
+
+    /// The viewport, once the graph was shown.
+    pub fn viewport(&self) -> Option<Viewport> {
+        self.viewport
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = Some(viewport);
+        self.drawn = None;
+    }
+
+    /// Size the renderer to the displayed size of the element, if that changed.
+    fn adjust_resolution(&mut self) {
+        let size = Size {
+            width: self.element.client_width() as f64,
+            height: self.element.client_height() as f64,
+        };
+        if size != self.size {
+            self.size = size;
+            self.drawn = None;
+            let dpi = web_sys::window().map_or(1.0, |window| window.device_pixel_ratio());
+            self.renderer
+                .resize((size.width * dpi) as u32, (size.height * dpi) as u32);
+        }
+    }
+
+    /// Draw the current state of the graph, if it or the view changed since the last time.
+    ///
+    /// While running, this is done with every frame.
+    pub fn draw(&mut self) -> Result<(), Error> {
+        self.adjust_resolution();
+
+        let Self {
+            renderer,
+            graph,
+            viewport,
+            size,
+            drawn,
+            ..
+        } = self;
+        graph
+            .with_graph(|graph| {
+                let redraws = graph.redraws();
+                if *drawn == Some(redraws) {
+                    return Ok(());
+                }
+                if viewport.is_none() {
+                    *viewport = graph
+                        .storage()
+                        .bounds()
+                        .and_then(|bounds| fit(bounds, *size));
+                }
+                *drawn = Some(redraws);
+                graph.draw_to(&mut **renderer, viewport.unwrap_or_default(), *size)
+            })
+            // shut down or busy, keep the last frame
+            .unwrap_or(Ok(()))
+    }
+
+    /// Convert a pointer position from the client area to the element.
+    fn element_position(&self, evt: &MouseEvent) -> Position {
+        let rect = self.element.get_bounding_client_rect();
+        Position {
+            x: evt.client_x() as f64 - rect.left(),
+            y: evt.client_y() as f64 - rect.top(),
+        }
+    }
+
+    fn pointer_down(&mut self, evt: &PointerEvent) {
+        let Some(viewport) = self.viewport else {
+            return;
+        };
+        if evt.button() != 0 {
+            return;
+        }
+
+        let position = self.element_position(evt);
+        let target = self
+            .graph
+            .with_graph(|graph| graph.target_at(viewport.to_graph(position)));
+        match target {
+            Some(Target::Node(node)) => {
+                self.graph.select([node]);
+            }
+            Some(_) => {
+                let _ = self.element.set_pointer_capture(evt.pointer_id());
+                self.pressed = Some(position);
+            }
+            None => {}
+        }
+    }
+
+    fn pointer_move(&mut self, evt: &PointerEvent) {
+        let position = self.element_position(evt);
+        let (Some(viewport), Some(last)) = (&mut self.viewport, self.pressed) else {
+            return;
+        };
+        viewport.offset.x += position.x - last.x;
+        viewport.offset.y += position.y - last.y;
+        self.pressed = Some(position);
+        self.drawn = None;
+    }
+
+    fn wheel(&mut self, evt: &WheelEvent) {
+        let Some(viewport) = self.viewport else {
+            return;
+        };
+        evt.prevent_default();
+
+        let delta = match evt.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => evt.delta_y() * WHEEL_LINE_HEIGHT,
+            _ => evt.delta_y(),
+        };
+        let position = self.element_position(evt);
+        let scale =
+            (viewport.scale * (-delta * WHEEL_ZOOM_SPEED).exp()).clamp(MIN_SCALE, MAX_SCALE);
+        self.set_viewport(viewport.zoom_at(position, scale));
+    }
+
+    /// Start drawing the changes with every frame, and handling input.
+    ///
+    /// The view keeps running until the returned handle gets dropped.
+    pub fn run(self) -> LinkedViewHandle {
+        let element = self.element.clone();
+        let view = Rc::new(RefCell::new(self));
+
+        fn pointer_event<F>(
+            element: &Element,
+            event_type: &'static str,
+            view: &Rc<RefCell<LinkedView>>,
+            f: F,
+        ) -> EventListener
+        where
+            F: Fn(&mut LinkedView, &PointerEvent) + 'static,
+        {
+            let view = view.clone();
+            EventListener::new(element, event_type, move |evt| {
+                if let Ok(mut view) = view.try_borrow_mut() {
+                    if let Some(evt) = evt.dyn_ref::<PointerEvent>() {
+                        f(&mut view, evt);
+                    }
+                }
+            })
+        }
+
+        let mut listeners = vec![
+            pointer_event(&element, "pointerdown", &view, LinkedView::pointer_down),
+            pointer_event(&element, "pointermove", &view, LinkedView::pointer_move),
+            pointer_event(&element, "pointerup", &view, |view, _| view.pressed = None),
+            pointer_event(&element, "pointercancel", &view, |view, _| {
+                view.pressed = None
+            }),
+        ];
+
+        {
+            let view = view.clone();
+            // not passive, so that scrolling the page can be prevented
+            let options = EventListenerOptions::enable_prevent_default();
+            listeners.push(EventListener::new_with_options(
+                &element,
+                "wheel",
+                options,
+                move |evt| {
+                    if let Ok(mut view) = view.try_borrow_mut() {
+                        if let Some(evt) = evt.dyn_ref::<WheelEvent>() {
+                            view.wheel(evt);
+                        }
+                    }
+                },
+            ));
+        }
+
+        let render_loop = RenderLoop::start(move |_| {
+            if let Ok(mut view) = view.try_borrow_mut() {
+                if let Err(err) = view.draw() {
+                    log::warn!("Failed to draw the linked view: {err}");
+                }
+            }
+        });
+
+        LinkedViewHandle {
+            render_loop,
+            _listeners: listeners,
+        }
+    }
+}
+
+/// The viewport showing all of an area, if there is space to show it.
+fn fit(bounds: Rect, size: Size) -> Option<Viewport> {
+    if size.width <= FIT_MARGIN * 2.0 || size.height <= FIT_MARGIN * 2.0 {
+        return None;
+    }
+
+    let scale = ((size.width - FIT_MARGIN * 2.0) / bounds.size.width)
+        .min((size.height - FIT_MARGIN * 2.0) / bounds.size.height)
+        .clamp(MIN_SCALE, MAX_SCALE);
+    let center = bounds.center();
+    Some(Viewport {
+        offset: Position {
+            x: size.width / 2.0 - center.x * scale,
+            y: size.height / 2.0 - center.y * scale,
+        },
+        scale,
+    })
+}
+
+/// A handle to a running [`LinkedView`].
+///
+/// Dropping the handle stops drawing, and removes all event listeners.
+pub struct LinkedViewHandle {
+    render_loop: Rc<RenderLoop>,
+    _listeners: Vec<EventListener>,
+}
+
+impl Drop for LinkedViewHandle {
+    fn drop(&mut self) {
+        self.render_loop.cancel();
+    }
+}
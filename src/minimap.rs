@@ -0,0 +1,249 @@
+//! A scaled-down overview of a graph, for navigating large graphs.
+
+use crate::graph::{Error, GraphController, Position, Rect, RenderLoop, Size};
+use crate::render::{EDGE_STROKE, NODE_FILL, SELECTED_NODE_FILL, SELECTION_RECT_FILL};
+use gloo_events::EventListener;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, PointerEvent};
+
+/// The space kept free around the graph, as a fraction of its size.
+const PADDING: f64 = 0.05;
+
+/// Shows all nodes of a graph, along with the area currently visible in it.
+///
+/// Pressing or dragging the pointer on the minimap moves the visible area of the graph.
+pub struct Minimap {
+    canvas: HtmlCanvasElement,
+    graph: GraphController,
+    ctx: Option<CanvasRenderingContext2d>,
+    /// The area of the graph shown with the last frame, and the scale it was drawn with
+    shown: Option<(Rect, f64)>,
+    /// If the pointer is pressed
+    dragging: bool,
+}
+
+impl Minimap {
+    pub fn new(canvas: HtmlCanvasElement, graph: GraphController) -> Self {
+        Self {
+            canvas,
+            graph,
+            ctx: None,
+            shown: None,
+            dragging: false,
+        }
+    }
+
+    fn context(&mut self) -> Result<&CanvasRenderingContext2d, Error> {
+        let ctx = match self.ctx.take() {
+            Some(ctx) => ctx,
+            None => self
+                .canvas
+                .get_context("2d")?
+                .ok_or(Error::MissingContext)?
+                .dyn_into::<CanvasRenderingContext2d>()?,
+        };
+
+        Ok(self.ctx.insert(ctx))
+    }
+
+    /// Draw the current state of the graph, which is done with every frame while running.
+    pub fn draw(&mut self) -> Result<(), Error> {
+        let size = Size {
+            width: self.canvas.client_width() as f64,
+            height: self.canvas.client_height() as f64,
+        };
+        let dpi = web_sys::window().map_or(1.0, |window| window.device_pixel_ratio());
+        let (width, height) = ((size.width * dpi) as u32, (size.height * dpi) as u32);
+        if self.canvas.width() != width || self.canvas.height() != height {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+        }
+
+        let Some((nodes, edges, visible)) = self.graph.with_graph(|graph| {
+            let storage = graph.storage();
+            let nodes: Vec<Rect> = storage
+                .nodes()
+                .filter_map(|node| storage.rect(node))
+                .collect();
+            let edges: Vec<(Position, Position)> = storage
+                .edges()
+                .filter_map(|(a, b, _)| Some((storage.center(a)?, storage.center(b)?)))
+                .collect();
+            (nodes, edges, graph.visible_rect())
+        }) else {
+            // shut down or busy, keep the last frame
+            return Ok(());
+        };
+
+        let shown = nodes
+            .iter()
+            .copied()
+            .chain(visible)
+            .reduce(|a, b| a.union(&b));
+
+        let ctx = self.context()?.clone();
+        ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)?;
+        ctx.clear_rect(0.0, 0.0, width as _, height as _);
+
+        let Some(shown) = shown else {
+            self.shown = None;
+            return Ok(());
+        };
+        let shown = Rect::spanning(
+            Position {
+                x: shown.position.x - shown.size.width * PADDING,
+                y: shown.position.y - shown.size.height * PADDING,
+            },
+            Position {
+                x: shown.right() + shown.size.width * PADDING,
+                y: shown.bottom() + shown.size.height * PADDING,
+            },
+        );
+        let scale = (size.width / shown.size.width).min(size.height / shown.size.height);
+        self.shown = Some((shown, scale));
+
+        // graph coordinates, centered in the minimap
+        let offset = Position {
+            x: (size.width - shown.size.width * scale) / 2.0 - shown.position.x * scale,
+            y: (size.height - shown.size.height * scale) / 2.0 - shown.position.y * scale,
+        };
+        ctx.set_transform(
+            dpi * scale,
+            0.0,
+            0.0,
+            dpi * scale,
+            dpi * offset.x,
+            dpi * offset.y,
+        )?;
+
+        ctx.set_line_width(1.0 / scale);
+        ctx.set_stroke_style_str(EDGE_STROKE);
+        ctx.begin_path();
+        for (from, to) in edges {
+            ctx.move_to(from.x, from.y);
+            ctx.line_to(to.x, to.y);
+        }
+        ctx.stroke();
+
+        ctx.set_fill_style_str(NODE_FILL);
+        for Rect { position, size } in nodes {
+            ctx.fill_rect(position.x, position.y, size.width, size.height);
+        }
+
+        if let Some(Rect { position, size }) = visible {
+            ctx.set_fill_style_str(SELECTION_RECT_FILL);
+            ctx.fill_rect(position.x, position.y, size.width, size.height);
+            ctx.set_line_width(2.0 / scale);
+            ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+            ctx.stroke_rect(position.x, position.y, size.width, size.height);
+        }
+
+        Ok(())
+    }
+
+    /// The position in graph coordinates, shown at a position relative to the canvas.
+    fn to_graph(&self, position: Position) -> Option<Position> {
+        let (shown, scale) = self.shown?;
+        let (width, height) = (
+            self.canvas.client_width() as f64,
+            self.canvas.client_height() as f64,
+        );
+        Some(Position {
+            x: shown.position.x + (position.x - (width - shown.size.width * scale) / 2.0) / scale,
+            y: shown.position.y + (position.y - (height - shown.size.height * scale) / 2.0) / scale,
+        })
+    }
+
+    /// Center the graph on the position of the pointer.
+    fn navigate(&self, evt: &PointerEvent) {
+        let rect = self.canvas.get_bounding_client_rect();
+        let position = Position {
+            x: evt.client_x() as f64 - rect.left(),
+            y: evt.client_y() as f64 - rect.top(),
+        };
+        if let Some(position) = self.to_graph(position) {
+            self.graph.with_graph(|graph| graph.center_on(position));
+        }
+    }
+
+    /// Start drawing with every frame, and following the pointer.
+    ///
+    /// The minimap keeps running until the returned handle gets dropped.
+    pub fn run(self) -> MinimapHandle {
+        let canvas = self.canvas.clone();
+        let minimap = Rc::new(RefCell::new(self));
+
+        // dragging on touch screens navigates, instead of scrolling the page
+        let _ = canvas.style().set_property("touch-action", "none");
+
+        fn pointer_event<F>(
+            canvas: &HtmlCanvasElement,
+            event_type: &'static str,
+            minimap: &Rc<RefCell<Minimap>>,
+            f: F,
+        ) -> EventListener
+        where
+            F: Fn(&mut Minimap, &PointerEvent) + 'static,
+        {
+            let minimap = minimap.clone();
+            EventListener::new(canvas, event_type, move |evt| {
+                if let Ok(mut minimap) = minimap.try_borrow_mut() {
+                    if let Some(evt) = evt.dyn_ref::<PointerEvent>() {
+                        f(&mut minimap, evt);
+                    }
+                }
+            })
+        }
+
+        let listeners = vec![
+            pointer_event(&canvas, "pointerdown", &minimap, |minimap, evt| {
+                if evt.button() != 0 {
+                    return;
+                }
+                let _ = minimap.canvas.set_pointer_capture(evt.pointer_id());
+                minimap.dragging = true;
+                minimap.navigate(evt);
+            }),
+            pointer_event(&canvas, "pointermove", &minimap, |minimap, evt| {
+                if minimap.dragging {
+                    minimap.navigate(evt);
+                }
+            }),
+            pointer_event(&canvas, "pointerup", &minimap, |minimap, _| {
+                minimap.dragging = false;
+            }),
+            pointer_event(&canvas, "pointercancel", &minimap, |minimap, _| {
+                minimap.dragging = false;
+            }),
+        ];
+
+        let render_loop = RenderLoop::start(move |_| {
+            if let Ok(mut minimap) = minimap.try_borrow_mut() {
+                if let Err(err) = minimap.draw() {
+                    log::warn!("Failed to draw the minimap: {err}");
+                }
+            }
+        });
+
+        MinimapHandle {
+            render_loop,
+            _listeners: listeners,
+        }
+    }
+}
+
+/// A handle to a running minimap.
+///
+/// Dropping the handle stops drawing, and removes all event listeners.
+pub struct MinimapHandle {
+    render_loop: Rc<RenderLoop>,
+    _listeners: Vec<EventListener>,
+}
+
+impl Drop for MinimapHandle {
+    fn drop(&mut self) {
+        self.render_loop.cancel();
+    }
+}
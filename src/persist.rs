@@ -0,0 +1,138 @@
+//! Keeping the layout of a graph in the local storage of the browser.
+//!
+//! Only the positions are stored, by keys of the application which stay the same when the
+//! nodes are loaded again, see [`crate::graph::Graph::export_positions`].
+
+use crate::graph::{Error, GraphController, Node, Position};
+use gloo_timers::callback::Interval;
+use js_sys::{Array, Object, Reflect, JSON};
+use std::collections::HashMap;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::Storage;
+
+/// How often a running graph is checked for a changed layout, in milliseconds.
+const SAVE_INTERVAL: u32 = 1000;
+
+/// Node positions, stored in the local storage under a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutStore {
+    storage_key: String,
+}
+
+impl LayoutStore {
+    pub fn new(storage_key: impl Into<String>) -> Self {
+        Self {
+            storage_key: storage_key.into(),
+        }
+    }
+
+    fn storage() -> Result<Storage, Error> {
+        web_sys::window()
+            .ok_or(Error::MissingWindow)?
+            .local_storage()?
+            .ok_or(Error::MissingWindow)
+    }
+
+    /// The stored positions, which are empty if nothing, or something invalid, was stored.
+    pub fn load(&self) -> Result<HashMap<String, Position>, Error> {
+        let Some(json) = Self::storage()?.get_item(&self.storage_key)? else {
+            return Ok(HashMap::new());
+        };
+        let Ok(value) = JSON::parse(&json) else {
+            log::warn!("Ignoring invalid layout stored as {}", self.storage_key);
+            return Ok(HashMap::new());
+        };
+
+        let mut positions = HashMap::new();
+        for entry in Object::entries(value.unchecked_ref()).iter() {
+            let entry: Array = entry.unchecked_into();
+            let (Some(key), Some(x), Some(y)) = (
+                entry.get(0).as_string(),
+                Reflect::get_u32(&entry.get(1), 0)
+                    .ok()
+                    .and_then(|x| x.as_f64()),
+                Reflect::get_u32(&entry.get(1), 1)
+                    .ok()
+                    .and_then(|y| y.as_f64()),
+            ) else {
+                continue;
+            };
+            positions.insert(key, Position { x, y });
+        }
+        Ok(positions)
+    }
+
+    /// Replace the stored positions, as a JSON object of `[x, y]` arrays.
+    pub fn save(&self, positions: &HashMap<String, Position>) -> Result<(), Error> {
+        let value = Object::new();
+        for (key, position) in positions {
+            let position = Array::of2(&position.x.into(), &position.y.into());
+            Reflect::set(&value, &JsValue::from_str(key), &position)?;
+        }
+        let json = String::from(JSON::stringify(&value)?);
+        Self::storage()?.set_item(&self.storage_key, &json)?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), Error> {
+        Self::storage()?.remove_item(&self.storage_key)?;
+        Ok(())
+    }
+}
+
+/// Restore the stored positions of the nodes, and keep storing them while the graph runs.
+///
+/// Nodes are identified by the keys the function returns for them. The layout is stored once it
+/// came to rest and nothing is being dragged, whenever it changed. Dropping the returned handle
+/// stops storing it.
+pub fn persist_layout<F>(
+    graph: GraphController,
+    storage_key: impl Into<String>,
+    key: F,
+) -> PersistHandle
+where
+    F: Fn(Node) -> Option<String> + 'static,
+{
+    let store = LayoutStore::new(storage_key);
+
+    let mut saved = match store.load() {
+        Ok(positions) => positions,
+        Err(err) => {
+            log::warn!("Failed to load the layout: {err}");
+            HashMap::new()
+        }
+    };
+    graph.with_graph(|graph| {
+        let nodes: HashMap<String, Node> = graph
+            .storage()
+            .nodes()
+            .filter_map(|node| Some((key(node)?, node)))
+            .collect();
+        graph.apply_positions(&saved, |key| nodes.get(key).copied());
+    });
+
+    let interval = Interval::new(SAVE_INTERVAL, move || {
+        let positions = graph.with_graph(|graph| {
+            (graph.is_stable() && !graph.is_dragging()).then(|| graph.export_positions(&key))
+        });
+        let Some(Some(positions)) = positions else {
+            return;
+        };
+        if positions == saved {
+            return;
+        }
+        match store.save(&positions) {
+            Ok(()) => saved = positions,
+            Err(err) => log::warn!("Failed to store the layout: {err}"),
+        }
+    });
+
+    PersistHandle {
+        _interval: interval,
+    }
+}
+
+/// Keeps storing the layout of a graph, until dropped.
+pub struct PersistHandle {
+    _interval: Interval,
+}
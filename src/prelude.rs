@@ -1 +1,15 @@
+pub use crate::bridge::*;
+pub use crate::clipboard::*;
+pub use crate::data::*;
+pub use crate::export::*;
 pub use crate::graph::*;
+pub use crate::label::*;
+pub use crate::layout::*;
+pub use crate::linked::*;
+pub use crate::minimap::*;
+pub use crate::persist::*;
+pub use crate::render::*;
+pub use crate::source::*;
+pub use crate::storage::*;
+pub use crate::transition::*;
+pub use crate::view::*;
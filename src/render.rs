@@ -0,0 +1,306 @@
+//! Rendering of the graph.
+//!
+//! The graph itself only decides what to draw, a [`Renderer`] takes care of how it is drawn.
+
+mod canvas2d;
+#[cfg(feature = "offscreen")]
+pub mod offscreen;
+mod svg;
+#[cfg(feature = "webgl")]
+mod webgl;
+
+pub use canvas2d::*;
+pub use svg::*;
+#[cfg(feature = "webgl")]
+pub use webgl::*;
+
+use crate::graph::{EdgeProperties, Error, Node, NodeProperties, Position, Rect, Size};
+use crate::label::{Label, LabelStyle};
+use crate::view::Viewport;
+use wasm_bindgen::JsCast;
+use web_sys::Element;
+
+/// The available rendering backends.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Canvas, using a 2D context.
+    #[default]
+    Canvas2d,
+    /// SVG elements.
+    Svg,
+    /// Canvas, using WebGL 2.
+    #[cfg(feature = "webgl")]
+    WebGl,
+}
+
+impl Backend {
+    /// The name of the element the backend renders into.
+    pub fn element_name(&self) -> &'static str {
+        match self {
+            Self::Canvas2d => "canvas",
+            Self::Svg => "svg",
+            #[cfg(feature = "webgl")]
+            Self::WebGl => "canvas",
+        }
+    }
+
+    /// Create a renderer for the element, which must be of the type [`Backend::element_name`].
+    pub fn create_renderer(&self, element: &Element) -> Result<Box<dyn Renderer>, Error> {
+        let invalid = |_| Error::Web(format!("expected a <{}> element", self.element_name()));
+
+        Ok(match self {
+            Self::Canvas2d => Box::new(Canvas2dRenderer::new(
+                element.clone().dyn_into().map_err(invalid)?,
+            )),
+            Self::Svg => Box::new(SvgRenderer::new(element.clone())?),
+            #[cfg(feature = "webgl")]
+            Self::WebGl => Box::new(WebGlRenderer::new(
+                element.clone().dyn_into().map_err(invalid)?,
+            )?),
+        })
+    }
+}
+
+/// The radius of a node, drawn as a dot, in screen pixels.
+pub const DOT_RADIUS: f64 = 3.0;
+/// The CSS color nodes are filled with.
+pub const NODE_FILL: &str = "red";
+/// The CSS color selected nodes are filled with.
+pub const SELECTED_NODE_FILL: &str = "royalblue";
+/// The CSS color edges are drawn with, selected edges use [`SELECTED_NODE_FILL`].
+pub const EDGE_STROKE: &str = "black";
+/// The CSS color the rectangle of a pending selection is filled with, its border uses
+/// [`SELECTED_NODE_FILL`].
+pub const SELECTION_RECT_FILL: &str = "rgba(65, 105, 225, 0.15)";
+
+/// Information about the frame which is about to be drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    /// The ratio between physical and CSS pixels.
+    pub dpi: f64,
+    /// The viewport of the graph.
+    pub viewport: Viewport,
+    /// How detailed the frame should be drawn.
+    pub detail: Detail,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            dpi: 1.0,
+            viewport: Default::default(),
+            detail: Default::default(),
+        }
+    }
+}
+
+/// The level of detail to draw with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Detail {
+    /// Full shapes and labels.
+    #[default]
+    Full,
+    /// Nodes as dots, without labels.
+    Reduced,
+}
+
+/// When to switch to [`Detail::Reduced`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelOfDetail {
+    /// Zoom level below which the detail gets reduced.
+    pub min_scale: f64,
+    /// Number of nodes above which the detail gets reduced.
+    pub max_nodes: usize,
+    /// Number of edges drawn with reduced detail, additional edges are skipped.
+    pub max_edges: usize,
+}
+
+impl Default for LevelOfDetail {
+    fn default() -> Self {
+        Self {
+            min_scale: 0.4,
+            max_nodes: 2_000,
+            max_edges: 5_000,
+        }
+    }
+}
+
+impl LevelOfDetail {
+    /// Disable reducing the detail.
+    pub fn never() -> Self {
+        Self {
+            min_scale: 0.0,
+            max_nodes: usize::MAX,
+            max_edges: usize::MAX,
+        }
+    }
+
+    pub fn detail(&self, scale: f64, nodes: usize) -> Detail {
+        if scale < self.min_scale || nodes > self.max_nodes {
+            Detail::Reduced
+        } else {
+            Detail::Full
+        }
+    }
+}
+
+/// A node to draw.
+pub struct NodeView<'a> {
+    pub node: Node,
+    /// The top left corner.
+    pub position: Position,
+    pub size: Size,
+    pub properties: &'a NodeProperties,
+    pub label: &'a Label,
+    pub label_style: &'a LabelStyle,
+    pub hovered: bool,
+    pub selected: bool,
+    /// The CSS color of its category, if it has a styled one.
+    pub color: Option<&'a str>,
+    /// From `0` (invisible) to `1` (opaque), lower when faded by a highlight.
+    pub opacity: f64,
+}
+
+impl<'a> NodeView<'a> {
+    pub fn center(&self) -> Position {
+        Position {
+            x: self.position.x + self.size.width / 2.0,
+            y: self.position.y + self.size.height / 2.0,
+        }
+    }
+
+    /// The CSS color to fill the node with.
+    pub fn fill(&self) -> &'a str {
+        match self.selected {
+            true => SELECTED_NODE_FILL,
+            false => self.color.unwrap_or(NODE_FILL),
+        }
+    }
+
+    /// The radius of the node drawn as a dot, in graph coordinates.
+    pub fn dot_radius(&self, frame: &Frame) -> f64 {
+        DOT_RADIUS / frame.viewport.scale
+    }
+}
+
+/// An edge to draw.
+pub struct EdgeView<'a> {
+    pub from: Node,
+    pub to: Node,
+    /// The center of the "from" node.
+    pub from_position: Position,
+    /// The center of the "to" node.
+    pub to_position: Position,
+    /// Points the edge passes through, ordered from "from" to "to".
+    pub waypoints: &'a [Position],
+    pub properties: &'a EdgeProperties,
+    pub selected: bool,
+    /// The CSS color of its category, if it has a styled one.
+    pub color: Option<&'a str>,
+    /// From `0` (invisible) to `1` (opaque), lower when faded by a highlight.
+    pub opacity: f64,
+}
+
+impl<'a> EdgeView<'a> {
+    /// The CSS color to draw the edge with.
+    pub fn stroke(&self) -> &'a str {
+        match self.selected {
+            true => SELECTED_NODE_FILL,
+            false => self.color.unwrap_or(EDGE_STROKE),
+        }
+    }
+
+    /// All points of the line, starting at the "from" node and ending at the "to" node.
+    pub fn points(&self) -> impl Iterator<Item = Position> + '_ {
+        std::iter::once(self.from_position)
+            .chain(self.waypoints.iter().copied())
+            .chain(std::iter::once(self.to_position))
+    }
+
+    /// The straight segments of the line, as pairs of start and end.
+    pub fn segments(&self) -> impl Iterator<Item = (Position, Position)> + '_ {
+        self.points().zip(self.points().skip(1))
+    }
+}
+
+/// A backend drawing the graph.
+///
+/// For each frame, the graph calls [`Renderer::begin_frame`] and [`Renderer::clear`], followed by
+/// all edges and then all nodes (bottom to top), handles of selected nodes and their edges, a
+/// selection rectangle or an edge (if the user is drawing one), and finally
+/// [`Renderer::end_frame`].
+pub trait Renderer {
+    /// Set the size of the drawing surface, in physical pixels.
+    fn resize(&mut self, width: u32, height: u32) {
+        let _ = (width, height);
+    }
+
+    fn begin_frame(&mut self, frame: &Frame) -> Result<(), Error>;
+
+    fn clear(&mut self) -> Result<(), Error>;
+
+    fn draw_edge(&mut self, edge: &EdgeView) -> Result<(), Error>;
+
+    fn draw_node(&mut self, node: &NodeView) -> Result<(), Error>;
+
+    /// Draw a handle for resizing a node or moving a waypoint, in graph coordinates.
+    fn draw_handle(&mut self, rect: &Rect) -> Result<(), Error> {
+        let _ = rect;
+        Ok(())
+    }
+
+    /// Draw the rectangle the user is selecting nodes with, in graph coordinates.
+    fn draw_selection_rect(&mut self, rect: &Rect) -> Result<(), Error> {
+        let _ = rect;
+        Ok(())
+    }
+
+    /// Draw the edge the user is creating, from the center of a node to the pointer, in graph
+    /// coordinates.
+    fn draw_pending_edge(&mut self, from: Position, to: Position) -> Result<(), Error> {
+        let _ = (from, to);
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<R: Renderer + ?Sized> Renderer for Box<R> {
+    fn resize(&mut self, width: u32, height: u32) {
+        (**self).resize(width, height)
+    }
+
+    fn begin_frame(&mut self, frame: &Frame) -> Result<(), Error> {
+        (**self).begin_frame(frame)
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        (**self).clear()
+    }
+
+    fn draw_edge(&mut self, edge: &EdgeView) -> Result<(), Error> {
+        (**self).draw_edge(edge)
+    }
+
+    fn draw_node(&mut self, node: &NodeView) -> Result<(), Error> {
+        (**self).draw_node(node)
+    }
+
+    fn draw_handle(&mut self, rect: &Rect) -> Result<(), Error> {
+        (**self).draw_handle(rect)
+    }
+
+    fn draw_selection_rect(&mut self, rect: &Rect) -> Result<(), Error> {
+        (**self).draw_selection_rect(rect)
+    }
+
+    fn draw_pending_edge(&mut self, from: Position, to: Position) -> Result<(), Error> {
+        (**self).draw_pending_edge(from, to)
+    }
+
+    fn end_frame(&mut self) -> Result<(), Error> {
+        (**self).end_frame()
+    }
+}
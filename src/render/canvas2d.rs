@@ -0,0 +1,187 @@
+use super::{
+    Detail, EdgeView, Frame, NodeView, Renderer, EDGE_STROKE, SELECTED_NODE_FILL,
+    SELECTION_RECT_FILL,
+};
+use crate::graph::{Error, Position, Rect};
+use std::f64::consts::TAU;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+/// The default renderer, using a 2D canvas context.
+pub struct Canvas2dRenderer {
+    canvas: HtmlCanvasElement,
+    ctx: Option<CanvasRenderingContext2d>,
+    frame: Frame,
+}
+
+impl Canvas2dRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Self {
+        Self {
+            canvas,
+            ctx: None,
+            frame: Default::default(),
+        }
+    }
+
+    fn context(&mut self) -> Result<&CanvasRenderingContext2d, Error> {
+        let ctx = match self.ctx.take() {
+            Some(ctx) => ctx,
+            None => self
+                .canvas
+                .get_context("2d")?
+                .ok_or(Error::MissingContext)?
+                .dyn_into::<CanvasRenderingContext2d>()?,
+        };
+
+        Ok(self.ctx.insert(ctx))
+    }
+}
+
+impl Renderer for Canvas2dRenderer {
+    fn resize(&mut self, width: u32, height: u32) {
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+    }
+
+    fn begin_frame(&mut self, frame: &Frame) -> Result<(), Error> {
+        self.frame = *frame;
+
+        let ctx = self.context()?;
+
+        ctx.save();
+        ctx.scale(frame.dpi, frame.dpi)?;
+        ctx.translate(frame.viewport.offset.x, frame.viewport.offset.y)?;
+        ctx.scale(frame.viewport.scale, frame.viewport.scale)?;
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        let (width, height) = (self.canvas.width(), self.canvas.height());
+        let ctx = self.context()?;
+
+        // clear in device pixels, independent of the current transformation
+        ctx.save();
+        ctx.reset_transform()?;
+        ctx.clear_rect(0.0, 0.0, width as _, height as _);
+        ctx.restore();
+
+        Ok(())
+    }
+
+    fn draw_edge(&mut self, edge: &EdgeView) -> Result<(), Error> {
+        let ctx = self.context()?;
+
+        ctx.begin_path();
+
+        let Position { x, y } = edge.from_position;
+        ctx.move_to(x, y);
+
+        for Position { x, y } in edge.points().skip(1) {
+            ctx.line_to(x, y);
+        }
+
+        ctx.set_stroke_style_str(edge.stroke());
+        ctx.set_global_alpha(edge.opacity);
+        ctx.stroke();
+        ctx.set_stroke_style_str(EDGE_STROKE);
+        ctx.set_global_alpha(1.0);
+
+        Ok(())
+    }
+
+    fn draw_node(&mut self, node: &NodeView) -> Result<(), Error> {
+        let frame = self.frame;
+        let ctx = self.context()?;
+        ctx.set_global_alpha(node.opacity);
+
+        if frame.detail == Detail::Reduced {
+            let Position { x, y } = node.center();
+            ctx.set_fill_style_str(node.fill());
+            ctx.begin_path();
+            ctx.arc(x, y, node.dot_radius(&frame), 0.0, TAU)?;
+            ctx.fill();
+            ctx.set_global_alpha(1.0);
+            return Ok(());
+        }
+
+        ctx.set_fill_style_str(node.fill());
+        ctx.begin_path();
+        ctx.fill_rect(
+            node.position.x,
+            node.position.y,
+            node.size.width,
+            node.size.height,
+        );
+        if node.hovered {
+            ctx.set_line_width(5.0);
+        } else {
+            ctx.set_line_width(1.0);
+        }
+        ctx.rect(
+            node.position.x,
+            node.position.y,
+            node.size.width,
+            node.size.height,
+        );
+        ctx.stroke();
+
+        node.label.draw(ctx, node.label_style, node.center());
+        ctx.set_global_alpha(1.0);
+
+        Ok(())
+    }
+
+    fn draw_handle(&mut self, rect: &Rect) -> Result<(), Error> {
+        let scale = self.frame.viewport.scale;
+        let ctx = self.context()?;
+
+        let Rect { position, size } = *rect;
+        ctx.save();
+        ctx.set_fill_style_str("white");
+        ctx.fill_rect(position.x, position.y, size.width, size.height);
+        ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+        ctx.set_line_width(1.0 / scale);
+        ctx.stroke_rect(position.x, position.y, size.width, size.height);
+        ctx.restore();
+
+        Ok(())
+    }
+
+    fn draw_selection_rect(&mut self, rect: &Rect) -> Result<(), Error> {
+        let scale = self.frame.viewport.scale;
+        let ctx = self.context()?;
+
+        let Rect { position, size } = *rect;
+        ctx.save();
+        ctx.set_fill_style_str(SELECTION_RECT_FILL);
+        ctx.fill_rect(position.x, position.y, size.width, size.height);
+        ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+        ctx.set_line_width(1.0 / scale);
+        ctx.stroke_rect(position.x, position.y, size.width, size.height);
+        ctx.restore();
+
+        Ok(())
+    }
+
+    fn draw_pending_edge(&mut self, from: Position, to: Position) -> Result<(), Error> {
+        let scale = self.frame.viewport.scale;
+        let ctx = self.context()?;
+
+        ctx.save();
+        ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+        ctx.set_line_width(2.0 / scale);
+        ctx.begin_path();
+        ctx.move_to(from.x, from.y);
+        ctx.line_to(to.x, to.y);
+        ctx.stroke();
+        ctx.restore();
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<(), Error> {
+        self.context()?.restore();
+        Ok(())
+    }
+}
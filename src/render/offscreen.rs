@@ -0,0 +1,402 @@
+//! Rendering on an `OffscreenCanvas` inside a web worker.
+//!
+//! The canvas is transferred to a worker, and the main thread only ships a display list for each
+//! frame. Simulation and input handling stay on the main thread, but all the actual drawing
+//! happens in the worker.
+//!
+//! The worker needs to load the application's WASM module and call [`worker_main`]. On the main
+//! thread, the graph is configured using a [`WorkerRenderer`]:
+//!
+//! ```ignore
+//! let worker = web_sys::Worker::new("./render-worker.js")?;
+//! graph.set_renderer(WorkerRenderer::new(&canvas, worker)?);
+//! ```
+
+use super::{
+    Detail, EdgeView, Frame, NodeView, Renderer, EDGE_STROKE, NODE_FILL, SELECTED_NODE_FILL,
+    SELECTION_RECT_FILL,
+};
+use crate::graph::{Error, Position, Rect};
+use crate::label::LabelStyle;
+use gloo_events::EventListener;
+use js_sys::{Array, Float32Array, Object, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    DedicatedWorkerGlobalScope, HtmlCanvasElement, MessageEvent, OffscreenCanvas,
+    OffscreenCanvasRenderingContext2d, Worker,
+};
+
+const TYPE_INIT: &str = "init";
+const TYPE_FRAME: &str = "frame";
+
+/// Number of values per edge in the display list.
+const EDGE_STRIDE: usize = 6;
+/// Number of values per node in the display list.
+const NODE_STRIDE: usize = 7;
+
+/// A renderer forwarding each frame to a worker running [`worker_main`].
+///
+/// The colors of categories are not forwarded, nodes and edges use the default colors.
+pub struct WorkerRenderer {
+    worker: Worker,
+
+    width: u32,
+    height: u32,
+    frame: Frame,
+
+    edges: Vec<f32>,
+    nodes: Vec<f32>,
+    labels: Array,
+    label_style: LabelStyle,
+    /// Resize handles, as x, y, width, and height
+    handles: Vec<f32>,
+    selection_rect: Option<Rect>,
+    pending_edge: Option<(Position, Position)>,
+}
+
+impl WorkerRenderer {
+    /// Transfer control of the canvas to the worker.
+    ///
+    /// After this, the canvas can no longer be used for any other context.
+    pub fn new(canvas: &HtmlCanvasElement, worker: Worker) -> Result<Self, Error> {
+        let offscreen = canvas.transfer_control_to_offscreen()?;
+
+        let msg = Object::new();
+        Reflect::set(&msg, &"type".into(), &TYPE_INIT.into())?;
+        Reflect::set(&msg, &"canvas".into(), &offscreen)?;
+        worker.post_message_with_transfer(&msg, &Array::of1(&offscreen))?;
+
+        Ok(Self {
+            worker,
+            width: canvas.width(),
+            height: canvas.height(),
+            frame: Default::default(),
+            edges: Vec::new(),
+            nodes: Vec::new(),
+            labels: Array::new(),
+            label_style: Default::default(),
+            handles: Vec::new(),
+            selection_rect: None,
+            pending_edge: None,
+        })
+    }
+}
+
+impl Renderer for WorkerRenderer {
+    fn resize(&mut self, width: u32, height: u32) {
+        // the worker applies this with the next frame
+        self.width = width;
+        self.height = height;
+    }
+
+    fn begin_frame(&mut self, frame: &Frame) -> Result<(), Error> {
+        self.frame = *frame;
+        self.edges.clear();
+        self.nodes.clear();
+        self.labels = Array::new();
+        self.handles.clear();
+        self.selection_rect = None;
+        self.pending_edge = None;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        // the worker always clears before drawing
+        Ok(())
+    }
+
+    fn draw_edge(&mut self, edge: &EdgeView) -> Result<(), Error> {
+        let selected = if edge.selected { 1.0 } else { 0.0 };
+        // one entry per segment, so that the worker doesn't need to know about waypoints
+        for (from, to) in edge.segments() {
+            self.edges.extend([
+                from.x as f32,
+                from.y as f32,
+                to.x as f32,
+                to.y as f32,
+                selected,
+                edge.opacity as f32,
+            ]);
+        }
+        Ok(())
+    }
+
+    fn draw_node(&mut self, node: &NodeView) -> Result<(), Error> {
+        let hovered = if node.hovered { 1.0 } else { 0.0 };
+        let selected = if node.selected { 1.0 } else { 0.0 };
+
+        match self.frame.detail {
+            Detail::Full => {
+                self.nodes.extend([
+                    node.position.x as f32,
+                    node.position.y as f32,
+                    node.size.width as f32,
+                    node.size.height as f32,
+                    hovered,
+                    selected,
+                    node.opacity as f32,
+                ]);
+                self.labels.push(
+                    &node
+                        .label
+                        .lines()
+                        .iter()
+                        .map(|line| JsValue::from_str(line))
+                        .collect::<Array>(),
+                );
+            }
+            Detail::Reduced => {
+                // the worker doesn't know about dots, so we send a small box without a label
+                let radius = node.dot_radius(&self.frame);
+                let center = node.center();
+                self.nodes.extend([
+                    (center.x - radius) as f32,
+                    (center.y - radius) as f32,
+                    (radius * 2.0) as f32,
+                    (radius * 2.0) as f32,
+                    hovered,
+                    selected,
+                    node.opacity as f32,
+                ]);
+                self.labels.push(&Array::new());
+            }
+        }
+
+        if &self.label_style != node.label_style {
+            self.label_style = node.label_style.clone();
+        }
+
+        Ok(())
+    }
+
+    fn draw_handle(&mut self, rect: &Rect) -> Result<(), Error> {
+        self.handles.extend([
+            rect.position.x as f32,
+            rect.position.y as f32,
+            rect.size.width as f32,
+            rect.size.height as f32,
+        ]);
+        Ok(())
+    }
+
+    fn draw_selection_rect(&mut self, rect: &Rect) -> Result<(), Error> {
+        self.selection_rect = Some(*rect);
+        Ok(())
+    }
+
+    fn draw_pending_edge(&mut self, from: Position, to: Position) -> Result<(), Error> {
+        self.pending_edge = Some((from, to));
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<(), Error> {
+        let pending_edge = match self.pending_edge {
+            Some((from, to)) => {
+                Array::of4(&from.x.into(), &from.y.into(), &to.x.into(), &to.y.into()).into()
+            }
+            None => JsValue::NULL,
+        };
+        let selection_rect = match self.selection_rect {
+            Some(Rect { position, size }) => Array::of4(
+                &position.x.into(),
+                &position.y.into(),
+                &size.width.into(),
+                &size.height.into(),
+            )
+            .into(),
+            None => JsValue::NULL,
+        };
+
+        let edges = Float32Array::from(self.edges.as_slice());
+        let nodes = Float32Array::from(self.nodes.as_slice());
+        let handles = Float32Array::from(self.handles.as_slice());
+
+        let msg = Object::new();
+        for (key, value) in [
+            ("type", JsValue::from_str(TYPE_FRAME)),
+            ("width", self.width.into()),
+            ("height", self.height.into()),
+            ("dpi", self.frame.dpi.into()),
+            ("offsetX", self.frame.viewport.offset.x.into()),
+            ("offsetY", self.frame.viewport.offset.y.into()),
+            ("scale", self.frame.viewport.scale.into()),
+            ("edges", edges.clone().into()),
+            ("nodes", nodes.clone().into()),
+            ("labels", self.labels.clone().into()),
+            ("handles", handles.clone().into()),
+            ("font", self.label_style.font.as_str().into()),
+            ("color", self.label_style.color.as_str().into()),
+            ("lineHeight", self.label_style.line_height.into()),
+            ("selectionRect", selection_rect),
+            ("pendingEdge", pending_edge),
+        ] {
+            Reflect::set(&msg, &key.into(), &value)?;
+        }
+
+        self.worker.post_message_with_transfer(
+            &msg,
+            &Array::of3(&edges.buffer(), &nodes.buffer(), &handles.buffer()),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Run the rendering side, must be called from inside the worker.
+///
+/// This registers a message handler for the lifetime of the worker.
+pub fn worker_main() -> Result<(), Error> {
+    let scope = js_sys::global().dyn_into::<DedicatedWorkerGlobalScope>()?;
+
+    let state = Rc::new(RefCell::new(None));
+
+    EventListener::new(&scope, "message", move |evt| {
+        if let Some(evt) = evt.dyn_ref::<MessageEvent>() {
+            if let Err(err) = handle_message(&state, &evt.data()) {
+                log::warn!("Failed to handle message: {err}");
+            }
+        }
+    })
+    .forget();
+
+    Ok(())
+}
+
+type WorkerState = Rc<RefCell<Option<(OffscreenCanvas, OffscreenCanvasRenderingContext2d)>>>;
+
+fn handle_message(state: &WorkerState, msg: &JsValue) -> Result<(), Error> {
+    match Reflect::get(msg, &"type".into())?.as_string().as_deref() {
+        Some(TYPE_INIT) => {
+            let canvas = Reflect::get(msg, &"canvas".into())?.dyn_into::<OffscreenCanvas>()?;
+            let ctx = canvas
+                .get_context("2d")?
+                .ok_or(Error::MissingContext)?
+                .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+            *state.borrow_mut() = Some((canvas, ctx));
+        }
+        Some(TYPE_FRAME) => {
+            if let Some((canvas, ctx)) = state.borrow().as_ref() {
+                draw_frame(canvas, ctx, msg)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn draw_frame(
+    canvas: &OffscreenCanvas,
+    ctx: &OffscreenCanvasRenderingContext2d,
+    msg: &JsValue,
+) -> Result<(), Error> {
+    let get = |key: &str| Reflect::get(msg, &key.into());
+    let number = |key: &str| get(key).map(|v| v.as_f64().unwrap_or_default());
+
+    let width = number("width")? as u32;
+    let height = number("height")? as u32;
+    if canvas.width() != width || canvas.height() != height {
+        canvas.set_width(width);
+        canvas.set_height(height);
+    }
+
+    let dpi = number("dpi")?;
+    let (offset_x, offset_y, scale) = (number("offsetX")?, number("offsetY")?, number("scale")?);
+    let edges = get("edges")?.dyn_into::<Float32Array>()?.to_vec();
+    let nodes = get("nodes")?.dyn_into::<Float32Array>()?.to_vec();
+    let labels = get("labels")?.dyn_into::<Array>()?;
+    let font = get("font")?.as_string().unwrap_or_default();
+    let color = get("color")?.as_string().unwrap_or_default();
+    let line_height = number("lineHeight")?;
+
+    ctx.clear_rect(0.0, 0.0, width as _, height as _);
+
+    ctx.save();
+    ctx.scale(dpi, dpi)?;
+    ctx.translate(offset_x, offset_y)?;
+    ctx.scale(scale, scale)?;
+
+    for edge in edges.chunks_exact(EDGE_STRIDE) {
+        ctx.set_stroke_style_str(match edge[4] > 0.0 {
+            true => SELECTED_NODE_FILL,
+            false => EDGE_STROKE,
+        });
+        ctx.set_global_alpha(edge[5] as _);
+        ctx.begin_path();
+        ctx.move_to(edge[0] as _, edge[1] as _);
+        ctx.line_to(edge[2] as _, edge[3] as _);
+        ctx.stroke();
+    }
+    ctx.set_stroke_style_str(EDGE_STROKE);
+
+    for (i, node) in nodes.chunks_exact(NODE_STRIDE).enumerate() {
+        let [x, y, w, h, hovered, selected, opacity] = [
+            node[0], node[1], node[2], node[3], node[4], node[5], node[6],
+        ]
+        .map(f64::from);
+
+        ctx.set_global_alpha(opacity);
+        ctx.set_fill_style_str(match selected > 0.0 {
+            true => SELECTED_NODE_FILL,
+            false => NODE_FILL,
+        });
+        ctx.begin_path();
+        ctx.fill_rect(x, y, w, h);
+        ctx.set_line_width(if hovered > 0.0 { 5.0 } else { 1.0 });
+        ctx.rect(x, y, w, h);
+        ctx.stroke();
+
+        let lines = labels.get(i as u32).dyn_into::<Array>()?;
+        if lines.length() > 0 {
+            ctx.save();
+            ctx.set_font(&font);
+            ctx.set_fill_style_str(&color);
+            ctx.set_text_align("center");
+            ctx.set_text_baseline("middle");
+
+            let top = y + h / 2.0 - (lines.length() - 1) as f64 * line_height / 2.0;
+            for (n, line) in lines.iter().enumerate() {
+                let line = line.as_string().unwrap_or_default();
+                ctx.fill_text(&line, x + w / 2.0, top + n as f64 * line_height)?;
+            }
+
+            ctx.restore();
+        }
+    }
+    ctx.set_global_alpha(1.0);
+
+    let handles = get("handles")?.dyn_into::<Float32Array>()?.to_vec();
+    for handle in handles.chunks_exact(4) {
+        let [x, y, w, h] = [handle[0], handle[1], handle[2], handle[3]].map(f64::from);
+        ctx.set_fill_style_str("white");
+        ctx.fill_rect(x, y, w, h);
+        ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+        ctx.set_line_width(1.0 / scale);
+        ctx.stroke_rect(x, y, w, h);
+    }
+
+    if let Ok(rect) = get("selectionRect")?.dyn_into::<Array>() {
+        let [x, y, w, h] = [0, 1, 2, 3].map(|i| rect.get(i).as_f64().unwrap_or_default());
+        ctx.set_fill_style_str(SELECTION_RECT_FILL);
+        ctx.fill_rect(x, y, w, h);
+        ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+        ctx.set_line_width(1.0 / scale);
+        ctx.stroke_rect(x, y, w, h);
+    }
+
+    if let Ok(edge) = get("pendingEdge")?.dyn_into::<Array>() {
+        let [x1, y1, x2, y2] = [0, 1, 2, 3].map(|i| edge.get(i).as_f64().unwrap_or_default());
+        ctx.set_stroke_style_str(SELECTED_NODE_FILL);
+        ctx.set_line_width(2.0 / scale);
+        ctx.begin_path();
+        ctx.move_to(x1, y1);
+        ctx.line_to(x2, y2);
+        ctx.stroke();
+    }
+
+    ctx.restore();
+
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use super::{Detail, EdgeView, Frame, NodeView, Renderer, SELECTED_NODE_FILL, SELECTION_RECT_FILL};
+use crate::graph::{document, Error, Node, Position, Rect};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use web_sys::Element;
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+/// The attribute carrying the node ID on the SVG elements of a node.
+pub const NODE_ATTRIBUTE: &str = "data-node";
+/// The attributes carrying the node IDs on the SVG element of an edge.
+pub const EDGE_FROM_ATTRIBUTE: &str = "data-edge-from";
+pub const EDGE_TO_ATTRIBUTE: &str = "data-edge-to";
+
+/// A renderer using SVG elements.
+///
+/// Each node and edge is represented by its own element, which is kept across frames. Elements
+/// carry data attributes ([`NODE_ATTRIBUTE`], [`EDGE_FROM_ATTRIBUTE`], [`EDGE_TO_ATTRIBUTE`]),
+/// so that applications can attach DOM event listeners to them. The output stays crisp at any
+/// zoom level, and can be exported using [`SvgRenderer::to_svg`].
+pub struct SvgRenderer {
+    svg: Element,
+    edge_group: Element,
+    node_group: Element,
+
+    nodes: HashMap<Node, SvgNode>,
+    edges: HashMap<(Node, Node), Element>,
+
+    seen_nodes: HashSet<Node>,
+    seen_edges: HashSet<(Node, Node)>,
+
+    /// Elements for resize handles, the first `seen_handles` are in use
+    handles: Vec<Element>,
+    seen_handles: usize,
+    /// The selection rectangle, while it is drawn
+    selection_rect: Option<Element>,
+    seen_selection_rect: bool,
+    /// The edge being created, while it is drawn
+    pending_edge: Option<Element>,
+    seen_pending_edge: bool,
+
+    frame: Frame,
+}
+
+struct SvgNode {
+    group: Element,
+    rect: Element,
+    text: Element,
+    lines: Vec<String>,
+}
+
+impl SvgRenderer {
+    /// Create a new renderer, drawing into the `<svg>` element.
+    pub fn new(svg: Element) -> Result<Self, Error> {
+        let edge_group = create_element("g")?;
+        let node_group = create_element("g")?;
+        svg.append_child(&edge_group)?;
+        svg.append_child(&node_group)?;
+
+        Ok(Self {
+            svg,
+            edge_group,
+            node_group,
+            nodes: Default::default(),
+            edges: Default::default(),
+            seen_nodes: Default::default(),
+            seen_edges: Default::default(),
+            handles: Default::default(),
+            seen_handles: 0,
+            selection_rect: None,
+            seen_selection_rect: false,
+            pending_edge: None,
+            seen_pending_edge: false,
+            frame: Default::default(),
+        })
+    }
+
+    /// The SVG document, as it was last rendered.
+    pub fn to_svg(&self) -> String {
+        let svg = self.svg.outer_html();
+        if svg.contains("xmlns=") {
+            svg
+        } else {
+            svg.replacen("<svg", &format!(r#"<svg xmlns="{SVG_NS}""#), 1)
+        }
+    }
+
+    fn node(&mut self, node: Node) -> Result<&mut SvgNode, Error> {
+        Ok(match self.nodes.entry(node) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let group = create_element("g")?;
+                group.set_attribute(NODE_ATTRIBUTE, &node.id.to_string())?;
+
+                let rect = create_element("rect")?;
+                rect.set_attribute("stroke", "black")?;
+                group.append_child(&rect)?;
+
+                let text = create_element("text")?;
+                text.set_attribute("text-anchor", "middle")?;
+                text.set_attribute("dominant-baseline", "middle")?;
+                group.append_child(&text)?;
+
+                entry.insert(SvgNode {
+                    group,
+                    rect,
+                    text,
+                    lines: vec![],
+                })
+            }
+        })
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn begin_frame(&mut self, frame: &Frame) -> Result<(), Error> {
+        let viewport = frame.viewport;
+        let transform = format!(
+            "translate({} {}) scale({})",
+            viewport.offset.x, viewport.offset.y, viewport.scale
+        );
+        self.edge_group.set_attribute("transform", &transform)?;
+        self.node_group.set_attribute("transform", &transform)?;
+
+        self.frame = *frame;
+        self.seen_nodes.clear();
+        self.seen_edges.clear();
+        self.seen_handles = 0;
+        self.seen_selection_rect = false;
+        self.seen_pending_edge = false;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        // elements are kept and updated, stale ones get removed at the end of the frame
+        Ok(())
+    }
+
+    fn draw_edge(&mut self, edge: &EdgeView) -> Result<(), Error> {
+        let key = (edge.from, edge.to);
+
+        let line = match self.edges.get(&key) {
+            Some(line) => line,
+            None => {
+                let line = create_element("polyline")?;
+                line.set_attribute(EDGE_FROM_ATTRIBUTE, &edge.from.id.to_string())?;
+                line.set_attribute(EDGE_TO_ATTRIBUTE, &edge.to.id.to_string())?;
+                line.set_attribute("fill", "none")?;
+                self.edge_group.append_child(&line)?;
+                self.edges.entry(key).or_insert(line)
+            }
+        };
+
+        let points: Vec<String> = edge
+            .points()
+            .map(|Position { x, y }| format!("{x},{y}"))
+            .collect();
+        line.set_attribute("points", &points.join(" "))?;
+        line.set_attribute("stroke", edge.stroke())?;
+        set_number(line, "opacity", edge.opacity)?;
+
+        self.seen_edges.insert(key);
+
+        Ok(())
+    }
+
+    fn draw_node(&mut self, node: &NodeView) -> Result<(), Error> {
+        let node_group = self.node_group.clone();
+        let frame = self.frame;
+        let svg_node = self.node(node.node)?;
+        set_number(&svg_node.group, "opacity", node.opacity)?;
+
+        if frame.detail == Detail::Reduced {
+            // a dot is a rect with fully rounded corners
+            let radius = node.dot_radius(&frame);
+            let center = node.center();
+            set_number(&svg_node.rect, "x", center.x - radius)?;
+            set_number(&svg_node.rect, "y", center.y - radius)?;
+            set_number(&svg_node.rect, "width", radius * 2.0)?;
+            set_number(&svg_node.rect, "height", radius * 2.0)?;
+            set_number(&svg_node.rect, "rx", radius)?;
+            svg_node.rect.set_attribute("stroke-width", "0")?;
+            svg_node.rect.set_attribute("fill", node.fill())?;
+            svg_node.text.set_attribute("display", "none")?;
+
+            node_group.append_child(&svg_node.group)?;
+            self.seen_nodes.insert(node.node);
+
+            return Ok(());
+        }
+
+        set_number(&svg_node.rect, "x", node.position.x)?;
+        set_number(&svg_node.rect, "y", node.position.y)?;
+        set_number(&svg_node.rect, "width", node.size.width)?;
+        set_number(&svg_node.rect, "height", node.size.height)?;
+        set_number(&svg_node.rect, "rx", 0.0)?;
+        svg_node
+            .rect
+            .set_attribute("stroke-width", if node.hovered { "5" } else { "1" })?;
+        svg_node.rect.set_attribute("fill", node.fill())?;
+        svg_node.text.remove_attribute("display")?;
+
+        let style = node.label_style;
+        let center = node.center();
+        let lines = node.label.lines();
+
+        if svg_node.lines != lines {
+            svg_node.text.set_inner_html("");
+            for line in lines {
+                let tspan = create_element("tspan")?;
+                tspan.set_text_content(Some(line));
+                svg_node.text.append_child(&tspan)?;
+            }
+            svg_node.lines = lines.to_vec();
+        }
+
+        svg_node
+            .text
+            .set_attribute("style", &format!("font: {}", style.font))?;
+        svg_node.text.set_attribute("fill", &style.color)?;
+
+        let tspans = svg_node.text.children();
+        let top = center.y - (lines.len().max(1) - 1) as f64 * style.line_height / 2.0;
+        for i in 0..tspans.length() {
+            if let Some(tspan) = tspans.item(i) {
+                set_number(&tspan, "x", center.x)?;
+                set_number(&tspan, "y", top + i as f64 * style.line_height)?;
+            }
+        }
+
+        // (re-)appending moves the element to the end, keeping the z-order of the frame
+        node_group.append_child(&svg_node.group)?;
+
+        self.seen_nodes.insert(node.node);
+
+        Ok(())
+    }
+
+    fn draw_handle(&mut self, rect: &Rect) -> Result<(), Error> {
+        let element = match self.handles.get(self.seen_handles) {
+            Some(element) => element.clone(),
+            None => {
+                let element = create_element("rect")?;
+                element.set_attribute("fill", "white")?;
+                element.set_attribute("stroke", SELECTED_NODE_FILL)?;
+                element.set_attribute("vector-effect", "non-scaling-stroke")?;
+                self.handles.push(element.clone());
+                element
+            }
+        };
+
+        set_number(&element, "x", rect.position.x)?;
+        set_number(&element, "y", rect.position.y)?;
+        set_number(&element, "width", rect.size.width)?;
+        set_number(&element, "height", rect.size.height)?;
+
+        // on top of all nodes
+        self.node_group.append_child(&element)?;
+        self.seen_handles += 1;
+
+        Ok(())
+    }
+
+    fn draw_selection_rect(&mut self, rect: &Rect) -> Result<(), Error> {
+        let element = match &self.selection_rect {
+            Some(element) => element.clone(),
+            None => {
+                let element = create_element("rect")?;
+                element.set_attribute("fill", SELECTION_RECT_FILL)?;
+                element.set_attribute("stroke", SELECTED_NODE_FILL)?;
+                element.set_attribute("vector-effect", "non-scaling-stroke")?;
+                self.selection_rect = Some(element.clone());
+                element
+            }
+        };
+
+        set_number(&element, "x", rect.position.x)?;
+        set_number(&element, "y", rect.position.y)?;
+        set_number(&element, "width", rect.size.width)?;
+        set_number(&element, "height", rect.size.height)?;
+
+        // on top of all nodes
+        self.node_group.append_child(&element)?;
+        self.seen_selection_rect = true;
+
+        Ok(())
+    }
+
+    fn draw_pending_edge(&mut self, from: Position, to: Position) -> Result<(), Error> {
+        let element = match &self.pending_edge {
+            Some(element) => element.clone(),
+            None => {
+                let element = create_element("line")?;
+                element.set_attribute("stroke", SELECTED_NODE_FILL)?;
+                element.set_attribute("stroke-width", "2")?;
+                element.set_attribute("vector-effect", "non-scaling-stroke")?;
+                self.pending_edge = Some(element.clone());
+                element
+            }
+        };
+
+        set_number(&element, "x1", from.x)?;
+        set_number(&element, "y1", from.y)?;
+        set_number(&element, "x2", to.x)?;
+        set_number(&element, "y2", to.y)?;
+
+        // on top of all nodes
+        self.node_group.append_child(&element)?;
+        self.seen_pending_edge = true;
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<(), Error> {
+        for element in self.handles.drain(self.seen_handles..) {
+            element.remove();
+        }
+        if !self.seen_selection_rect {
+            if let Some(element) = self.selection_rect.take() {
+                element.remove();
+            }
+        }
+        if !self.seen_pending_edge {
+            if let Some(element) = self.pending_edge.take() {
+                element.remove();
+            }
+        }
+
+        let seen_nodes = &self.seen_nodes;
+        self.nodes.retain(|node, svg_node| {
+            let keep = seen_nodes.contains(node);
+            if !keep {
+                svg_node.group.remove();
+            }
+            keep
+        });
+
+        let seen_edges = &self.seen_edges;
+        self.edges.retain(|edge, line| {
+            let keep = seen_edges.contains(edge);
+            if !keep {
+                line.remove();
+            }
+            keep
+        });
+
+        Ok(())
+    }
+}
+
+fn create_element(name: &str) -> Result<Element, Error> {
+    Ok(document()?.create_element_ns(Some(SVG_NS), name)?)
+}
+
+fn set_number(element: &Element, name: &str, value: f64) -> Result<(), Error> {
+    Ok(element.set_attribute(name, &value.to_string())?)
+}
@@ -0,0 +1,348 @@
+use super::{Detail, EdgeView, Frame, NodeView, Renderer};
+use crate::graph::Error;
+use js_sys::Float32Array;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlShader,
+    WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+const EDGE_VERTEX_SHADER: &str = r#"#version 300 es
+uniform vec2 u_resolution;
+uniform vec3 u_viewport;
+in vec2 a_position;
+in float a_opacity;
+out float v_opacity;
+void main() {
+    vec2 screen = a_position * u_viewport.z + u_viewport.xy;
+    vec2 clip = screen / u_resolution * 2.0 - 1.0;
+    gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+    v_opacity = a_opacity;
+}
+"#;
+
+const EDGE_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+in float v_opacity;
+out vec4 color;
+void main() {
+    color = vec4(0.0, 0.0, 0.0, v_opacity);
+}
+"#;
+
+const NODE_VERTEX_SHADER: &str = r#"#version 300 es
+uniform vec2 u_resolution;
+uniform vec3 u_viewport;
+in vec2 a_corner;
+in vec4 a_rect;
+in vec4 a_color;
+out vec4 v_color;
+void main() {
+    vec2 position = a_rect.xy + a_corner * a_rect.zw;
+    vec2 screen = position * u_viewport.z + u_viewport.xy;
+    vec2 clip = screen / u_resolution * 2.0 - 1.0;
+    gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+    v_color = a_color;
+}
+"#;
+
+const NODE_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+in vec4 v_color;
+out vec4 color;
+void main() {
+    color = v_color;
+}
+"#;
+
+const NODE_COLOR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const NODE_HOVER_COLOR: [f32; 4] = [0.6, 0.0, 0.0, 1.0];
+const NODE_SELECTED_COLOR: [f32; 4] = [0.25, 0.41, 0.88, 1.0];
+const NODE_SELECTED_HOVER_COLOR: [f32; 4] = [0.15, 0.25, 0.53, 1.0];
+
+/// A renderer using WebGL 2, for large graphs.
+///
+/// Nodes are drawn as instanced quads and edges as a single batch of lines. All geometry is
+/// collected during the frame and submitted in [`Renderer::end_frame`].
+///
+/// Labels, resize handles, the selection rectangle, and pending edges are not rendered by this
+/// backend. Selected edges are drawn like all other edges, and the colors of categories are
+/// ignored.
+pub struct WebGlRenderer {
+    canvas: HtmlCanvasElement,
+    gl: Gl,
+
+    edge_program: Program,
+    edge_vao: WebGlVertexArrayObject,
+    edge_buffer: WebGlBuffer,
+
+    node_program: Program,
+    node_vao: WebGlVertexArrayObject,
+    node_buffer: WebGlBuffer,
+
+    frame: Frame,
+    edges: Vec<f32>,
+    nodes: Vec<f32>,
+}
+
+struct Program {
+    program: WebGlProgram,
+    resolution: Option<WebGlUniformLocation>,
+    /// Offset (x, y) and scale (z) of the viewport
+    viewport: Option<WebGlUniformLocation>,
+}
+
+impl WebGlRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Result<Self, Error> {
+        let gl = canvas
+            .get_context("webgl2")?
+            .ok_or(Error::MissingContext)?
+            .dyn_into::<Gl>()?;
+
+        let edge_program = Program::new(&gl, EDGE_VERTEX_SHADER, EDGE_FRAGMENT_SHADER)?;
+        let node_program = Program::new(&gl, NODE_VERTEX_SHADER, NODE_FRAGMENT_SHADER)?;
+
+        // edges: one buffer of line segments
+
+        let edge_vao = create_vao(&gl)?;
+        let edge_buffer = create_buffer(&gl)?;
+        gl.bind_vertex_array(Some(&edge_vao));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&edge_buffer));
+        edge_program.attribute(&gl, "a_position", 2, 3 * 4, 0, 0);
+        edge_program.attribute(&gl, "a_opacity", 1, 3 * 4, 2 * 4, 0);
+
+        // nodes: a static unit quad, plus one instance (rect + color) per node
+
+        let node_vao = create_vao(&gl)?;
+        let quad_buffer = create_buffer(&gl)?;
+        let node_buffer = create_buffer(&gl)?;
+        gl.bind_vertex_array(Some(&node_vao));
+
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&quad_buffer));
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &Float32Array::from(&[0.0f32, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0][..]),
+            Gl::STATIC_DRAW,
+        );
+        node_program.attribute(&gl, "a_corner", 2, 0, 0, 0);
+
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&node_buffer));
+        node_program.attribute(&gl, "a_rect", 4, 8 * 4, 0, 1);
+        node_program.attribute(&gl, "a_color", 4, 8 * 4, 4 * 4, 1);
+
+        gl.bind_vertex_array(None);
+
+        Ok(Self {
+            canvas,
+            gl,
+            edge_program,
+            edge_vao,
+            edge_buffer,
+            node_program,
+            node_vao,
+            node_buffer,
+            frame: Default::default(),
+            edges: Vec::new(),
+            nodes: Vec::new(),
+        })
+    }
+
+    fn resolution(&self) -> (f32, f32) {
+        (
+            (self.canvas.width() as f64 / self.frame.dpi) as f32,
+            (self.canvas.height() as f64 / self.frame.dpi) as f32,
+        )
+    }
+}
+
+impl Renderer for WebGlRenderer {
+    fn resize(&mut self, width: u32, height: u32) {
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+    }
+
+    fn begin_frame(&mut self, frame: &Frame) -> Result<(), Error> {
+        self.frame = *frame;
+        self.edges.clear();
+        self.nodes.clear();
+
+        self.gl
+            .viewport(0, 0, self.canvas.width() as _, self.canvas.height() as _);
+        // for faded nodes and edges
+        self.gl.enable(Gl::BLEND);
+        self.gl.blend_func(Gl::SRC_ALPHA, Gl::ONE_MINUS_SRC_ALPHA);
+
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        self.gl.clear(Gl::COLOR_BUFFER_BIT);
+        Ok(())
+    }
+
+    fn draw_edge(&mut self, edge: &EdgeView) -> Result<(), Error> {
+        let opacity = edge.opacity as f32;
+        for (from, to) in edge.segments() {
+            self.edges.extend([
+                from.x as f32,
+                from.y as f32,
+                opacity,
+                to.x as f32,
+                to.y as f32,
+                opacity,
+            ]);
+        }
+        Ok(())
+    }
+
+    fn draw_node(&mut self, node: &NodeView) -> Result<(), Error> {
+        match self.frame.detail {
+            Detail::Full => self.nodes.extend([
+                node.position.x as f32,
+                node.position.y as f32,
+                node.size.width as f32,
+                node.size.height as f32,
+            ]),
+            Detail::Reduced => {
+                let radius = node.dot_radius(&self.frame);
+                let center = node.center();
+                self.nodes.extend([
+                    (center.x - radius) as f32,
+                    (center.y - radius) as f32,
+                    (radius * 2.0) as f32,
+                    (radius * 2.0) as f32,
+                ]);
+            }
+        }
+        let [r, g, b, a] = match (node.selected, node.hovered) {
+            (false, false) => NODE_COLOR,
+            (false, true) => NODE_HOVER_COLOR,
+            (true, false) => NODE_SELECTED_COLOR,
+            (true, true) => NODE_SELECTED_HOVER_COLOR,
+        };
+        self.nodes.extend([r, g, b, a * node.opacity as f32]);
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<(), Error> {
+        let gl = &self.gl;
+        let (width, height) = self.resolution();
+        let viewport = self.frame.viewport;
+        let (x, y, scale) = (
+            viewport.offset.x as f32,
+            viewport.offset.y as f32,
+            viewport.scale as f32,
+        );
+
+        // edges
+
+        gl.use_program(Some(&self.edge_program.program));
+        gl.uniform2f(self.edge_program.resolution.as_ref(), width, height);
+        gl.uniform3f(self.edge_program.viewport.as_ref(), x, y, scale);
+        gl.bind_vertex_array(Some(&self.edge_vao));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.edge_buffer));
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &Float32Array::from(self.edges.as_slice()),
+            Gl::DYNAMIC_DRAW,
+        );
+        gl.draw_arrays(Gl::LINES, 0, (self.edges.len() / 3) as _);
+
+        // nodes
+
+        gl.use_program(Some(&self.node_program.program));
+        gl.uniform2f(self.node_program.resolution.as_ref(), width, height);
+        gl.uniform3f(self.node_program.viewport.as_ref(), x, y, scale);
+        gl.bind_vertex_array(Some(&self.node_vao));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.node_buffer));
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &Float32Array::from(self.nodes.as_slice()),
+            Gl::DYNAMIC_DRAW,
+        );
+        gl.draw_arrays_instanced(Gl::TRIANGLE_STRIP, 0, 4, (self.nodes.len() / 8) as _);
+
+        gl.bind_vertex_array(None);
+
+        Ok(())
+    }
+}
+
+impl Program {
+    fn new(gl: &Gl, vertex: &str, fragment: &str) -> Result<Self, Error> {
+        let vertex = compile_shader(gl, Gl::VERTEX_SHADER, vertex)?;
+        let fragment = compile_shader(gl, Gl::FRAGMENT_SHADER, fragment)?;
+
+        let program = gl
+            .create_program()
+            .ok_or_else(|| Error::Web("unable to create program".into()))?;
+        gl.attach_shader(&program, &vertex);
+        gl.attach_shader(&program, &fragment);
+        gl.link_program(&program);
+
+        if !gl
+            .get_program_parameter(&program, Gl::LINK_STATUS)
+            .as_bool()
+            .unwrap_or_default()
+        {
+            return Err(Error::Web(
+                gl.get_program_info_log(&program)
+                    .unwrap_or_else(|| "unable to link program".into()),
+            ));
+        }
+
+        let resolution = gl.get_uniform_location(&program, "u_resolution");
+        let viewport = gl.get_uniform_location(&program, "u_viewport");
+
+        Ok(Self {
+            program,
+            resolution,
+            viewport,
+        })
+    }
+
+    /// Set up a float attribute from the currently bound array buffer.
+    fn attribute(&self, gl: &Gl, name: &str, size: i32, stride: i32, offset: i32, divisor: u32) {
+        let location = gl.get_attrib_location(&self.program, name);
+        if location < 0 {
+            return;
+        }
+        let location = location as u32;
+
+        gl.enable_vertex_attrib_array(location);
+        gl.vertex_attrib_pointer_with_i32(location, size, Gl::FLOAT, false, stride, offset);
+        gl.vertex_attrib_divisor(location, divisor);
+    }
+}
+
+fn compile_shader(gl: &Gl, r#type: u32, source: &str) -> Result<WebGlShader, Error> {
+    let shader = gl
+        .create_shader(r#type)
+        .ok_or_else(|| Error::Web("unable to create shader".into()))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or_default()
+    {
+        Ok(shader)
+    } else {
+        Err(Error::Web(
+            gl.get_shader_info_log(&shader)
+                .unwrap_or_else(|| "unable to compile shader".into()),
+        ))
+    }
+}
+
+fn create_vao(gl: &Gl) -> Result<WebGlVertexArrayObject, Error> {
+    gl.create_vertex_array()
+        .ok_or_else(|| Error::Web("unable to create vertex array".into()))
+}
+
+fn create_buffer(gl: &Gl) -> Result<WebGlBuffer, Error> {
+    gl.create_buffer()
+        .ok_or_else(|| Error::Web("unable to create buffer".into()))
+}
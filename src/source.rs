@@ -0,0 +1,187 @@
+//! Streams of changes from a live backend, applied to a running graph.
+//!
+//! A [`GraphSource`] sends [`crate::bridge::GraphMessage`]s as they arrive, and
+//! [`connect_source`] applies them through a [`GraphBridge`]. With the `websocket` feature,
+//! [`WebSocketSource`] receives them as JSON.
+
+use crate::bridge::{GraphBridge, GraphBridgeHandle, GraphSender};
+use crate::graph::{Error, GraphController, Node};
+use std::hash::Hash;
+
+#[cfg(feature = "websocket")]
+pub use websocket::*;
+
+/// Produces the messages changing a graph, e.g. received from a backend.
+pub trait GraphSource {
+    /// The keys of the backend, identifying the nodes.
+    type Key: Clone + Eq + Hash + 'static;
+    /// Keeps producing messages, until dropped.
+    type Connection;
+
+    /// Start sending messages to the sender.
+    fn connect(self, sender: GraphSender<Self::Key>) -> Result<Self::Connection, Error>;
+}
+
+/// Apply the messages of a source to a running graph, until the returned handle is dropped.
+pub fn connect_source<S: GraphSource>(
+    graph: GraphController,
+    source: S,
+) -> Result<GraphSourceHandle<S>, Error> {
+    let bridge = GraphBridge::new(graph).run();
+    let connection = source.connect(bridge.sender())?;
+    Ok(GraphSourceHandle { bridge, connection })
+}
+
+/// A connected [`GraphSource`], disconnected when dropped.
+pub struct GraphSourceHandle<S: GraphSource> {
+    bridge: GraphBridgeHandle<S::Key>,
+    connection: S::Connection,
+}
+
+impl<S: GraphSource> GraphSourceHandle<S> {
+    /// The node of a key, once the message adding it was applied.
+    pub fn node(&self, key: &S::Key) -> Option<Node> {
+        self.bridge.node(key)
+    }
+
+    pub fn connection(&self) -> &S::Connection {
+        &self.connection
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod websocket {
+    use super::GraphSource;
+    use crate::bridge::{GraphMessage, GraphSender};
+    use crate::graph::{EdgeProperties, Error, NodeProperties, NodeSize, Position, Size};
+    use gloo_events::EventListener;
+    use js_sys::{Array, Reflect, JSON};
+    use wasm_bindgen::{JsCast, JsValue};
+    use web_sys::{MessageEvent, WebSocket};
+
+    /// Receives messages as JSON text from a WebSocket.
+    ///
+    /// Each text message holds a message object, or an array of them:
+    ///
+    /// * `{"type": "node", "key": "a", "label": "A", "category": "c", "x": 0, "y": 0,
+    ///   "width": 80, "height": 40}`, adding or updating a node. All but the key are optional,
+    ///   the node fits its label without a size.
+    /// * `{"type": "removeNode", "key": "a"}`
+    /// * `{"type": "edge", "from": "a", "to": "b", "length": 100, "strength": 1,
+    ///   "category": "c"}`, with all but `from` and `to` being optional.
+    /// * `{"type": "removeEdge", "from": "a", "to": "b"}`
+    /// * `{"type": "clear"}`
+    ///
+    /// Invalid messages are logged and skipped.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct WebSocketSource {
+        url: String,
+    }
+
+    impl WebSocketSource {
+        pub fn new(url: impl Into<String>) -> Self {
+            Self { url: url.into() }
+        }
+    }
+
+    impl GraphSource for WebSocketSource {
+        type Key = String;
+        type Connection = WebSocketConnection;
+
+        fn connect(self, sender: GraphSender<String>) -> Result<WebSocketConnection, Error> {
+            let socket = WebSocket::new(&self.url)?;
+            let listener = EventListener::new(&socket, "message", move |evt| {
+                let Some(text) = evt
+                    .dyn_ref::<MessageEvent>()
+                    .and_then(|evt| evt.data().as_string())
+                else {
+                    return;
+                };
+                let Ok(value) = JSON::parse(&text) else {
+                    log::warn!("Ignoring a message which isn't JSON");
+                    return;
+                };
+                let values = match value.dyn_into::<Array>() {
+                    Ok(values) => values,
+                    Err(value) => Array::of1(&value),
+                };
+                for value in values.iter() {
+                    match read_message(&value) {
+                        Some(message) => sender.send(message),
+                        None => log::warn!("Ignoring an invalid message: {value:?}"),
+                    }
+                }
+            });
+
+            Ok(WebSocketConnection {
+                socket,
+                _listener: listener,
+            })
+        }
+    }
+
+    /// A connected [`WebSocketSource`], closing the socket when dropped.
+    pub struct WebSocketConnection {
+        socket: WebSocket,
+        _listener: EventListener,
+    }
+
+    impl WebSocketConnection {
+        pub fn socket(&self) -> &WebSocket {
+            &self.socket
+        }
+    }
+
+    impl Drop for WebSocketConnection {
+        fn drop(&mut self) {
+            let _ = self.socket.close();
+        }
+    }
+
+    fn get(value: &JsValue, name: &str) -> JsValue {
+        Reflect::get(value, &name.into()).unwrap_or(JsValue::UNDEFINED)
+    }
+
+    fn read_message(value: &JsValue) -> Option<GraphMessage<String>> {
+        let string = |name| get(value, name).as_string();
+        let number = |name| get(value, name).as_f64();
+
+        Some(match string("type")?.as_str() {
+            "node" => {
+                let size = match (number("width"), number("height")) {
+                    (Some(width), Some(height)) => NodeSize::Fixed(Size { width, height }),
+                    _ => NodeSize::FitLabel,
+                };
+                let position = match (number("x"), number("y")) {
+                    (Some(x), Some(y)) => Some(Position { x, y }),
+                    _ => None,
+                };
+                GraphMessage::Node {
+                    key: string("key")?,
+                    properties: NodeProperties {
+                        label: string("label").unwrap_or_default(),
+                        category: string("category"),
+                    },
+                    size,
+                    position,
+                }
+            }
+            "removeNode" => GraphMessage::RemoveNode(string("key")?),
+            "edge" => GraphMessage::Edge {
+                from: string("from")?,
+                to: string("to")?,
+                properties: EdgeProperties {
+                    length: number("length"),
+                    strength: number("strength").unwrap_or(EdgeProperties::default().strength),
+                    category: string("category"),
+                },
+            },
+            "removeEdge" => GraphMessage::RemoveEdge {
+                from: string("from")?,
+                to: string("to")?,
+            },
+            "clear" => GraphMessage::Clear,
+            _ => return None,
+        })
+    }
+}
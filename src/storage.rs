@@ -0,0 +1,276 @@
+//! The nodes and edges of a graph.
+
+use crate::graph::{EdgeProperties, Node, NodeProperties, Position, Rect, Size};
+use crate::label::Label;
+use crate::layout::Constraint;
+use std::cell::{Ref, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+
+/// Nodes and edges, with their positions.
+///
+/// This is what a [`crate::layout::Layout`] operates on.
+#[derive(Default)]
+pub struct GraphStorage {
+    pub(crate) counter: usize,
+    pub(crate) nodes: HashMap<Node, Rc<RefCell<NodeState>>>,
+
+    pub(crate) edges: HashMap<Node, HashMap<Node, Rc<EdgeState>>>,
+    pub(crate) edges_rev: HashMap<Node, HashMap<Node, Rc<EdgeState>>>,
+
+    /// Nodes currently held by the user, e.g. while dragging them
+    pub(crate) held: BTreeSet<Node>,
+
+    /// The area currently shown, if known
+    pub(crate) visible: Option<Rect>,
+
+    pub(crate) constraints: HashMap<Node, Vec<Constraint>>,
+}
+
+impl GraphStorage {
+    pub(crate) fn next_handle(&mut self) -> Node {
+        let handle = Node { id: self.counter };
+        self.counter += 1;
+        handle
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, node: Node) -> bool {
+        self.nodes.contains_key(&node)
+    }
+
+    /// All nodes, in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = Node> + '_ {
+        self.nodes.keys().copied()
+    }
+
+    /// All edges, in no particular order.
+    ///
+    /// Edges are undirected, each one is only reported once.
+    pub fn edges(&self) -> impl Iterator<Item = (Node, Node, &EdgeProperties)> + '_ {
+        self.edges.iter().flat_map(|(from, edges)| {
+            edges
+                .iter()
+                .map(move |(to, edge)| (*from, *to, &edge.properties))
+        })
+    }
+
+    /// All edges, as `(source, target)`, in the direction they were created.
+    pub fn directed_edges(&self) -> impl Iterator<Item = (Node, Node, &EdgeProperties)> + '_ {
+        self.edges.iter().flat_map(|(a, edges)| {
+            edges.iter().map(move |(b, edge)| match edge.source == *a {
+                true => (*a, *b, &edge.properties),
+                false => (*b, *a, &edge.properties),
+            })
+        })
+    }
+
+    /// The center of all nodes, if there are any.
+    pub fn centroid(&self) -> Option<Position> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let (x, y) = self.nodes.values().fold((0.0, 0.0), |(x, y), state| {
+            let center = state.borrow().center();
+            (x + center.x, y + center.y)
+        });
+        let n = self.nodes.len() as f64;
+
+        Some(Position { x: x / n, y: y / n })
+    }
+
+    /// The area covered by all nodes, if there are any.
+    pub fn bounds(&self) -> Option<Rect> {
+        self.nodes
+            .values()
+            .map(|state| state.borrow().rect())
+            .reduce(|a, b| {
+                let top_left = Position {
+                    x: a.position.x.min(b.position.x),
+                    y: a.position.y.min(b.position.y),
+                };
+                let bottom_right = Position {
+                    x: a.right().max(b.right()),
+                    y: a.bottom().max(b.bottom()),
+                };
+                Rect::spanning(top_left, bottom_right)
+            })
+    }
+
+    /// All nodes connected to this node.
+    pub fn neighbors(&self, node: Node) -> impl Iterator<Item = Node> + '_ {
+        let forward = self.edges.get(&node).into_iter().flat_map(|e| e.keys());
+        let backward = self.edges_rev.get(&node).into_iter().flat_map(|e| e.keys());
+        forward.chain(backward).copied()
+    }
+
+    pub fn properties(&self, node: Node) -> Option<Ref<'_, NodeProperties>> {
+        self.nodes
+            .get(&node)
+            .map(|state| Ref::map(state.borrow(), |state| &state.properties))
+    }
+
+    /// The state of the edge between two nodes, and if it is stored in the opposite direction.
+    pub(crate) fn edge(&self, a: Node, b: Node) -> Option<(&EdgeState, bool)> {
+        let (first, second, reversed) = match a <= b {
+            true => (a, b, false),
+            false => (b, a, true),
+        };
+        self.edges
+            .get(&first)?
+            .get(&second)
+            .map(|state| (&**state, reversed))
+    }
+
+    /// Remove a node, along with its edges and constraints.
+    ///
+    /// Returns `false` if the node didn't exist.
+    pub(crate) fn remove_node(&mut self, node: Node) -> bool {
+        if self.nodes.remove(&node).is_none() {
+            return false;
+        }
+
+        for other in self
+            .edges
+            .remove(&node)
+            .into_iter()
+            .flat_map(|e| e.into_keys())
+        {
+            if let Some(edges) = self.edges_rev.get_mut(&other) {
+                edges.remove(&node);
+            }
+        }
+        for other in self
+            .edges_rev
+            .remove(&node)
+            .into_iter()
+            .flat_map(|e| e.into_keys())
+        {
+            if let Some(edges) = self.edges.get_mut(&other) {
+                edges.remove(&node);
+            }
+        }
+        self.held.remove(&node);
+        self.constraints.remove(&node);
+
+        true
+    }
+
+    /// Remove the edge between two nodes.
+    ///
+    /// Returns `false` if there was no such edge.
+    pub(crate) fn remove_edge(&mut self, a: Node, b: Node) -> bool {
+        let (a, b) = (a.min(b), a.max(b));
+        let removed = self
+            .edges
+            .get_mut(&a)
+            .and_then(|edges| edges.remove(&b))
+            .is_some();
+        if let Some(edges) = self.edges_rev.get_mut(&b) {
+            edges.remove(&a);
+        }
+        removed
+    }
+
+    /// The points of the line drawn for an edge: the centers of both nodes, with the waypoints
+    /// in between.
+    pub fn edge_points(&self, a: Node, b: Node) -> Option<Vec<Position>> {
+        let (state, reversed) = self.edge(a, b)?;
+        let mut waypoints = state.waypoints.borrow().clone();
+        if reversed {
+            waypoints.reverse();
+        }
+
+        let mut points = vec![self.center(a)?];
+        points.extend(waypoints);
+        points.push(self.center(b)?);
+        Some(points)
+    }
+
+    /// The center of a node.
+    pub fn center(&self, node: Node) -> Option<Position> {
+        self.nodes.get(&node).map(|state| state.borrow().center())
+    }
+
+    /// Move a node, so that its center is at the position.
+    pub fn set_center(&mut self, node: Node, position: Position) {
+        if let Some(state) = self.nodes.get(&node) {
+            state.borrow_mut().set_centered(position);
+        }
+    }
+
+    pub fn size(&self, node: Node) -> Option<Size> {
+        self.nodes.get(&node).map(|state| state.borrow().size)
+    }
+
+    /// The area covered by a node.
+    pub fn rect(&self, node: Node) -> Option<Rect> {
+        self.nodes.get(&node).map(|state| state.borrow().rect())
+    }
+
+    /// The area of the graph currently shown, if it is being displayed.
+    pub fn visible_rect(&self) -> Option<Rect> {
+        self.visible
+    }
+
+    /// The constraints of a node, in the order they were added.
+    pub fn constraints(&self, node: Node) -> &[Constraint] {
+        self.constraints
+            .get(&node)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// If the node must not be moved by a layout, e.g. because the user is dragging it.
+    pub fn is_fixed(&self, node: Node) -> bool {
+        self.held.contains(&node)
+    }
+}
+
+pub(crate) struct EdgeState {
+    pub(crate) properties: EdgeProperties,
+    /// The node the edge was created from
+    pub(crate) source: Node,
+    /// Points the edge passes through, ordered from the smaller node to the larger one
+    pub(crate) waypoints: RefCell<Vec<Position>>,
+}
+
+#[allow(dead_code)]
+pub(crate) struct NodeState {
+    pub(crate) properties: NodeProperties,
+    pub(crate) handle: Node,
+    pub(crate) position: Position,
+    pub(crate) size: Size,
+    pub(crate) fit_label: bool,
+    pub(crate) label: Label,
+    pub(crate) z_index: i32,
+}
+
+impl NodeState {
+    pub(crate) fn set_centered(&mut self, position: impl Into<Position>) {
+        let position = position.into();
+        self.position = Position {
+            x: position.x - self.size.width / 2.0,
+            y: position.y - self.size.height / 2.0,
+        }
+    }
+
+    pub(crate) fn center(&self) -> Position {
+        self.rect().center()
+    }
+
+    pub(crate) fn rect(&self) -> Rect {
+        Rect {
+            position: self.position,
+            size: self.size,
+        }
+    }
+}
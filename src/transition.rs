@@ -0,0 +1,118 @@
+use crate::graph::{Node, Position};
+use crate::storage::GraphStorage;
+use std::collections::HashMap;
+
+/// How the progress of a transition is mapped to the movement of the nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Start slow, end slow.
+    #[default]
+    EaseInOut,
+    /// Start fast, end slow.
+    EaseOut,
+}
+
+impl Easing {
+    /// Map the linear progress `t` (from `0` to `1`) to the eased progress.
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseInOut => match t < 0.5 {
+                true => 4.0 * t * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            },
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+}
+
+/// How nodes move to positions set by an arrangement, like [`crate::layout::CircularLayout`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// Duration of the movement, in seconds. Zero moves nodes instantly.
+    pub duration: f64,
+    pub easing: Easing,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self {
+            duration: 0.5,
+            easing: Default::default(),
+        }
+    }
+}
+
+impl Transition {
+    /// Move nodes instantly.
+    pub fn none() -> Self {
+        Self {
+            duration: 0.0,
+            ..Default::default()
+        }
+    }
+}
+
+/// A running transition of nodes, from their current to their target positions.
+pub(crate) struct Animation {
+    transition: Transition,
+    /// Start and target center of each moving node
+    nodes: HashMap<Node, (Position, Position)>,
+    elapsed: f64,
+}
+
+impl Animation {
+    pub(crate) fn new(
+        graph: &GraphStorage,
+        targets: HashMap<Node, Position>,
+        transition: Transition,
+    ) -> Self {
+        let nodes = targets
+            .into_iter()
+            .filter_map(|(node, target)| Some((node, (graph.center(node)?, target))))
+            .collect();
+
+        Self {
+            transition,
+            nodes,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Stop moving a node, e.g. because it gets dragged.
+    pub(crate) fn remove(&mut self, node: Node) {
+        self.nodes.remove(&node);
+    }
+
+    /// Move all nodes to their targets at once.
+    pub(crate) fn finish(self, graph: &mut GraphStorage) {
+        for (node, (_, target)) in self.nodes {
+            graph.set_center(node, target);
+        }
+    }
+
+    /// Advance by `dt` seconds, returns `true` once finished.
+    pub(crate) fn step(&mut self, graph: &mut GraphStorage, dt: f64) -> bool {
+        self.elapsed += dt;
+        let t = match self.transition.duration > 0.0 {
+            true => self.elapsed / self.transition.duration,
+            false => 1.0,
+        };
+        let progress = self.transition.easing.apply(t);
+
+        for (node, (start, target)) in &self.nodes {
+            graph.set_center(
+                *node,
+                Position {
+                    x: start.x + (target.x - start.x) * progress,
+                    y: start.y + (target.y - start.y) * progress,
+                },
+            );
+        }
+
+        t >= 1.0
+    }
+}
@@ -0,0 +1,98 @@
+//! Keeping the view of a graph in the fragment of the URL, so that views can be linked to.
+//!
+//! The view is kept as a [`ViewState`], in its `view` parameter, e.g. `#view=12.5,-40,1.5~a`.
+//! Other parameters of the fragment are kept. Selected nodes are identified by keys of the
+//! application, like with [`crate::graph::Graph::export_positions`].
+
+use crate::graph::{Error, GraphController, Node};
+use crate::view::ViewState;
+use gloo_events::EventListener;
+use gloo_timers::callback::Interval;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+use web_sys::Window;
+
+/// How often a running graph is checked for a changed view, in milliseconds.
+const SYNC_INTERVAL: u32 = 500;
+
+fn window() -> Result<Window, Error> {
+    web_sys::window().ok_or(Error::MissingWindow)
+}
+
+fn hash() -> Result<String, Error> {
+    Ok(window()?.location().hash()?)
+}
+
+/// The view in the fragment of the current URL.
+///
+/// An invalid view is ignored, like a missing one.
+pub fn current() -> Result<Option<ViewState>, Error> {
+    match ViewState::from_fragment(&hash()?) {
+        Ok(view) => Ok(view),
+        Err(err) => {
+            log::warn!("Ignoring an invalid view in the URL: {err}");
+            Ok(None)
+        }
+    }
+}
+
+/// Replace the view in the fragment of the current URL, without adding an entry to the history.
+pub fn replace(view: &ViewState) -> Result<(), Error> {
+    let hash = view.replace_in_fragment(&hash()?);
+    window()?
+        .history()?
+        .replace_state_with_url(&JsValue::NULL, "", Some(&hash))?;
+    Ok(())
+}
+
+/// Restore the view from the URL, and keep the URL updated while the graph runs.
+///
+/// Nodes are identified by the keys the function returns for them. The view is restored again
+/// when the fragment gets changed, e.g. by following a link. Dropping the returned handle stops
+/// updating the URL.
+pub fn sync_url_hash<F>(graph: GraphController, key: F) -> UrlHashHandle
+where
+    F: Fn(Node) -> Option<String> + 'static,
+{
+    let key = Rc::new(key);
+
+    let restore = {
+        let graph = graph.clone();
+        let key = key.clone();
+        move || match current() {
+            Ok(Some(view)) => {
+                graph.with_graph(|graph| graph.apply_view_state(view, &*key));
+            }
+            Ok(None) => {}
+            Err(err) => log::warn!("Failed to read the view from the URL: {err}"),
+        }
+    };
+    restore();
+
+    let listener = web_sys::window()
+        .map(|window| EventListener::new(&window, "hashchange", move |_| restore()));
+
+    let interval = Interval::new(SYNC_INTERVAL, move || {
+        let Some(view) = graph.with_graph(|graph| graph.view_state(&*key)) else {
+            return;
+        };
+        // compared as encoded, which rounds the viewport
+        if hash().is_ok_and(|hash| view.replace_in_fragment(&hash) == hash) {
+            return;
+        }
+        if let Err(err) = replace(&view) {
+            log::warn!("Failed to update the URL: {err}");
+        }
+    });
+
+    UrlHashHandle {
+        _listener: listener,
+        _interval: interval,
+    }
+}
+
+/// Keeps the URL updated with the view of a graph, until dropped.
+pub struct UrlHashHandle {
+    _listener: Option<EventListener>,
+    _interval: Interval,
+}
@@ -0,0 +1,295 @@
+use crate::graph::{Position, Rect, Size, MAX_SCALE, MIN_SCALE};
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+
+/// The name of the fragment parameter used by [`ViewState::from_fragment`] and
+/// [`ViewState::to_fragment`].
+pub const FRAGMENT_KEY: &str = "view";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ParseViewError {
+    #[error("invalid viewport: {0}")]
+    Viewport(String),
+    #[error("invalid node key: {0}")]
+    Node(String),
+    #[error("invalid filter: {0}")]
+    Filter(String),
+    #[error("too many sections")]
+    TooManySections,
+}
+
+/// The transformation from graph coordinates to canvas coordinates.
+///
+/// A graph position `p` is shown on the canvas at `p * scale + offset`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// The translation, in canvas pixels.
+    pub offset: Position,
+    /// The zoom level.
+    pub scale: f64,
+}
+
+impl Viewport {
+    /// Convert a position in graph coordinates to canvas coordinates.
+    pub fn to_screen(&self, position: Position) -> Position {
+        Position {
+            x: position.x * self.scale + self.offset.x,
+            y: position.y * self.scale + self.offset.y,
+        }
+    }
+
+    /// Convert a position in canvas coordinates to graph coordinates.
+    pub fn to_graph(&self, position: Position) -> Position {
+        Position {
+            x: (position.x - self.offset.x) / self.scale,
+            y: (position.y - self.offset.y) / self.scale,
+        }
+    }
+
+    /// Change the zoom level, keeping the graph position shown at a canvas position in place.
+    pub fn zoom_at(&self, position: Position, scale: f64) -> Self {
+        let anchor = self.to_graph(position);
+        Self {
+            offset: Position {
+                x: position.x - anchor.x * scale,
+                y: position.y - anchor.y * scale,
+            },
+            scale,
+        }
+    }
+
+    /// The area of the graph visible on a canvas of the provided size.
+    pub fn visible_rect(&self, size: Size) -> Rect {
+        Rect {
+            position: self.to_graph(Position { x: 0.0, y: 0.0 }),
+            size: Size {
+                width: size.width / self.scale,
+                height: size.height / self.scale,
+            },
+        }
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            offset: Position { x: 0.0, y: 0.0 },
+            scale: 1.0,
+        }
+    }
+}
+
+/// The area nodes are kept in, while the layout moves them or they get dragged.
+///
+/// Nodes are confined with their whole size, nodes larger than the area get centered.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Bounds {
+    /// Nodes can move anywhere.
+    #[default]
+    None,
+    /// The area currently visible on the canvas.
+    Canvas,
+    /// A fixed area, in graph coordinates.
+    Rect(Rect),
+}
+
+impl Bounds {
+    /// The area to keep nodes in, provided the currently visible area.
+    pub fn rect(&self, visible: Option<Rect>) -> Option<Rect> {
+        match self {
+            Self::None => None,
+            Self::Canvas => visible,
+            Self::Rect(rect) => Some(*rect),
+        }
+    }
+}
+
+/// A snapshot of what the user is looking at: viewport, selection, and hidden categories.
+///
+/// The state can be encoded into a compact, URL-safe string, e.g. for deep links like
+/// `#view=12.5,-40,1.5~a,b~foo,bar`, and parsed back. Selected nodes are identified by keys of
+/// the application, which stay the same when the nodes are loaded again, see
+/// [`crate::graph::Graph::view_state`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ViewState {
+    pub viewport: Viewport,
+    /// The keys of the selected nodes.
+    pub selection: Vec<String>,
+    /// The names of the hidden categories, see [`crate::graph::Category::visible`].
+    pub filters: Vec<String>,
+}
+
+impl ViewState {
+    /// Encode the state as a fragment parameter, like `view=...`.
+    pub fn to_fragment(&self) -> String {
+        format!("{FRAGMENT_KEY}={self}")
+    }
+
+    /// Find and parse the view state in a URL fragment, like `#view=...&other=value`.
+    ///
+    /// Returns `Ok(None)` if the fragment doesn't contain a view state.
+    pub fn from_fragment(fragment: &str) -> Result<Option<Self>, ParseViewError> {
+        fragment
+            .trim_start_matches('#')
+            .split('&')
+            .find_map(|param| {
+                param
+                    .split_once('=')
+                    .filter(|(key, _)| *key == FRAGMENT_KEY)
+                    .map(|(_, value)| value)
+            })
+            .map(Self::from_str)
+            .transpose()
+    }
+
+    /// Put the view state into a URL fragment, replacing a previous one and keeping the other
+    /// parameters.
+    pub fn replace_in_fragment(&self, fragment: &str) -> String {
+        let others = fragment.trim_start_matches('#').split('&').filter(|param| {
+            let key = param.split_once('=').map_or(*param, |(key, _)| key);
+            !param.is_empty() && key != FRAGMENT_KEY
+        });
+        let params: Vec<String> = [self.to_fragment()]
+            .into_iter()
+            .chain(others.map(String::from))
+            .collect();
+        format!("#{}", params.join("&"))
+    }
+}
+
+impl Display for ViewState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Viewport { offset, scale } = self.viewport;
+        write!(
+            f,
+            "{},{},{}",
+            round(offset.x, 100.0),
+            round(offset.y, 100.0),
+            round(scale, 1000.0)
+        )?;
+
+        if self.selection.is_empty() && self.filters.is_empty() {
+            return Ok(());
+        }
+
+        f.write_char('~')?;
+        for (i, key) in self.selection.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            percent_encode(f, key)?;
+        }
+
+        if self.filters.is_empty() {
+            return Ok(());
+        }
+
+        f.write_char('~')?;
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            percent_encode(f, filter)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for ViewState {
+    type Err = ParseViewError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sections = s.split('~');
+
+        let viewport = match sections.next() {
+            Some(viewport) if !viewport.is_empty() => parse_viewport(viewport)?,
+            _ => Viewport::default(),
+        };
+
+        let selection = sections
+            .next()
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.split(',')
+                    .map(|key| percent_decode(key).map_err(ParseViewError::Node))
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let filters = sections
+            .next()
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.split(',')
+                    .map(|filter| percent_decode(filter).map_err(ParseViewError::Filter))
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        if sections.next().is_some() {
+            return Err(ParseViewError::TooManySections);
+        }
+
+        Ok(Self {
+            viewport,
+            selection,
+            filters,
+        })
+    }
+}
+
+fn parse_viewport(s: &str) -> Result<Viewport, ParseViewError> {
+    let values = s
+        .split(',')
+        .map(f64::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ParseViewError::Viewport(s.to_string()))?;
+
+    match values.as_slice() {
+        [x, y, scale] if x.is_finite() && y.is_finite() && scale.is_finite() && *scale > 0.0 => {
+            Ok(Viewport {
+                offset: Position { x: *x, y: *y },
+                scale: scale.clamp(MIN_SCALE, MAX_SCALE),
+            })
+        }
+        _ => Err(ParseViewError::Viewport(s.to_string())),
+    }
+}
+
+fn round(value: f64, factor: f64) -> f64 {
+    (value * factor).round() / factor
+}
+
+fn percent_encode(f: &mut Formatter<'_>, value: &str) -> std::fmt::Result {
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                f.write_char(b as char)?
+            }
+            _ => write!(f, "%{b:02X}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Decode a percent-encoded value, returning the value itself if it's invalid.
+fn percent_decode(value: &str) -> Result<String, String> {
+    let err = || value.to_string();
+
+    let mut result = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next().ok_or_else(err)?, bytes.next().ok_or_else(err)?];
+            let hex = std::str::from_utf8(&hex).map_err(|_| err())?;
+            result.push(u8::from_str_radix(hex, 16).map_err(|_| err())?);
+        } else {
+            result.push(b);
+        }
+    }
+
+    String::from_utf8(result).map_err(|_| err())
+}